@@ -0,0 +1,195 @@
+// src/arrow_stream.rs
+//! Column sources for Apache Arrow IPC (feature `arrow`) and Parquet (feature `parquet`).
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Float64Type;
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType};
+
+use crate::error::{DataError, Location};
+use crate::nulls::NullPolicy;
+use crate::stream::ScalarStream;
+
+/// Default number of rows per record batch.
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// A `ScalarStream` over one numeric column of a sequence of Arrow record batches.
+///
+/// Batches are pulled one at a time from the underlying reader, so memory is
+/// bounded by one batch (plus, for Parquet, the row group column chunk being decoded).
+/// Integer, float and decimal columns are cast to `f64`; other types are a
+/// [`DataError::TypeMismatch`]. Nulls are handled according to a [`NullPolicy`].
+///
+/// Use the type aliases [`ParquetScalarStream`] and [`ArrowIpcScalarStream`] to open files.
+pub struct ArrowScalarStream<I> {
+    batches: I,
+    column: String,
+    nulls: NullPolicy,
+    current: Option<ArrayRef>,
+    pos: usize,
+    row: u64,
+    path: Option<PathBuf>,
+}
+
+/// Parquet column stream; row groups are read lazily, only the selected column is decoded.
+#[cfg(feature = "parquet")]
+pub type ParquetScalarStream = ArrowScalarStream<parquet::arrow::arrow_reader::ParquetRecordBatchReader>;
+
+/// Arrow IPC file (`.arrow`/Feather v2) column stream.
+pub type ArrowIpcScalarStream = ArrowScalarStream<FileReader<BufReader<File>>>;
+
+impl<I> ArrowScalarStream<I>
+where
+    I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+{
+    /// Streams `column` from any iterator of record batches.
+    ///
+    /// # Arguments
+    /// * `batches` – e.g. an Arrow IPC `StreamReader` or a `ParquetRecordBatchReader`.
+    /// * `column` – name of the numeric column.
+    /// * `nulls` – what to do with null values.
+    pub fn from_batches(batches: I, column: impl Into<String>, nulls: NullPolicy) -> Self {
+        Self { batches, column: column.into(), nulls, current: None, pos: 0, row: 0, path: None }
+    }
+
+    fn loc(&self) -> Location {
+        Location { path: self.path.clone(), ..Location::default() }.with_column(self.column.clone())
+    }
+
+    /// Selects the column from a batch and casts it to `Float64`.
+    fn load(&self, batch: &RecordBatch) -> Result<ArrayRef, DataError> {
+        let loc = self.loc().with_record(self.row + 1);
+        let col = batch.column_by_name(&self.column)
+            .ok_or_else(|| DataError::missing_key(loc.clone(), self.column.clone()))?;
+        let numeric = col.data_type().is_numeric() || *col.data_type() == DataType::Null;
+        if !numeric {
+            return Err(DataError::TypeMismatch { loc, expected: "numeric column", found: col.data_type().to_string() });
+        }
+        arrow_cast::cast(col, &DataType::Float64).map_err(|e| arrow_error(e, loc))
+    }
+}
+
+impl ArrowIpcScalarStream {
+    /// Opens an Arrow IPC file and streams `column`.
+    pub fn from_path(path: impl AsRef<Path>, column: impl Into<String>, nulls: NullPolicy) -> Result<Self, DataError> {
+        let path = path.as_ref();
+        let loc = Location::default().with_path(path);
+        let file = File::open(path).map_err(|e| DataError::io(loc.clone(), e))?;
+        let reader = FileReader::try_new(BufReader::new(file), None).map_err(|e| arrow_error(e, loc))?;
+        let mut s = Self::from_batches(reader, column, nulls);
+        s.path = Some(path.to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: Read> ArrowScalarStream<StreamReader<BufReader<R>>> {
+    /// Streams `column` from Arrow IPC stream-format bytes (e.g. stdin or a socket).
+    pub fn from_ipc_stream(reader: R, column: impl Into<String>, nulls: NullPolicy) -> Result<Self, DataError> {
+        let reader = StreamReader::try_new_buffered(reader, None).map_err(|e| arrow_error(e, Location::default()))?;
+        Ok(Self::from_batches(reader, column, nulls))
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetScalarStream {
+    /// Opens a Parquet file and streams `column`, decoding only that column.
+    ///
+    /// # Arguments
+    /// * `path` – path to the Parquet file.
+    /// * `column` – name of a top-level numeric column.
+    /// * `nulls` – what to do with null values.
+    /// * `batch_size` – rows per decoded batch (see [`DEFAULT_BATCH_SIZE`]).
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        column: impl Into<String>,
+        nulls: NullPolicy,
+        batch_size: usize,
+    ) -> Result<Self, DataError> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        use parquet::arrow::ProjectionMask;
+
+        let path = path.as_ref();
+        let column = column.into();
+        let loc = Location::default().with_path(path).with_column(column.clone());
+        let file = File::open(path).map_err(|e| DataError::io(loc.clone(), e))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| parquet_error(e, loc.clone()))?;
+        let idx = builder.schema().index_of(&column)
+            .map_err(|_| DataError::missing_key(loc.clone(), column.clone()))?;
+        let mask = ProjectionMask::roots(builder.parquet_schema(), [idx]);
+        let reader = builder
+            .with_projection(mask)
+            .with_batch_size(batch_size.max(1))
+            .build()
+            .map_err(|e| parquet_error(e, loc))?;
+        let mut s = Self::from_batches(reader, column, nulls);
+        s.path = Some(path.to_path_buf());
+        Ok(s)
+    }
+}
+
+fn arrow_error(e: ArrowError, loc: Location) -> DataError {
+    match e {
+        ArrowError::IoError(_, io) => DataError::io(loc, io),
+        e => DataError::parse(loc, e.to_string()),
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_error(e: parquet::errors::ParquetError, loc: Location) -> DataError {
+    DataError::parse(loc, e.to_string())
+}
+
+impl<I> ScalarStream for ArrowScalarStream<I>
+where
+    I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+{
+    /// Returns the next value of the column, loading the next batch when needed.
+    ///
+    /// - `Some(Ok(f64))` → value (or the `Replace` substitute for a null).
+    /// - `Some(Err(e))` → error while reading a batch, a missing/non-numeric column,
+    ///   or a null under `NullPolicy::Error`.
+    /// - `None` → all batches exhausted.
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            if let Some(arr) = &self.current {
+                if self.pos < arr.len() {
+                    let i = self.pos;
+                    self.pos += 1;
+                    self.row += 1;
+                    if arr.is_null(i) {
+                        match self.nulls {
+                            NullPolicy::Skip => continue,
+                            NullPolicy::Replace(v) => return Some(Ok(v)),
+                            NullPolicy::Error => {
+                                let loc = self.loc().with_record(self.row);
+                                return Some(Err(DataError::missing_key(loc, format!("{} (null)", self.column))));
+                            }
+                        }
+                    }
+                    return Some(Ok(arr.as_primitive::<Float64Type>().value(i)));
+                }
+                self.current = None;
+            }
+            let batch = match self.batches.next()? {
+                Ok(batch) => batch,
+                Err(e) => return Some(Err(arrow_error(e, self.loc().with_record(self.row + 1)))),
+            };
+            match self.load(&batch) {
+                Ok(arr) => {
+                    self.current = Some(arr);
+                    self.pos = 0;
+                }
+                Err(e) => {
+                    // Skip the whole batch so the row numbers stay correct.
+                    self.row += batch.num_rows() as u64;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
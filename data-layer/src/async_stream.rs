@@ -0,0 +1,375 @@
+// src/async_stream.rs
+//! Async counterparts of the scalar streams and stream queries (feature `async`).
+//!
+//! An [`AsyncScalarStream`] is any `futures` [`Stream`] of `Result<f64, DataError>`.
+//! The sources here read from tokio `AsyncRead`s and mirror their blocking
+//! versions (same parsing, errors and locations), so queries give the same
+//! exact results in both worlds:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), data_layer::DataError> {
+//! use data_layer::async_stream::{sum_stream_async, AsyncNdjsonStream};
+//! use data_layer::stream_queries::BoundedF64;
+//!
+//! let s = AsyncNdjsonStream::from_path("metrics.ndjson", "v").await?;
+//! let (sum, n) = sum_stream_async(s, BoundedF64::new(0.0, 100.0)).await?;
+//! # Ok(()) }
+//! ```
+//!
+//! [`SyncToAsync`] and [`AsyncToSync`] bridge to and from [`ScalarStream`] for
+//! sources that only exist on one side. Compressed input is not decoded here;
+//! use the blocking source behind [`SyncToAsync`] for it.
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader, Lines};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+
+use crate::csv_stream::{CsvColumn, CsvOptions};
+use crate::error::{DataError, Location};
+use crate::json_stream::{json_error, Extractor, FanOutStats};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{Aggregate, BoundedF64, CountState, HistogramState, SumState};
+
+/// A stream of values that is polled instead of blocking; see the [module docs](self).
+pub trait AsyncScalarStream: Stream<Item = Result<f64, DataError>> {}
+
+impl<T: Stream<Item = Result<f64, DataError>> + ?Sized> AsyncScalarStream for T {}
+
+/// The next item of `s`.
+pub async fn next_val_async<S: AsyncScalarStream + Unpin>(s: &mut S) -> Option<Result<f64, DataError>> {
+    std::future::poll_fn(|cx| Pin::new(&mut *s).poll_next(cx)).await
+}
+
+/* ----------------------------- NDJSON ----------------------------- */
+
+/// Async version of [`NdjsonScalarStream`](crate::json_stream::NdjsonScalarStream).
+pub struct AsyncNdjsonStream<R> {
+    lines: Lines<BufReader<R>>,
+    extractor: Extractor,
+    path: Option<PathBuf>,
+    line: u64,
+}
+
+impl AsyncNdjsonStream<tokio::fs::File> {
+    /// Opens an (uncompressed) NDJSON file.
+    ///
+    /// # Arguments
+    /// * `path` – path to the NDJSON file.
+    /// * `key_path` – key path to extract from each JSON object (see [`json_path`](crate::json_path)).
+    pub async fn from_path(path: impl AsRef<Path>, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        let loc = Location::default().with_path(path.as_ref());
+        let file = tokio::fs::File::open(path.as_ref()).await.map_err(|e| DataError::io(loc, e))?;
+        let mut s = Self::new(file, key_path)?;
+        s.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncNdjsonStream<R> {
+    /// Creates a stream over any async reader; buffered internally.
+    ///
+    /// Fails only if `key_path` is not a valid path.
+    pub fn new(reader: R, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            extractor: Extractor::new(key_path.as_ref())?,
+            path: None,
+            line: 0,
+        })
+    }
+
+    /// Caps the number of values a fan-out path yields per record (the first `k` matches).
+    pub fn max_per_record(mut self, k: usize) -> Self { self.extractor.cap = Some(k); self }
+
+    /// Per-record contribution counts so far; final once the stream has ended.
+    pub fn fan_out(&self) -> &FanOutStats { &self.extractor.stats }
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncNdjsonStream<R> {
+    type Item = Result<f64, DataError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.extractor.pending.pop_front() {
+                return Poll::Ready(Some(item));
+            }
+            let loc = Location { path: this.path.clone(), ..Location::default() }.with_line(this.line + 1);
+            let line = match ready!(Pin::new(&mut this.lines).poll_next_line(cx)) {
+                Ok(Some(line)) => line,
+                Ok(None) => return Poll::Ready(None),
+                Err(e) => return Poll::Ready(Some(Err(DataError::io(loc, e)))),
+            };
+            this.line += 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(line) {
+                Ok(v) => this.extractor.push(&v, loc),
+                Err(e) => return Poll::Ready(Some(Err(json_error(e, loc)))),
+            }
+        }
+    }
+}
+
+/* ----------------------------- CSV ----------------------------- */
+
+/// Async version of [`CsvScalarStream::from_path_with`](crate::csv_stream::CsvScalarStream::from_path_with)
+/// (RFC 4180 parsing).
+///
+/// A column selected by name is resolved when the header row arrives; if it is
+/// missing, the first item is a [`DataError::MissingKey`] and the stream ends.
+pub struct AsyncCsvStream<R> {
+    reader: BufReader<R>,
+    core: csv_core::Reader,
+    column: Option<usize>,
+    name: Option<String>,
+    label: String,
+    header_pending: bool,
+    out: Vec<u8>,
+    ends: Vec<usize>,
+    nout: usize,
+    nend: usize,
+    record_line: Option<u64>,
+    records: u64,
+    path: Option<PathBuf>,
+    done: bool,
+}
+
+impl AsyncCsvStream<tokio::fs::File> {
+    /// Opens an (uncompressed) CSV file.
+    ///
+    /// # Arguments
+    /// * `path` – path to the CSV file.
+    /// * `opts` – column, delimiter, quote and header settings.
+    pub async fn from_path(path: impl AsRef<Path>, opts: CsvOptions) -> Result<Self, DataError> {
+        let loc = Location::default().with_path(path.as_ref());
+        let file = tokio::fs::File::open(path.as_ref()).await.map_err(|e| DataError::io(loc, e))?;
+        let mut s = Self::new(file, opts).map_err(|e| e.with_path(path.as_ref()))?;
+        s.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncCsvStream<R> {
+    /// Creates a stream over any async reader; buffered internally.
+    ///
+    /// Fails only if the column is selected by name without a header row.
+    pub fn new(reader: R, opts: CsvOptions) -> Result<Self, DataError> {
+        let (column, name, label) = match opts.column {
+            CsvColumn::Index(i) => (Some(i), None, i.to_string()),
+            CsvColumn::Name(name) => {
+                if !opts.has_header {
+                    return Err(DataError::schema(
+                        Location::default().with_column(name),
+                        "column selected by name, but no header row is configured",
+                    ));
+                }
+                (None, Some(name.clone()), name)
+            }
+        };
+        Ok(Self {
+            reader: BufReader::new(reader),
+            core: csv_core::ReaderBuilder::new().delimiter(opts.delimiter).quote(opts.quote).build(),
+            column,
+            name,
+            label,
+            header_pending: opts.has_header,
+            out: vec![0; 1024],
+            ends: vec![0; 16],
+            nout: 0,
+            nend: 0,
+            record_line: None,
+            records: 0,
+            path: None,
+            done: false,
+        })
+    }
+
+    /// Handles one complete record; `None` if it yields no item.
+    fn on_record(&mut self, line: u64) -> Option<Result<f64, DataError>> {
+        let (nout, nend) = (std::mem::take(&mut self.nout), std::mem::take(&mut self.nend));
+        let fields = &self.ends[..nend];
+        let field = |i: usize| &self.out[if i == 0 { 0 } else { fields[i - 1] }..fields[i].min(nout)];
+        let loc = Location { path: self.path.clone(), ..Location::default() }.with_line(line);
+
+        if std::mem::take(&mut self.header_pending) {
+            let name = self.name.as_ref()?;
+            let found = (0..nend).position(|i| std::str::from_utf8(field(i)).map(str::trim) == Ok(name.as_str()));
+            return match found {
+                Some(i) => { self.column = Some(i); None }
+                None => {
+                    self.done = true;
+                    Some(Err(DataError::missing_key(loc, name.clone())))
+                }
+            };
+        }
+
+        self.records += 1;
+        let column = self.column.expect("column is resolved once the header is read");
+        let loc = loc.with_record(self.records).with_column(self.label.clone());
+        if column >= nend {
+            return Some(Err(DataError::schema(
+                loc,
+                format!("row has {} fields, column index {} is out of range", nend, column),
+            )));
+        }
+        let raw = match std::str::from_utf8(field(column)) {
+            Ok(raw) => raw.trim(),
+            Err(e) => return Some(Err(DataError::parse(loc, format!("invalid UTF-8: {}", e)))),
+        };
+        if raw.is_empty() {
+            return None;
+        }
+        Some(raw.parse::<f64>().map_err(|e| DataError::parse(loc, format!("cannot parse '{}' as f64: {}", raw, e))))
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncCsvStream<R> {
+    type Item = Result<f64, DataError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            let line = *this.record_line.get_or_insert(this.core.line());
+            let input = match ready!(Pin::new(&mut this.reader).poll_fill_buf(cx)) {
+                Ok(input) => input,
+                Err(e) => {
+                    let loc = Location { path: this.path.clone(), ..Location::default() }.with_line(line);
+                    return Poll::Ready(Some(Err(DataError::io(loc, e))));
+                }
+            };
+            let (res, nin, nout, nend) =
+                this.core.read_record(input, &mut this.out[this.nout..], &mut this.ends[this.nend..]);
+            Pin::new(&mut this.reader).consume(nin);
+            this.nout += nout;
+            this.nend += nend;
+            match res {
+                csv_core::ReadRecordResult::InputEmpty => {}
+                csv_core::ReadRecordResult::OutputFull => this.out.resize(this.out.len() * 2, 0),
+                csv_core::ReadRecordResult::OutputEndsFull => this.ends.resize(this.ends.len() * 2, 0),
+                csv_core::ReadRecordResult::Record => {
+                    this.record_line = None;
+                    if let Some(item) = this.on_record(line) {
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                csv_core::ReadRecordResult::End => this.done = true,
+            }
+        }
+    }
+}
+
+/* ----------------------------- bridges ----------------------------- */
+
+/// Runs a blocking [`ScalarStream`] on its own thread and forwards its items
+/// through a bounded channel, so a slow consumer holds the producer back.
+///
+/// The thread ends with the stream, or at the next item after the bridge is dropped.
+pub struct SyncToAsync {
+    rx: mpsc::Receiver<Result<f64, DataError>>,
+}
+
+impl SyncToAsync {
+    /// # Arguments
+    /// * `s` – the blocking stream.
+    /// * `buffer` – number of items buffered between the thread and the consumer (at least 1).
+    pub fn new<S: ScalarStream + Send + 'static>(mut s: S, buffer: usize) -> Self {
+        let (tx, rx) = mpsc::channel(buffer.max(1));
+        std::thread::spawn(move || {
+            while let Some(item) = s.next_val() {
+                if tx.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { rx }
+    }
+}
+
+impl Stream for SyncToAsync {
+    type Item = Result<f64, DataError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Drives an [`AsyncScalarStream`] from blocking code on the given tokio runtime.
+///
+/// `next_val` blocks the calling thread, so it must not be called from async
+/// code; use it from a plain thread or inside `spawn_blocking`.
+pub struct AsyncToSync<S> {
+    stream: S,
+    handle: Handle,
+}
+
+impl<S: AsyncScalarStream + Unpin> AsyncToSync<S> {
+    pub fn new(stream: S, handle: Handle) -> Self { Self { stream, handle } }
+}
+
+impl<S: AsyncScalarStream + Unpin> ScalarStream for AsyncToSync<S> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        self.handle.block_on(next_val_async(&mut self.stream))
+    }
+}
+
+/* ----------------------------- queries ----------------------------- */
+
+/// Async version of [`Aggregate::feed`]: consumes up to `max` values of `s`;
+/// returns `true` once `s` is exhausted.
+pub async fn feed_async<A: Aggregate, S: AsyncScalarStream + Unpin>(
+    state: &mut A,
+    s: &mut S,
+    max: usize,
+) -> Result<bool, DataError> {
+    for _ in 0..max {
+        match next_val_async(s).await {
+            Some(val) => state.push(val?),
+            None => return Ok(true),
+        }
+    }
+    Ok(false)
+}
+
+/// Async version of [`count_stream`](crate::stream_queries::count_stream).
+pub async fn count_stream_async<S: AsyncScalarStream + Unpin>(mut s: S) -> Result<usize, DataError> {
+    let mut state = CountState::new();
+    feed_async(&mut state, &mut s, usize::MAX).await?;
+    Ok(state.result())
+}
+
+/// Async version of [`sum_stream`](crate::stream_queries::sum_stream).
+pub async fn sum_stream_async<S: AsyncScalarStream + Unpin>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+    let mut state = SumState::new(dom);
+    feed_async(&mut state, &mut s, usize::MAX).await?;
+    Ok(state.result())
+}
+
+/// Async version of [`mean_stream`](crate::stream_queries::mean_stream).
+pub async fn mean_stream_async<S: AsyncScalarStream + Unpin>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+    let mut state = SumState::new(dom);
+    feed_async(&mut state, &mut s, usize::MAX).await?;
+    Ok(state.mean())
+}
+
+/// Async version of [`histogram_stream`](crate::stream_queries::histogram_stream).
+pub async fn histogram_stream_async<S: AsyncScalarStream + Unpin>(
+    mut s: S,
+    dom: BoundedF64,
+    bins: usize,
+) -> Result<Vec<(f64, f64, usize)>, DataError> {
+    let mut state = HistogramState::new(dom, bins);
+    feed_async(&mut state, &mut s, usize::MAX).await?;
+    Ok(state.result())
+}
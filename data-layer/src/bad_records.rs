@@ -0,0 +1,146 @@
+//! Policies for malformed records in scalar sources.
+//!
+//! By default every source yields a malformed record as `Some(Err(..))`, and
+//! query functions such as [`sum_stream`](crate::stream_queries::sum_stream)
+//! abort on the first one. Wrapping a source in a [`BadRecordFilter`] lets a job
+//! choose fail-fast, skip-and-count or quarantine-to-file instead.
+//!
+//! # Privacy note
+//! Dropping records changes the dataset the query runs on, so the policy must be
+//! **data-independent**: choose it (and the quarantine path) before looking at the
+//! data, never in reaction to how many records were rejected. Under a fixed policy
+//! each input record maps to at most one output record, independently of all other
+//! records, so the sensitivities in `stream_queries` still hold.
+//!
+//! The [`BadRecordReport`] counts and the quarantine file are computed from the
+//! private data itself. They are for operators, not for release: publishing them
+//! requires its own DP mechanism and budget.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{DataError, DataErrorKind, Location};
+use crate::stream::ScalarStream;
+
+/// What to do with a record whose content cannot be turned into an `f64`.
+///
+/// Only record-level errors (parse, missing key, type mismatch, schema) are
+/// subject to the policy. I/O and other errors always pass through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BadRecordPolicy {
+    /// Pass the error through, so the consumer aborts on the first bad record.
+    Fail,
+    /// Drop bad records and count them.
+    Skip,
+    /// Drop bad records, count them, and append one line per record to a file.
+    Quarantine(PathBuf),
+}
+
+/// Counts of records rejected by a [`BadRecordFilter`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BadRecordReport {
+    /// Number of good values passed through.
+    pub accepted: u64,
+    /// Number of records dropped.
+    pub rejected: u64,
+    /// Dropped records per error kind.
+    pub by_kind: BTreeMap<DataErrorKind, u64>,
+    /// Location of the first dropped record, for debugging.
+    pub first: Option<Location>,
+}
+
+/// Applies a [`BadRecordPolicy`] to an upstream `ScalarStream`.
+///
+/// Consumers take streams by value; pass `&mut filter` to keep access to the
+/// report after the query has run:
+///
+/// ```no_run
+/// # use data_layer::bad_records::{BadRecordFilter, BadRecordPolicy};
+/// # use data_layer::json_stream::NdjsonScalarStream;
+/// # use data_layer::stream_queries::{sum_stream, BoundedF64};
+/// let src = NdjsonScalarStream::from_path("events.ndjson", "v").unwrap();
+/// let mut filtered = BadRecordFilter::new(src, BadRecordPolicy::Skip).unwrap();
+/// let (sum, n) = sum_stream(&mut filtered, BoundedF64::new(0.0, 10.0)).unwrap();
+/// eprintln!("rejected {} records", filtered.report().rejected);
+/// ```
+pub struct BadRecordFilter<S> {
+    src: S,
+    policy: BadRecordPolicy,
+    quarantine: Option<BufWriter<File>>,
+    report: BadRecordReport,
+}
+
+impl<S> BadRecordFilter<S> {
+    /// Wraps `src`. For [`BadRecordPolicy::Quarantine`] the file is created (or truncated) here.
+    pub fn new(src: S, policy: BadRecordPolicy) -> Result<Self, DataError> {
+        let quarantine = match &policy {
+            BadRecordPolicy::Quarantine(path) => Some(BufWriter::new(
+                File::create(path).map_err(|e| DataError::io(Location::default().with_path(path), e))?,
+            )),
+            _ => None,
+        };
+        Ok(Self { src, policy, quarantine, report: BadRecordReport::default() })
+    }
+
+    pub fn policy(&self) -> &BadRecordPolicy { &self.policy }
+
+    /// Counts so far; final once the stream has returned `None`.
+    pub fn report(&self) -> &BadRecordReport { &self.report }
+
+    pub fn into_report(self) -> BadRecordReport { self.report }
+
+    fn quarantine_path(&self) -> Option<&Path> {
+        match &self.policy {
+            BadRecordPolicy::Quarantine(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Records a dropped error and writes it to the quarantine file, if any.
+    fn reject(&mut self, e: &DataError) -> Result<(), DataError> {
+        self.report.rejected += 1;
+        *self.report.by_kind.entry(e.kind()).or_insert(0) += 1;
+        if self.report.first.is_none() {
+            self.report.first = e.location().cloned();
+        }
+        if let Some(w) = self.quarantine.as_mut() {
+            if let Err(io) = writeln!(w, "{}", e) {
+                let loc = Location { path: self.quarantine_path().map(Path::to_path_buf), ..Location::default() };
+                return Err(DataError::io(loc, io));
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), DataError> {
+        if let Some(w) = self.quarantine.as_mut() {
+            if let Err(io) = w.flush() {
+                let loc = Location { path: self.quarantine_path().map(Path::to_path_buf), ..Location::default() };
+                return Err(DataError::io(loc, io));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: ScalarStream> ScalarStream for BadRecordFilter<S> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            match self.src.next_val() {
+                None => return self.flush().err().map(Err),
+                Some(Ok(v)) => {
+                    self.report.accepted += 1;
+                    return Some(Ok(v));
+                }
+                Some(Err(e)) if self.policy != BadRecordPolicy::Fail && e.is_record_error() => {
+                    if let Err(io) = self.reject(&e) {
+                        return Some(Err(io));
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// src/checkpoint.rs
+//! Checkpoint and resume for long-running stream queries.
+//!
+//! A job pairs the aggregation state of a query (e.g. [`SumState`](crate::stream_queries::SumState))
+//! with the [`SourcePosition`] of its input, saves both periodically and, after a
+//! restart, reopens the input at that position and continues with the saved state:
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//!
+//! let dom = BoundedF64::new(0.0, 100.0);
+//! let (mut s, mut state) = match Checkpoint::<SumState>::load("job.ckpt").unwrap() {
+//!     Some(ck) => (NdjsonScalarStream::resume("big.ndjson", "v", &ck.source).unwrap(), ck.state),
+//!     None => (NdjsonScalarStream::from_path("big.ndjson", "v").unwrap(), SumState::new(dom)),
+//! };
+//! while !state.feed(&mut s, 1_000_000).unwrap() {
+//!     Checkpoint::new(state.clone(), s.position()).save("job.ckpt").unwrap();
+//! }
+//! let (sum, n) = state.result();
+//! ```
+//!
+//! States hold exact, noise-free aggregates: noise is added once, when the final
+//! state is released by a mechanism. A checkpoint is as sensitive as the data it
+//! summarizes and must be stored accordingly; releasing two copies of one state
+//! spends the privacy budget twice.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DataError, Location};
+
+/// Position of a stream in its input, taken between two values.
+///
+/// `offset` counts bytes of the decompressed input. `line` and `record` are the
+/// lines and records consumed before `offset` and keep error locations of a
+/// resumed stream aligned with the file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePosition {
+    pub offset: u64,
+    pub line: u64,
+    pub record: u64,
+    /// Values of the record at `offset` that were already consumed (fan-out paths).
+    #[serde(default)]
+    pub skip: u64,
+}
+
+/// A stream that can report its [`SourcePosition`].
+pub trait Resumable {
+    /// The position after the last value returned by `next_val`; reopening the
+    /// input there yields exactly the values not returned yet.
+    fn position(&self) -> SourcePosition;
+}
+
+/// Aggregation state and source position, saved together.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint<T> {
+    pub state: T,
+    pub source: SourcePosition,
+}
+
+impl<T> Checkpoint<T> {
+    pub fn new(state: T, source: SourcePosition) -> Self { Self { state, source } }
+}
+
+impl<T: Serialize + DeserializeOwned> Checkpoint<T> {
+    /// Writes the checkpoint as JSON. The file is written to a temporary file,
+    /// synced to disk and renamed over `path` (on unix the directory is synced
+    /// too), so a crash or power loss while saving leaves the previous
+    /// checkpoint intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DataError> {
+        let path = path.as_ref();
+        let loc = Location::default().with_path(path);
+        let json = serde_json::to_vec(self).map_err(DataError::other)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let write = || -> io::Result<()> {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&json)?;
+            file.sync_all()?;
+            fs::rename(&tmp, path)?;
+            #[cfg(unix)]
+            {
+                let dir = match path.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir,
+                    _ => Path::new("."),
+                };
+                File::open(dir)?.sync_all()?;
+            }
+            Ok(())
+        };
+        write().map_err(|e| DataError::io(loc, e))
+    }
+
+    /// Reads a checkpoint written by [`save`](Self::save); `Ok(None)` if there is none yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>, DataError> {
+        let path = path.as_ref();
+        let loc = Location::default().with_path(path);
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| DataError::parse(loc, format!("invalid checkpoint: {}", e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(DataError::io(loc, e)),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// src/compression.rs
+//! Transparent decompression for file-backed streams.
+//!
+//! The `from_path` constructors of all sources open files through [`open_path`],
+//! which detects gzip, zstd and bzip2 input and decompresses while streaming.
+//! Each codec sits behind a cargo feature (`gzip`, `zstd`, `bzip2`) so the
+//! default build stays lean; compressed input without the matching feature is
+//! reported as an `Unsupported` I/O error.
+
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::error::{DataError, Location};
+
+/// Compression format of an input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the format from the first bytes of the input.
+    pub fn from_magic(magic: &[u8]) -> Self {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if magic.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Detects the format from the file extension (`.gz`, `.zst`, `.bz2`, ...).
+    pub fn from_extension(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            Some("bz2" | "bzip2") => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    fn feature(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+}
+
+/// Opens a file, detecting compression from its magic bytes. Returns a reader
+/// over the decompressed bytes.
+///
+/// The content wins over the name: a plain-text file called `*.gz` is read as
+/// plain text. The extension is only consulted for files too short to hold a
+/// magic number.
+pub fn open_path(path: &Path) -> Result<Box<dyn Read + Send>, DataError> {
+    let loc = || Location::default().with_path(path);
+    let (file, magic, compression) = open_detect(path)?;
+    // Put the peeked bytes back in front of the rest of the file.
+    let raw = Cursor::new(magic).chain(file);
+    decompress(raw, compression).map_err(|e| DataError::io(loc(), e))
+}
+
+/// Like [`open_path`], but positioned at `offset` bytes of the decompressed input.
+///
+/// Uncompressed files are seeked; compressed input is decoded and skipped up to
+/// `offset`. An `offset` past the end of the input is an `UnexpectedEof` error.
+pub fn open_path_at(path: &Path, offset: u64) -> Result<Box<dyn Read + Send>, DataError> {
+    let loc = || Location::default().with_path(path);
+    let eof = |len: u64| DataError::io(loc(), io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("input has {} bytes, cannot resume at offset {}", len, offset),
+    ));
+    let (mut file, magic, compression) = open_detect(path)?;
+    if compression == Compression::None {
+        let len = file.metadata().map_err(|e| DataError::io(loc(), e))?.len();
+        if offset > len {
+            return Err(eof(len));
+        }
+        file.seek(SeekFrom::Start(offset)).map_err(|e| DataError::io(loc(), e))?;
+        return Ok(Box::new(file));
+    }
+    let mut reader = decompress(Cursor::new(magic).chain(file), compression).map_err(|e| DataError::io(loc(), e))?;
+    let skipped = io::copy(&mut reader.by_ref().take(offset), &mut io::sink()).map_err(|e| DataError::io(loc(), e))?;
+    if skipped < offset {
+        return Err(eof(skipped));
+    }
+    Ok(reader)
+}
+
+/// Opens `path` and detects its compression. Returns the file positioned after
+/// the peeked magic bytes, those bytes, and the format.
+pub(crate) fn open_detect(path: &Path) -> Result<(File, Vec<u8>, Compression), DataError> {
+    let loc = || Location::default().with_path(path);
+    let mut file = File::open(path).map_err(|e| DataError::io(loc(), e))?;
+
+    let mut magic = [0u8; 4];
+    let mut len = 0;
+    while len < magic.len() {
+        match file.read(&mut magic[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(DataError::io(loc(), e)),
+        }
+    }
+
+    // Trust the magic bytes; fall back to the extension only if there were
+    // too few bytes to tell.
+    let compression = match Compression::from_magic(&magic[..len]) {
+        Compression::None if len < magic.len() => Compression::from_extension(path),
+        c => c,
+    };
+    Ok((file, magic[..len].to_vec(), compression))
+}
+
+/// Wraps `reader` in a streaming decoder for `compression`.
+pub fn decompress(reader: impl Read + Send + 'static, compression: Compression) -> io::Result<Box<dyn Read + Send>> {
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(reader)?)),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(reader))),
+        #[allow(unreachable_patterns)]
+        other => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{:?} input requires the `{}` feature of data-layer", other, other.feature()),
+        )),
+    }
+}
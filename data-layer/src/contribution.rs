@@ -0,0 +1,348 @@
+// src/contribution.rs
+//! Privacy-unit aware streams and per-unit contribution bounding.
+//!
+//! The sensitivities in [`stream_queries`](crate::stream_queries) assume that
+//! every record belongs to a different individual. Event logs break that
+//! assumption: one user emits many rows. This module attaches a privacy-unit
+//! identifier (user id, device id, ...) to every value and bounds what a single
+//! unit can contribute:
+//!
+//! - at most [`max_rows`](ContributionBounds::max_rows) rows per unit, chosen
+//!   first-k or by uniform reservoir sampling ([`Selection`]);
+//! - optionally, at most [`max_total`](ContributionBounds::max_total) in absolute
+//!   value for the sum of a unit's clamped values.
+//!
+//! The `l1_sens_*_unit*` functions derive sensitivities from those caps, so a
+//! mechanism calibrated with them is DP at the unit level rather than the event level.
+//! For sums the neighbouring relation matters: use
+//! [`l1_sens_sum_unit_add_remove`] with the mechanisms crate (neighbours differ
+//! by one unit's rows) and [`l1_sens_sum_unit_replace`] when a unit's rows are
+//! replaced by another's. The two differ once the domain contains 0 in its interior.
+//!
+//! # Example
+//! ```no_run
+//! use data_layer::contribution::{l1_sens_sum_unit_add_remove, ContributionBounder, ContributionBounds, Selection, UnitProjection};
+//! use data_layer::json_stream::NdjsonRecordStream;
+//! use data_layer::nulls::NullPolicy;
+//! use data_layer::record::RecordSchema;
+//! use data_layer::stream_queries::{sum_stream, BoundedF64};
+//!
+//! let dom = BoundedF64::new(0.0, 100.0);
+//! let bounds = ContributionBounds::rows(5).unwrap().max_total(200.0).unwrap();
+//! let events = NdjsonRecordStream::from_path("events.ndjson", RecordSchema::new().str("user").num("amount")).unwrap();
+//! let pairs = UnitProjection::new(events, "user", "amount", NullPolicy::Skip).unwrap();
+//! let bounded = ContributionBounder::new(pairs, dom, bounds, Selection::FirstK).unwrap();
+//! let (sum, _) = sum_stream(bounded, dom).unwrap();
+//! let sensitivity = l1_sens_sum_unit_add_remove(dom, &bounds); // 200.0 instead of 100.0 * 5
+//! ```
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{DataError, Location};
+use crate::nulls::NullPolicy;
+use crate::record::{Field, FieldType, RecordStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::BoundedF64;
+
+/// Trait for a stream of `(privacy unit, value)` pairs.
+pub trait UnitStream {
+    fn next_unit_val(&mut self) -> Option<Result<(String, f64), DataError>>;
+}
+
+impl<U: UnitStream + ?Sized> UnitStream for &mut U {
+    fn next_unit_val(&mut self) -> Option<Result<(String, f64), DataError>> {
+        (**self).next_unit_val()
+    }
+}
+
+impl<U: UnitStream + ?Sized> UnitStream for Box<U> {
+    fn next_unit_val(&mut self) -> Option<Result<(String, f64), DataError>> {
+        (**self).next_unit_val()
+    }
+}
+
+/// Reads a privacy-unit column and a numeric column from a [`RecordStream`].
+///
+/// The unit field may be of any type; its text form is the unit key. A record
+/// with a null unit is a [`DataError::MissingKey`]: it cannot be attributed to
+/// anyone, so it cannot be bounded. Null values follow the [`NullPolicy`].
+pub struct UnitProjection<R> {
+    src: R,
+    unit: usize,
+    value: usize,
+    nulls: NullPolicy,
+}
+
+impl<R: RecordStream> UnitProjection<R> {
+    /// Fails if either column is not in the schema, or `value` is a string field.
+    pub fn new(src: R, unit: &str, value: &str, nulls: NullPolicy) -> Result<Self, DataError> {
+        let schema = src.schema();
+        let unit = schema.index_of(unit)
+            .ok_or_else(|| DataError::missing_key(Location::default().with_column(unit), unit))?;
+        let loc = Location::default().with_column(value);
+        let value = schema.index_of(value).ok_or_else(|| DataError::missing_key(loc.clone(), value))?;
+        let ty = schema.fields()[value].ty;
+        if ty == FieldType::Str {
+            return Err(DataError::TypeMismatch { loc, expected: "number or timestamp", found: ty.name().into() });
+        }
+        Ok(Self { src, unit, value, nulls })
+    }
+}
+
+impl<R: RecordStream> UnitStream for UnitProjection<R> {
+    fn next_unit_val(&mut self) -> Option<Result<(String, f64), DataError>> {
+        loop {
+            let rec = match self.src.next_record()? {
+                Ok(rec) => rec,
+                Err(e) => return Some(Err(e)),
+            };
+            let unit_name = &rec.schema().fields()[self.unit].name;
+            let unit = match &rec.values()[self.unit] {
+                Field::Null => {
+                    let loc = rec.location().clone().with_column(unit_name.clone());
+                    return Some(Err(DataError::missing_key(loc, format!("{} (null)", unit_name))));
+                }
+                f => f.to_string(),
+            };
+            let value = match &rec.values()[self.value] {
+                Field::Null => match self.nulls {
+                    NullPolicy::Skip => continue,
+                    NullPolicy::Replace(v) => v,
+                    NullPolicy::Error => {
+                        let name = &rec.schema().fields()[self.value].name;
+                        let loc = rec.location().clone().with_column(name.clone());
+                        return Some(Err(DataError::missing_key(loc, format!("{} (null)", name))));
+                    }
+                },
+                // The schema was checked in `new`, but a record may still not follow it.
+                f => match f.as_f64() {
+                    Some(v) => v,
+                    None => {
+                        let name = &rec.schema().fields()[self.value].name;
+                        return Some(Err(DataError::TypeMismatch {
+                            loc: rec.location().clone().with_column(name.clone()),
+                            expected: "number or timestamp",
+                            found: f.type_name().into(),
+                        }));
+                    }
+                },
+            };
+            return Some(Ok((unit, value)));
+        }
+    }
+}
+
+/// Per-unit caps applied by a [`ContributionBounder`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContributionBounds {
+    /// Maximum number of rows kept per privacy unit (`k`, at least 1).
+    pub max_rows: usize,
+    /// Optional cap on `|sum|` of one unit's clamped values. Requires a
+    /// value domain that contains 0, since capping shrinks values towards 0.
+    pub max_total: Option<f64>,
+}
+
+impl ContributionBounds {
+    /// Keep at most `max_rows` rows per unit, without a cap on the per-unit total.
+    ///
+    /// Fails with [`DataError::InvalidParam`] if `max_rows` is 0.
+    pub fn rows(max_rows: usize) -> Result<Self, DataError> {
+        if max_rows == 0 {
+            return Err(DataError::InvalidParam("max_rows must be at least 1"));
+        }
+        Ok(Self { max_rows, max_total: None })
+    }
+
+    /// Additionally cap the absolute per-unit total at `max_total`.
+    ///
+    /// Fails with [`DataError::InvalidParam`] unless `max_total` is positive.
+    pub fn max_total(mut self, max_total: f64) -> Result<Self, DataError> {
+        if max_total.is_nan() || max_total <= 0.0 {
+            return Err(DataError::InvalidParam("max_total must be positive"));
+        }
+        self.max_total = Some(max_total);
+        Ok(self)
+    }
+}
+
+/// How a [`ContributionBounder`] picks the rows it keeps for a unit with too many.
+///
+/// Like the bad-record and null policies, the choice must not depend on the data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// Keep each unit's first `max_rows` rows in stream order. Streams with
+    /// memory proportional to the number of units.
+    FirstK,
+    /// Keep a uniform random sample of `max_rows` rows per unit (reservoir sampling).
+    /// Buffers up to `max_rows` values per unit and yields them once the source
+    /// is exhausted, in their original order. `seed` makes the sample reproducible.
+    Reservoir { seed: Option<u64> },
+}
+
+/// Applies [`ContributionBounds`] to a [`UnitStream`] and yields the kept values.
+///
+/// Every value is first clamped to `dom`; the per-unit total cap is applied to
+/// the clamped values in stream order, shrinking the value that would push a
+/// unit's running sum outside `[-max_total, max_total]`. Errors from the source
+/// pass through unchanged.
+pub struct ContributionBounder<U> {
+    src: U,
+    dom: BoundedF64,
+    bounds: ContributionBounds,
+    state: BounderState,
+}
+
+enum BounderState {
+    FirstK { units: HashMap<String, (usize, f64)> },
+    Reservoir { rng: Box<StdRng>, units: HashMap<String, Reservoir>, seq: u64 },
+    Draining { kept: std::vec::IntoIter<f64> },
+}
+
+/// Sample of one unit's rows: `(position in stream, clamped value)`.
+#[derive(Default)]
+struct Reservoir {
+    seen: usize,
+    rows: Vec<(u64, f64)>,
+}
+
+impl<U> ContributionBounder<U> {
+    /// Fails with [`DataError::InvalidParam`] if `bounds.max_total` is set and
+    /// `dom` does not contain 0.
+    pub fn new(src: U, dom: BoundedF64, bounds: ContributionBounds, selection: Selection) -> Result<Self, DataError> {
+        if bounds.max_total.is_some() && !(dom.min <= 0.0 && dom.max >= 0.0) {
+            return Err(DataError::InvalidParam("max_total requires a value domain that contains 0"));
+        }
+        let state = match selection {
+            Selection::FirstK => BounderState::FirstK { units: HashMap::new() },
+            Selection::Reservoir { seed } => BounderState::Reservoir {
+                rng: Box::new(seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy)),
+                units: HashMap::new(),
+                seq: 0,
+            },
+        };
+        Ok(Self { src, dom, bounds, state })
+    }
+
+    pub fn bounds(&self) -> &ContributionBounds { &self.bounds }
+}
+
+/// Shrinks `v` so that `total + v` stays within `[-cap, cap]`.
+fn cap_total(total: f64, v: f64, cap: Option<f64>) -> f64 {
+    match cap {
+        Some(c) => (total + v).clamp(-c, c) - total,
+        None => v,
+    }
+}
+
+impl<U: UnitStream> ScalarStream for ContributionBounder<U> {
+    /// Returns the next kept value.
+    ///
+    /// - `Some(Ok(f64))` → clamped value of a row within its unit's caps.
+    /// - `Some(Err(e))` → error from the source.
+    /// - `None` → source exhausted and all kept values returned.
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            match &mut self.state {
+                BounderState::FirstK { units } => {
+                    let (unit, v) = match self.src.next_unit_val()? {
+                        Ok(pair) => pair,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    let v = self.dom.clamp(v);
+                    let (rows, total) = units.entry(unit).or_insert((0, 0.0));
+                    if *rows >= self.bounds.max_rows {
+                        continue;
+                    }
+                    *rows += 1;
+                    let v = cap_total(*total, v, self.bounds.max_total);
+                    *total += v;
+                    return Some(Ok(v));
+                }
+                BounderState::Reservoir { rng, units, seq } => match self.src.next_unit_val() {
+                    Some(Ok((unit, v))) => {
+                        let v = self.dom.clamp(v);
+                        let r = units.entry(unit).or_default();
+                        r.seen += 1;
+                        if r.rows.len() < self.bounds.max_rows {
+                            r.rows.push((*seq, v));
+                        } else {
+                            let j = rng.gen_range(0..r.seen);
+                            if j < self.bounds.max_rows {
+                                r.rows[j] = (*seq, v);
+                            }
+                        }
+                        *seq += 1;
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => {
+                        let mut kept = Vec::new();
+                        for r in std::mem::take(units).into_values() {
+                            let mut rows = r.rows;
+                            rows.sort_by_key(|&(s, _)| s);
+                            let mut total = 0.0;
+                            for (s, v) in rows {
+                                let v = cap_total(total, v, self.bounds.max_total);
+                                total += v;
+                                kept.push((s, v));
+                            }
+                        }
+                        kept.sort_by_key(|&(s, _)| s);
+                        let kept: Vec<f64> = kept.into_iter().map(|(_, v)| v).collect();
+                        self.state = BounderState::Draining { kept: kept.into_iter() };
+                    }
+                },
+                BounderState::Draining { kept } => return kept.next().map(Ok),
+            }
+        }
+    }
+}
+
+/* ----------------------------- unit-level sensitivities ----------------------------- */
+
+/// COUNT sensitivity when each unit contributes at most `max_rows` rows.
+pub fn l1_sens_count_unit(bounds: &ContributionBounds) -> f64 { bounds.max_rows as f64 }
+
+/// Range `[lo, hi]` that one unit's total can take.
+///
+/// With values in `dom` and `k = max_rows`, the total lies between
+/// `min(dom.min, k·dom.min)` and `max(dom.max, k·dom.max)`, intersected with
+/// `[-max_total, max_total]`.
+fn unit_total_range(dom: BoundedF64, bounds: &ContributionBounds) -> (f64, f64) {
+    let k = bounds.max_rows as f64;
+    let (mut lo, mut hi) = (dom.min.min(k * dom.min), dom.max.max(k * dom.max));
+    if let Some(c) = bounds.max_total {
+        lo = lo.max(-c);
+        hi = hi.min(c);
+    }
+    (lo, hi)
+}
+
+/// SUM sensitivity at unit level for **replace-one** neighbours (one unit's
+/// rows swapped for another's): the width `hi - lo` of the range a unit's
+/// total can take. For `k = 1` without a total cap this equals
+/// [`l1_sens_sum`](crate::stream_queries::l1_sens_sum).
+pub fn l1_sens_sum_unit_replace(dom: BoundedF64, bounds: &ContributionBounds) -> f64 {
+    let (lo, hi) = unit_total_range(dom, bounds);
+    (hi - lo).max(0.0)
+}
+
+/// SUM sensitivity at unit level for **add/remove** neighbours (one unit's rows
+/// added or removed), the convention of the mechanisms crate: the largest
+/// absolute total a unit can have, `max(|lo|, |hi|)`, i.e. at most
+/// `max(|dom.min|, |dom.max|)·max_rows`.
+pub fn l1_sens_sum_unit_add_remove(dom: BoundedF64, bounds: &ContributionBounds) -> f64 {
+    let (lo, hi) = unit_total_range(dom, bounds);
+    lo.abs().max(hi.abs())
+}
+
+/// MEAN sensitivity at unit level for a public count `n` (replace-one
+/// neighbours, since `n` does not change).
+pub fn l1_sens_mean_unit(dom: BoundedF64, bounds: &ContributionBounds, n: usize) -> f64 {
+    if n == 0 { 0.0 } else { l1_sens_sum_unit_replace(dom, bounds) / n as f64 }
+}
+
+/// Histogram count sensitivity: a unit's `max_rows` rows may all land in different bins.
+pub fn l1_sens_hist_count_unit(bounds: &ContributionBounds) -> f64 { bounds.max_rows as f64 }
@@ -1,63 +1,455 @@
 // src/csv_stream.rs
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::checkpoint::{Resumable, SourcePosition};
+use crate::compression;
+use crate::error::{DataError, Location};
+use crate::record::{Record, RecordSchema, RecordStream};
 use crate::stream::ScalarStream;
+use crate::tail::{TailHandle, TailOptions, TailReader};
+
+/// Selects the CSV column to parse.
+#[derive(Clone, Debug)]
+pub enum CsvColumn {
+    /// 0-based column index.
+    Index(usize),
+    /// Column name as given in the header row (requires `has_header`).
+    Name(String),
+}
+
+/// Options for the RFC 4180 reader mode of [`CsvScalarStream`].
+///
+/// In this mode quoted fields may contain delimiters, `""` escapes and line breaks,
+/// and rows that are too short for the selected column are reported as errors.
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    pub column: CsvColumn,
+    pub delimiter: u8,
+    pub quote: u8,
+    pub has_header: bool,
+}
+
+impl CsvOptions {
+    /// Options for `column` with `,` as delimiter, `"` as quote and no header row.
+    pub fn new(column: CsvColumn) -> Self {
+        Self { column, delimiter: b',', quote: b'"', has_header: false }
+    }
+
+    /// Options that consume a header row and select the column by name.
+    pub fn by_name(name: impl Into<String>) -> Self {
+        Self { has_header: true, ..Self::new(CsvColumn::Name(name.into())) }
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self { self.delimiter = delimiter; self }
+    pub fn quote(mut self, quote: u8) -> Self { self.quote = quote; self }
+    pub fn has_header(mut self, has_header: bool) -> Self { self.has_header = has_header; self }
+}
+
+enum CsvMode {
+    /// Line-based: each line is split on the raw delimiter. There is no header
+    /// row; `record` counts the non-blank lines, like the RFC 4180 reader.
+    Split { reader: Box<dyn BufRead + Send>, column: usize, delimiter: u8, line: u64, record: u64, offset: u64 },
+    /// RFC 4180: quoting, escapes and multiline fields.
+    Rfc4180 {
+        reader: csv::Reader<Box<dyn Read + Send>>,
+        column: usize,
+        label: String,
+        header_rows: u64,
+        record: csv::StringRecord,
+        /// Position of the reader's first byte in the file (resumed streams).
+        base: SourcePosition,
+    },
+}
 
 /// A CSV-backed implementation of `ScalarStream`.
 ///
-/// It reads a CSV file line by line, extracts a specific column,
-/// and parses each cell into an `f64`.
+/// It reads a CSV file record by record, extracts a specific column,
+/// and parses each cell into an `f64`. Empty cells are skipped.
+///
+/// Two modes are available:
+/// - [`from_path`](Self::from_path) splits each line on the raw delimiter
+///   and skips rows that are too short.
+/// - [`from_path_with`](Self::from_path_with) parses RFC 4180 CSV, can consume a
+///   header row and select the column by name, and reports short rows as
+///   [`DataError::Schema`] instead of skipping them.
+///
+/// Errors carry the file path, line, record number and column in their [`Location`].
 pub struct CsvScalarStream {
-    reader: Box<dyn BufRead + Send>,
-    column: usize,
-    delimiter: u8,
+    mode: CsvMode,
+    path: Option<PathBuf>,
 }
 
 impl CsvScalarStream {
     /// Creates a new `CsvScalarStream` from a file path.
     ///
+    /// Compressed files are decompressed while streaming (see [`compression`]).
+    ///
+    /// # Arguments
+    /// * `path` – path to the CSV file.
+    /// * `column` – which column to parse (0-based index).
+    /// * `delimiter` – delimiter as a single byte (e.g. `b','`).
+    pub fn from_path(path: impl AsRef<Path>, column: usize, delimiter: u8) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(file, column, delimiter);
+        s.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+
+    /// Creates a new `CsvScalarStream` from any reader (stdin, in-memory buffer, socket, ...).
+    ///
+    /// # Arguments
+    /// * `reader` – source of CSV bytes; it is buffered internally.
+    /// * `column` – which column to parse (0-based index).
+    /// * `delimiter` – delimiter as a single byte (e.g. `b','`).
+    pub fn from_reader(reader: impl Read + Send + 'static, column: usize, delimiter: u8) -> Self {
+        Self {
+            mode: CsvMode::Split { reader: Box::new(BufReader::new(reader)), column, delimiter, line: 0, record: 0, offset: 0 },
+            path: None,
+        }
+    }
+
+    /// Creates a new RFC 4180 `CsvScalarStream` from a file path.
+    ///
+    /// If `opts.has_header` is set, the first record is consumed as the header row.
+    /// Selecting a column by name without a header row, or by a name that is not
+    /// in the header, is an error.
+    ///
     /// # Arguments
     /// * `path` – path to the CSV file.
+    /// * `opts` – column, delimiter, quote and header settings.
+    pub fn from_path_with(path: impl AsRef<Path>, opts: CsvOptions) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        Self::rfc4180(file, opts, Some(path.as_ref().to_path_buf()))
+    }
+
+    /// Creates a new RFC 4180 `CsvScalarStream` from any reader.
+    ///
+    /// Fails only if the header row is configured and cannot be read or
+    /// does not contain the selected column.
+    pub fn from_reader_with(reader: impl Read + Send + 'static, opts: CsvOptions) -> Result<Self, DataError> {
+        Self::rfc4180(Box::new(reader), opts, None)
+    }
+
+    /// Creates an RFC 4180 stream that follows a growing CSV file (see [`tail`](crate::tail)).
+    ///
+    /// `opts.has_header` takes precedence over `tail.header`: the header row is read
+    /// once, also when resuming from a checkpoint, and skipped in rotated files.
+    ///
+    /// # Arguments
+    /// * `path` – path to the (uncompressed) CSV file.
+    /// * `opts` – column, delimiter, quote and header settings.
+    /// * `tail` – start position and poll interval.
+    pub fn tail_with(path: impl AsRef<Path>, opts: CsvOptions, tail: TailOptions) -> Result<(Self, TailHandle), DataError> {
+        let reader = TailReader::open(path.as_ref(), tail.header(opts.has_header))?;
+        let handle = reader.handle();
+        let s = Self::rfc4180(Box::new(reader), opts, Some(path.as_ref().to_path_buf()))?;
+        Ok((s, handle))
+    }
+
+    /// Reopens a CSV file read with [`from_path`](Self::from_path) at a position
+    /// taken with [`Resumable::position`].
+    ///
+    /// # Arguments
+    /// * `path` – path to the CSV file the position was taken on.
     /// * `column` – which column to parse (0-based index).
     /// * `delimiter` – delimiter as a single byte (e.g. `b','`).
-    pub fn from_path(path: &str, column: usize, delimiter: u8) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let file = File::open(path)?;
+    /// * `pos` – where to continue (see [`checkpoint`](crate::checkpoint)).
+    pub fn resume(path: impl AsRef<Path>, column: usize, delimiter: u8, pos: &SourcePosition) -> Result<Self, DataError> {
+        let file = compression::open_path_at(path.as_ref(), pos.offset)?;
+        let mut s = Self::from_reader(file, column, delimiter);
+        s.path = Some(path.as_ref().to_path_buf());
+        if let CsvMode::Split { line, record, offset, .. } = &mut s.mode {
+            (*line, *record, *offset) = (pos.line, pos.record, pos.offset);
+        }
+        Ok(s)
+    }
+
+    /// Reopens a CSV file read with [`from_path_with`](Self::from_path_with) at a
+    /// position taken with [`Resumable::position`]. The header row, if any, is
+    /// read again from the start of the file to resolve the column.
+    ///
+    /// # Arguments
+    /// * `path` – path to the CSV file the position was taken on.
+    /// * `opts` – the same options as before.
+    /// * `pos` – where to continue (see [`checkpoint`](crate::checkpoint)).
+    pub fn resume_with(path: impl AsRef<Path>, opts: CsvOptions, pos: &SourcePosition) -> Result<Self, DataError> {
+        let s = Self::from_path_with(path.as_ref(), opts.clone())?;
+        if pos.offset == 0 {
+            return Ok(s);
+        }
+        let file = compression::open_path_at(path.as_ref(), pos.offset)?;
+        Ok(s.reopen(file, &opts, pos))
+    }
+
+    /// An RFC 4180 stream over the bytes `start..end` of an uncompressed file;
+    /// `start` must be a record boundary. Line and record numbers count from `start`.
+    pub(crate) fn chunk_with(path: &Path, opts: CsvOptions, start: u64, end: u64) -> Result<Self, DataError> {
+        let loc = Location::default().with_path(path);
+        let mut file = std::fs::File::open(path).map_err(|e| DataError::io(loc.clone(), e))?;
+        if start == 0 {
+            return Self::rfc4180(Box::new(file.take(end)), opts, Some(path.to_path_buf()));
+        }
+        let s = Self::from_path_with(path, opts.clone())?;
+        file.seek(SeekFrom::Start(start)).map_err(|e| DataError::io(loc, e))?;
+        let pos = SourcePosition { offset: start, ..SourcePosition::default() };
+        Ok(s.reopen(Box::new(file.take(end - start)), &opts, &pos))
+    }
+
+    /// Replaces the reader of a stream whose column is resolved with one that
+    /// starts at `pos`, past the header row.
+    fn reopen(mut self, inner: Box<dyn Read + Send>, opts: &CsvOptions, pos: &SourcePosition) -> Self {
+        if let CsvMode::Rfc4180 { reader, header_rows, base, .. } = &mut self.mode {
+            *reader = csv_reader(inner, opts, false);
+            *header_rows = 0;
+            *base = *pos;
+        }
+        self
+    }
+
+    fn rfc4180(inner: Box<dyn Read + Send>, opts: CsvOptions, path: Option<PathBuf>) -> Result<Self, DataError> {
+        let mut reader = csv_reader(inner, &opts, opts.has_header);
+        let loc = Location { path: path.clone(), ..Location::default() };
+        let (column, label) = match opts.column {
+            CsvColumn::Index(i) => (i, i.to_string()),
+            CsvColumn::Name(name) => {
+                if !opts.has_header {
+                    return Err(DataError::schema(
+                        loc.with_column(name),
+                        "column selected by name, but no header row is configured",
+                    ));
+                }
+                let headers = reader.headers().map_err(|e| csv_error(e, &loc))?;
+                let i = headers.iter()
+                    .position(|h| h.trim() == name)
+                    .ok_or_else(|| DataError::missing_key(loc.clone().with_line(1), name.clone()))?;
+                (i, name)
+            }
+        };
         Ok(Self {
-            reader: Box::new(BufReader::new(file)),
-            column,
-            delimiter,
+            mode: CsvMode::Rfc4180 {
+                reader,
+                column,
+                label,
+                header_rows: opts.has_header as u64,
+                record: csv::StringRecord::new(),
+                base: SourcePosition::default(),
+            },
+            path,
         })
     }
 }
 
+fn csv_reader(inner: Box<dyn Read + Send>, opts: &CsvOptions, has_header: bool) -> csv::Reader<Box<dyn Read + Send>> {
+    csv::ReaderBuilder::new()
+        .delimiter(opts.delimiter)
+        .quote(opts.quote)
+        .has_headers(has_header)
+        .flexible(true)
+        .from_reader(inner)
+}
+
+/// Converts a `csv` crate error into a `DataError`, keeping its position.
+pub(crate) fn csv_error(e: csv::Error, loc: &Location) -> DataError {
+    let mut loc = loc.clone();
+    if let Some(p) = e.position() {
+        loc = loc.with_line(p.line());
+    }
+    let msg = e.to_string();
+    match e.into_kind() {
+        csv::ErrorKind::Io(io) => DataError::io(loc, io),
+        _ => DataError::parse(loc, msg),
+    }
+}
+
 impl ScalarStream for CsvScalarStream {
     /// Returns the next value from the CSV column.
     ///
     /// - `Some(Ok(f64))` → successfully parsed value.
     /// - `Some(Err(e))` → error while reading/parsing.
     /// - `None` → end of file reached.
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>> {
-        let mut buf = String::new();
-        loop {
-            buf.clear();
-            match self.reader.read_line(&mut buf) {
-                Ok(0) => return None,
-                Ok(_) => {},
-                Err(e) => return Some(Err(Box::new(e))),
-            };
-            let fields = buf.trim_end_matches('\n').split(self.delimiter as char).collect::<Vec<_>>();
-            if self.column >= fields.len() {
-                continue;
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        let loc = Location { path: self.path.clone(), ..Location::default() };
+        match &mut self.mode {
+            CsvMode::Split { reader, column, delimiter, line, record, offset } => {
+                let mut buf = String::new();
+                loop {
+                    buf.clear();
+                    match reader.read_line(&mut buf) {
+                        Ok(0) => return None,
+                        Ok(n) => (*line, *offset) = (*line + 1, *offset + n as u64),
+                        Err(e) => return Some(Err(DataError::io(loc.with_line(*line + 1), e))),
+                    };
+                    if buf.trim_end_matches(['\r', '\n']).is_empty() {
+                        continue;
+                    }
+                    *record += 1;
+                    let fields = buf.trim_end_matches('\n').split(*delimiter as char).collect::<Vec<_>>();
+                    if *column >= fields.len() {
+                        continue;
+                    }
+                    let raw = fields[*column].trim();
+                    if raw.is_empty() {
+                        continue;
+                    }
+                    return Some(raw.parse::<f64>().map_err(|e| DataError::parse(
+                        loc.with_line(*line).with_record(*record).with_column(column.to_string()),
+                        format!("cannot parse '{}' as f64: {}", raw, e),
+                    )));
+                }
             }
-            let raw = fields[self.column].trim();
-            if raw.is_empty() {
-                continue;
+            CsvMode::Rfc4180 { reader, column, label, header_rows, record, base } => loop {
+                match reader.read_record(record) {
+                    Ok(false) => return None,
+                    Ok(true) => {},
+                    Err(e) => return Some(Err(csv_error(e, &loc))),
+                }
+                let loc = match record.position() {
+                    Some(p) => loc.clone()
+                        .with_line(base.line + p.line())
+                        .with_record(base.record + p.record() + 1 - *header_rows),
+                    None => loc.clone(),
+                }.with_column(label.clone());
+                let Some(cell) = record.get(*column) else {
+                    return Some(Err(DataError::schema(
+                        loc,
+                        format!("row has {} fields, column index {} is out of range", record.len(), column),
+                    )));
+                };
+                let raw = cell.trim();
+                if raw.is_empty() {
+                    continue;
+                }
+                return Some(raw.parse::<f64>().map_err(|e| DataError::parse(
+                    loc,
+                    format!("cannot parse '{}' as f64: {}", raw, e),
+                )));
+            },
+        }
+    }
+}
+
+impl Resumable for CsvScalarStream {
+    fn position(&self) -> SourcePosition {
+        match &self.mode {
+            CsvMode::Split { line, record, offset, .. } => SourcePosition { offset: *offset, line: *line, record: *record, skip: 0 },
+            CsvMode::Rfc4180 { reader, header_rows, base, .. } => {
+                let p = reader.position();
+                SourcePosition {
+                    offset: base.offset + p.byte(),
+                    line: base.line + p.line().saturating_sub(1),
+                    record: base.record + p.record().saturating_sub(*header_rows),
+                    skip: 0,
+                }
             }
-            match raw.parse::<f64>() {
-                Ok(v) => return Some(Ok(v)),
-                Err(e) => return Some(Err(Box::new(e))),
+        }
+    }
+}
+
+/* ----------------------------- records ----------------------------- */
+
+/// A CSV-backed implementation of [`RecordStream`] (RFC 4180 parsing).
+///
+/// With a header row, schema fields are matched to columns by name; without
+/// one, schema field `i` is column `i`. Empty cells are [`Field::Null`](crate::record::Field::Null),
+/// short rows are [`DataError::Schema`] and unparsable cells [`DataError::Parse`].
+pub struct CsvRecordStream {
+    reader: csv::Reader<Box<dyn Read + Send>>,
+    schema: Arc<RecordSchema>,
+    columns: Vec<usize>,
+    header_rows: u64,
+    record: csv::StringRecord,
+    path: Option<PathBuf>,
+}
+
+impl CsvRecordStream {
+    /// Creates a new `CsvRecordStream` from a file path.
+    ///
+    /// # Arguments
+    /// * `path` – path to the CSV file (compressed files are decompressed while streaming).
+    /// * `schema` – fields to read and their types.
+    /// * `delimiter` – delimiter as a single byte (e.g. `b','`).
+    /// * `has_header` – whether the first record is a header row.
+    pub fn from_path(path: impl AsRef<Path>, schema: RecordSchema, delimiter: u8, has_header: bool) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        Self::new(file, schema, delimiter, has_header, Some(path.as_ref().to_path_buf()))
+    }
+
+    /// Creates a new `CsvRecordStream` from any reader.
+    ///
+    /// Fails only if the header row is configured and cannot be read or
+    /// does not contain every schema field.
+    pub fn from_reader(reader: impl Read + Send + 'static, schema: RecordSchema, delimiter: u8, has_header: bool) -> Result<Self, DataError> {
+        Self::new(Box::new(reader), schema, delimiter, has_header, None)
+    }
+
+    fn new(
+        inner: Box<dyn Read + Send>,
+        schema: RecordSchema,
+        delimiter: u8,
+        has_header: bool,
+        path: Option<PathBuf>,
+    ) -> Result<Self, DataError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(has_header)
+            .flexible(true)
+            .from_reader(inner);
+        let columns = if has_header {
+            let loc = Location { path: path.clone(), ..Location::default() };
+            let headers = reader.headers().map_err(|e| csv_error(e, &loc))?;
+            schema.fields().iter()
+                .map(|f| headers.iter()
+                    .position(|h| h.trim() == f.name)
+                    .ok_or_else(|| DataError::missing_key(loc.clone().with_line(1), f.name.clone())))
+                .collect::<Result<_, _>>()?
+        } else {
+            (0..schema.len()).collect()
+        };
+        Ok(Self {
+            reader,
+            schema: Arc::new(schema),
+            columns,
+            header_rows: has_header as u64,
+            record: csv::StringRecord::new(),
+            path,
+        })
+    }
+}
+
+impl RecordStream for CsvRecordStream {
+    fn schema(&self) -> &Arc<RecordSchema> { &self.schema }
+
+    /// Reads the next CSV row and converts the selected cells.
+    ///
+    /// - `Some(Ok(Record))` → row with one typed field per schema entry.
+    /// - `Some(Err(e))` → error while reading, a short row or an unparsable cell.
+    /// - `None` → end of file reached.
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        let loc = Location { path: self.path.clone(), ..Location::default() };
+        match self.reader.read_record(&mut self.record) {
+            Ok(false) => return None,
+            Ok(true) => {},
+            Err(e) => return Some(Err(csv_error(e, &loc))),
+        }
+        let loc = match self.record.position() {
+            Some(p) => loc.with_line(p.line()).with_record(p.record() + 1 - self.header_rows),
+            None => loc,
+        };
+        let mut values = Vec::with_capacity(self.columns.len());
+        for (spec, &col) in self.schema.fields().iter().zip(&self.columns) {
+            let cell_loc = loc.clone().with_column(spec.name.clone());
+            let Some(cell) = self.record.get(col) else {
+                return Some(Err(DataError::schema(
+                    cell_loc,
+                    format!("row has {} fields, column index {} is out of range", self.record.len(), col),
+                )));
+            };
+            match spec.ty.parse(cell, &cell_loc) {
+                Ok(v) => values.push(v),
+                Err(e) => return Some(Err(e)),
             }
         }
+        Some(Ok(Record::new(self.schema.clone(), values, loc)))
     }
 }
@@ -0,0 +1,177 @@
+//! Error types shared by all data-layer streams.
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+/// Position in the input where an error occurred.
+///
+/// All fields are optional; each source fills in what it knows
+/// (e.g. CSV sets line, record and column, NDJSON sets line and key path).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Location {
+    /// File the record came from, if the stream was opened from a path.
+    pub path: Option<PathBuf>,
+    /// 1-based line number (start line of the record for multiline formats).
+    pub line: Option<u64>,
+    /// 1-based record number (CSV row, JSON array element, XML record).
+    pub record: Option<u64>,
+    /// Column name/index or key path of the value.
+    pub column: Option<String>,
+}
+
+impl Location {
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self { self.path = Some(path.into()); self }
+    pub fn with_line(mut self, line: u64) -> Self { self.line = Some(line); self }
+    pub fn with_record(mut self, record: u64) -> Self { self.record = Some(record); self }
+    pub fn with_column(mut self, column: impl Into<String>) -> Self { self.column = Some(column.into()); self }
+
+    pub fn is_empty(&self) -> bool {
+        self.path.is_none() && self.line.is_none() && self.record.is_none() && self.column.is_none()
+    }
+
+    /// `" at <location>"`, or an empty string if nothing is known.
+    fn suffix(&self) -> String {
+        if self.is_empty() { String::new() } else { format!(" at {}", self) }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(p) = &self.path { parts.push(p.display().to_string()); }
+        if let Some(l) = self.line { parts.push(format!("line {}", l)); }
+        if let Some(r) = self.record { parts.push(format!("record {}", r)); }
+        if let Some(c) = &self.column { parts.push(format!("column '{}'", c)); }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Coarse category of a [`DataError`], e.g. for counting or routing failures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DataErrorKind {
+    Io,
+    Parse,
+    MissingKey,
+    TypeMismatch,
+    Schema,
+    InvalidParam,
+    Other,
+}
+
+/// Structured error returned by every [`ScalarStream`](crate::stream::ScalarStream).
+///
+/// Each variant except `InvalidParam` and `Other` carries a [`Location`], so callers can
+/// branch on the kind of failure and report where it happened.
+#[derive(Debug, Error)]
+pub enum DataError {
+    /// Reading the underlying source failed.
+    #[error("I/O error{}: {source}", .loc.suffix())]
+    Io { loc: Location, #[source] source: std::io::Error },
+
+    /// The input is malformed or a value could not be parsed as a number.
+    #[error("parse error{}: {msg}", .loc.suffix())]
+    Parse { loc: Location, msg: String },
+
+    /// A key, element or column required by the stream is absent.
+    #[error("missing key{}: '{key}' not found", .loc.suffix())]
+    MissingKey { loc: Location, key: String },
+
+    /// The value exists but has the wrong type (e.g. a JSON object instead of a number).
+    #[error("type mismatch{}: expected {expected}, found {found}", .loc.suffix())]
+    TypeMismatch { loc: Location, expected: &'static str, found: String },
+
+    /// The input does not match the expected structure (e.g. a short CSV row).
+    #[error("schema error{}: {msg}", .loc.suffix())]
+    Schema { loc: Location, msg: String },
+
+    /// A constructor or option was given an invalid value (e.g. from configuration).
+    #[error("invalid parameter: {0}")]
+    InvalidParam(&'static str),
+
+    /// Error from a stream implementation outside data-layer.
+    #[error("{0}")]
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl DataError {
+    pub fn io(loc: Location, source: std::io::Error) -> Self { DataError::Io { loc, source } }
+    pub fn parse(loc: Location, msg: impl Into<String>) -> Self { DataError::Parse { loc, msg: msg.into() } }
+    pub fn missing_key(loc: Location, key: impl Into<String>) -> Self { DataError::MissingKey { loc, key: key.into() } }
+    pub fn schema(loc: Location, msg: impl Into<String>) -> Self { DataError::Schema { loc, msg: msg.into() } }
+    pub fn other(e: impl Into<Box<dyn Error + Send + Sync>>) -> Self { DataError::Other(e.into()) }
+
+    pub fn kind(&self) -> DataErrorKind {
+        match self {
+            DataError::Io { .. } => DataErrorKind::Io,
+            DataError::Parse { .. } => DataErrorKind::Parse,
+            DataError::MissingKey { .. } => DataErrorKind::MissingKey,
+            DataError::TypeMismatch { .. } => DataErrorKind::TypeMismatch,
+            DataError::Schema { .. } => DataErrorKind::Schema,
+            DataError::InvalidParam(_) => DataErrorKind::InvalidParam,
+            DataError::Other(_) => DataErrorKind::Other,
+        }
+    }
+
+    /// True for errors caused by the content of a single record
+    /// (parse, missing key, type mismatch, schema), as opposed to I/O failures.
+    pub fn is_record_error(&self) -> bool {
+        matches!(
+            self.kind(),
+            DataErrorKind::Parse | DataErrorKind::MissingKey | DataErrorKind::TypeMismatch | DataErrorKind::Schema
+        )
+    }
+
+    /// The location attached to this error, if any.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            DataError::Io { loc, .. }
+            | DataError::Parse { loc, .. }
+            | DataError::MissingKey { loc, .. }
+            | DataError::TypeMismatch { loc, .. }
+            | DataError::Schema { loc, .. } => Some(loc),
+            DataError::InvalidParam(_) | DataError::Other(_) => None,
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            DataError::Io { loc, .. }
+            | DataError::Parse { loc, .. }
+            | DataError::MissingKey { loc, .. }
+            | DataError::TypeMismatch { loc, .. }
+            | DataError::Schema { loc, .. } => Some(loc),
+            DataError::InvalidParam(_) | DataError::Other(_) => None,
+        }
+    }
+
+    /// Adds `lines` and `records` to the line and record numbers of the location,
+    /// for errors of a stream that started in the middle of its input.
+    pub(crate) fn shifted(mut self, lines: u64, records: u64) -> Self {
+        if let Some(loc) = self.location_mut() {
+            loc.line = loc.line.map(|l| l + lines);
+            loc.record = loc.record.map(|r| r + records);
+        }
+        self
+    }
+
+    /// Sets the file path on the location unless one is already present.
+    pub fn with_path(mut self, path: &Path) -> Self {
+        if let Some(loc) = self.location_mut() {
+            if loc.path.is_none() {
+                loc.path = Some(path.to_path_buf());
+            }
+        }
+        self
+    }
+}
+
+impl From<std::io::Error> for DataError {
+    fn from(e: std::io::Error) -> Self { DataError::io(Location::default(), e) }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for DataError {
+    fn from(e: Box<dyn Error + Send + Sync>) -> Self { DataError::Other(e) }
+}
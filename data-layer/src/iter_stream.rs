@@ -0,0 +1,91 @@
+// src/iter_stream.rs
+use std::error::Error;
+
+use crate::error::DataError;
+use crate::stream::ScalarStream;
+
+/// An item an [`IterStream`] can yield: a plain `f64` or a `Result<f64, E>`.
+pub trait IntoScalarItem {
+    fn into_item(self) -> Result<f64, DataError>;
+}
+
+impl IntoScalarItem for f64 {
+    fn into_item(self) -> Result<f64, DataError> { Ok(self) }
+}
+
+impl<E: Into<DataError>> IntoScalarItem for Result<f64, E> {
+    fn into_item(self) -> Result<f64, DataError> { self.map_err(Into::into) }
+}
+
+/// An in-memory `ScalarStream` over any iterator of `f64` or `Result<f64, E>`.
+///
+/// Errors must convert into [`DataError`]; for other error types use
+/// [`IterStream::from_fallible`].
+///
+/// Useful for tests, for values computed in Rust code, and for feeding
+/// already-collected data into `stream_queries` or the `mechanisms` crate.
+///
+/// ```
+/// use data_layer::iter_stream::{IterStream, VecStream};
+/// use data_layer::stream_queries::{sum_stream, BoundedF64};
+///
+/// let dom = BoundedF64::new(0.0, 10.0);
+/// let (sum, n) = sum_stream(IterStream::new((1..=4).map(f64::from)), dom).unwrap();
+/// assert_eq!((sum, n), (10.0, 4));
+///
+/// let (sum, _) = sum_stream(VecStream::from(vec![1.5, 20.0]), dom).unwrap();
+/// assert_eq!(sum, 11.5);
+/// ```
+pub struct IterStream<I> {
+    iter: I,
+}
+
+/// An `IterStream` that owns a `Vec<f64>`.
+pub type VecStream = IterStream<std::vec::IntoIter<f64>>;
+
+/// An `IterStream` over results with a foreign error type; see [`IterStream::from_fallible`].
+pub type FallibleStream<I, E> = IterStream<std::iter::Map<I, fn(Result<f64, E>) -> Result<f64, DataError>>>;
+
+impl<I: Iterator> IterStream<I> {
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        Self { iter: iter.into_iter() }
+    }
+}
+
+impl<I, E> FallibleStream<I, E>
+where
+    I: Iterator<Item = Result<f64, E>>,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    /// Wraps an iterator of `Result<f64, E>` whose error has no conversion into
+    /// [`DataError`] (`String`, `&str`, boxed or foreign errors). Errors become
+    /// [`DataError::Other`].
+    ///
+    /// ```
+    /// use data_layer::iter_stream::IterStream;
+    /// use data_layer::ScalarStream;
+    ///
+    /// let items: Vec<Result<f64, String>> = vec![Ok(1.0), Err("sensor offline".into())];
+    /// let mut s = IterStream::from_fallible(items);
+    /// assert_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    /// assert_eq!(s.next_val().unwrap().unwrap_err().to_string(), "sensor offline");
+    /// ```
+    pub fn from_fallible(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        let convert: fn(Result<f64, E>) -> Result<f64, DataError> = |r| r.map_err(DataError::other);
+        Self::new(iter.into_iter().map(convert))
+    }
+}
+
+impl From<Vec<f64>> for VecStream {
+    fn from(v: Vec<f64>) -> Self { Self::new(v) }
+}
+
+impl<I> ScalarStream for IterStream<I>
+where
+    I: Iterator,
+    I::Item: IntoScalarItem,
+{
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        self.iter.next().map(IntoScalarItem::into_item)
+    }
+}
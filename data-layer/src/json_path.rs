@@ -0,0 +1,358 @@
+// src/json_path.rs
+//! Paths into JSON values for the JSON sources.
+//!
+//! Three notations are accepted:
+//!
+//! | Notation     | Example                                   |
+//! |--------------|-------------------------------------------|
+//! | dotted       | `metrics.value`, `items[0].price`         |
+//! | JSONPath     | `$.readings[*].v`, `$['key.with.dots']`   |
+//! | JSON pointer | `/items/0/price`, `/a~1b` (key `a/b`)     |
+//!
+//! Dotted paths are JSONPath without the leading `$.`. Supported JSONPath
+//! selectors: `.name`, `['name']` / `["name"]`, `[i]` (negative counts from the
+//! end), `[*]` / `.*`, and filters `[?(@.field)]`, `[?(@.field <op> literal)]` with
+//! `==`, `!=`, `<`, `<=`, `>`, `>=` against numbers, strings, `true`, `false` or `null`.
+//! Recursive descent (`..`) is not supported. The empty path selects the root value.
+//!
+//! A path without wildcards or filters is *singular*: it selects at most one
+//! value, and a missing step is reported as [`DataError::MissingKey`]. Other paths
+//! *fan out*: they select zero or more values per record.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{DataError, Location};
+
+/// A parsed JSON path; see the [module docs](self) for the syntax.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    /// Object key, or array index if the token is a non-negative integer (dotted/pointer steps).
+    Child(String),
+    /// Object key only (`['name']`).
+    Key(String),
+    /// Array index; negative values count from the end.
+    Index(i64),
+    /// All array elements or object values.
+    Wildcard,
+    /// Array elements or object values for which the filter holds.
+    Filter(Filter),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Filter {
+    path: Vec<Segment>,
+    cmp: Option<(CmpOp, Value)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CmpOp { Eq, Ne, Lt, Le, Gt, Ge }
+
+impl JsonPath {
+    /// Parses a dotted path, JSONPath expression or JSON pointer.
+    pub fn parse(path: &str) -> Result<Self, DataError> {
+        let segments = if path.starts_with('/') {
+            parse_pointer(path)
+        } else {
+            let rest = path.strip_prefix('$').unwrap_or(path);
+            Parser { s: rest.as_bytes(), pos: 0, src: path }.segments(!path.starts_with('$'))?
+        };
+        Ok(Self { source: path.to_string(), segments })
+    }
+
+    /// The path as written.
+    pub fn as_str(&self) -> &str { &self.source }
+
+    /// True if the path has no wildcards or filters, i.e. selects at most one value.
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|s| matches!(s, Segment::Child(_) | Segment::Key(_) | Segment::Index(_)))
+    }
+
+    /// Follows a singular path. On failure, returns the first step that did not match.
+    pub fn get<'a>(&self, v: &'a Value) -> Result<&'a Value, String> {
+        let mut cur = v;
+        for seg in &self.segments {
+            let mut next = Vec::with_capacity(1);
+            seg.apply(cur, &mut next);
+            cur = next.pop().ok_or_else(|| seg.to_string())?;
+        }
+        Ok(cur)
+    }
+
+    /// All values the path selects, in document order.
+    pub fn select<'a>(&self, v: &'a Value) -> Vec<&'a Value> {
+        let mut cur = vec![v];
+        for seg in &self.segments {
+            let mut next = Vec::new();
+            for c in cur {
+                seg.apply(c, &mut next);
+            }
+            cur = next;
+        }
+        cur
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = DataError;
+    fn from_str(s: &str) -> Result<Self, DataError> { Self::parse(s) }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.source) }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Child(k) | Segment::Key(k) => write!(f, "{}", k),
+            Segment::Index(i) => write!(f, "[{}]", i),
+            Segment::Wildcard => write!(f, "[*]"),
+            Segment::Filter(_) => write!(f, "[?()]"),
+        }
+    }
+}
+
+impl Segment {
+    fn apply<'a>(&self, v: &'a Value, out: &mut Vec<&'a Value>) {
+        match (self, v) {
+            (Segment::Child(k), Value::Object(m)) | (Segment::Key(k), Value::Object(m)) => out.extend(m.get(k)),
+            (Segment::Child(k), Value::Array(a)) => {
+                if let Ok(i) = k.parse::<usize>() {
+                    out.extend(a.get(i));
+                }
+            }
+            (Segment::Index(i), Value::Array(a)) => {
+                let i = if *i < 0 { a.len() as i64 + i } else { *i };
+                if i >= 0 {
+                    out.extend(a.get(i as usize));
+                }
+            }
+            (Segment::Wildcard, Value::Array(a)) => out.extend(a.iter()),
+            (Segment::Wildcard, Value::Object(m)) => out.extend(m.values()),
+            (Segment::Filter(f), Value::Array(a)) => out.extend(a.iter().filter(|c| f.holds(c))),
+            (Segment::Filter(f), Value::Object(m)) => out.extend(m.values().filter(|c| f.holds(c))),
+            _ => {}
+        }
+    }
+}
+
+impl Filter {
+    fn holds(&self, v: &Value) -> bool {
+        let mut cur = v;
+        for seg in &self.path {
+            let mut next = Vec::with_capacity(1);
+            seg.apply(cur, &mut next);
+            match next.pop() {
+                Some(n) => cur = n,
+                None => return false,
+            }
+        }
+        let Some((op, lit)) = &self.cmp else { return true };
+        let ord = match (cur, lit) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (a, b) if a == b => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        };
+        match (op, ord) {
+            (CmpOp::Ne, None) => true,
+            (_, None) => false,
+            (CmpOp::Eq, Some(o)) => o.is_eq(),
+            (CmpOp::Ne, Some(o)) => o.is_ne(),
+            (CmpOp::Lt, Some(o)) => o.is_lt(),
+            (CmpOp::Le, Some(o)) => o.is_le(),
+            (CmpOp::Gt, Some(o)) => o.is_gt(),
+            (CmpOp::Ge, Some(o)) => o.is_ge(),
+        }
+    }
+}
+
+/// RFC 6901: `/`-separated tokens with `~1` for `/` and `~0` for `~`.
+fn parse_pointer(path: &str) -> Vec<Segment> {
+    path[1..].split('/')
+        .map(|t| Segment::Child(t.replace("~1", "/").replace("~0", "~")))
+        .collect()
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+    src: &'a str,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> DataError {
+        DataError::parse(Location::default().with_column(self.src), format!("invalid JSON path: {} at offset {}", msg, self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> { self.s.get(self.pos).copied() }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let hit = self.peek() == Some(b);
+        if hit {
+            self.pos += 1;
+        }
+        hit
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses steps until the end of input. `bare_first` allows a leading name without `.`.
+    fn segments(&mut self, bare_first: bool) -> Result<Vec<Segment>, DataError> {
+        let mut out = Vec::new();
+        if bare_first && self.peek().is_some_and(|b| b != b'[') {
+            out.push(self.name(false)?);
+        }
+        while self.pos < self.s.len() {
+            out.push(self.step(false)?);
+        }
+        Ok(out)
+    }
+
+    /// One `.name`, `.*` or `[...]` step. Inside filters only singular steps are allowed.
+    fn step(&mut self, in_filter: bool) -> Result<Segment, DataError> {
+        if self.eat(b'.') {
+            if self.peek() == Some(b'.') {
+                return Err(self.error("recursive descent '..' is not supported"));
+            }
+            return self.name(in_filter);
+        }
+        if !self.eat(b'[') {
+            return Err(self.error("expected '.' or '['"));
+        }
+        self.skip_ws();
+        let seg = match self.peek() {
+            Some(b'*') if !in_filter => {
+                self.pos += 1;
+                Segment::Wildcard
+            }
+            Some(b'\'' | b'"') => Segment::Key(self.quoted()?),
+            Some(b'?') if !in_filter => {
+                self.pos += 1;
+                self.filter()?
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                let text = std::str::from_utf8(&self.s[start..self.pos]).unwrap_or_default();
+                Segment::Index(text.parse().map_err(|_| self.error("invalid array index"))?)
+            }
+            _ => return Err(self.error("expected index, '*', quoted key or filter")),
+        };
+        self.skip_ws();
+        if !self.eat(b']') {
+            return Err(self.error("expected ']'"));
+        }
+        Ok(seg)
+    }
+
+    /// A bare key. Inside filters it also ends at whitespace, operators and `)`.
+    fn name(&mut self, in_filter: bool) -> Result<Segment, DataError> {
+        let start = self.pos;
+        let end = |b: u8| matches!(b, b'.' | b'[' | b']')
+            || (in_filter && (b.is_ascii_whitespace() || matches!(b, b'=' | b'!' | b'<' | b'>' | b')')));
+        while self.peek().is_some_and(|b| !end(b)) {
+            self.pos += 1;
+        }
+        match &self.s[start..self.pos] {
+            b"" => Err(self.error("empty key")),
+            b"*" => Ok(Segment::Wildcard),
+            name => Ok(Segment::Child(String::from_utf8_lossy(name).into_owned())),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, DataError> {
+        let q = self.s[self.pos];
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'\\') => {
+                    out.extend(self.s.get(self.pos + 1));
+                    self.pos += 2;
+                }
+                Some(b) if b == q => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| self.error("invalid UTF-8"));
+                }
+                Some(b) => {
+                    out.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// `?(@<steps> [op literal])`; the leading `?` is already consumed.
+    fn filter(&mut self) -> Result<Segment, DataError> {
+        if !self.eat(b'(') {
+            return Err(self.error("expected '(' after '?'"));
+        }
+        self.skip_ws();
+        if !self.eat(b'@') {
+            return Err(self.error("filter must start with '@'"));
+        }
+        let mut path = Vec::new();
+        while matches!(self.peek(), Some(b'.' | b'[')) {
+            path.push(self.step(true)?);
+        }
+        self.skip_ws();
+        let cmp = if self.peek() == Some(b')') {
+            None
+        } else {
+            let op = match (self.peek(), self.s.get(self.pos + 1)) {
+                (Some(b'='), Some(b'=')) => CmpOp::Eq,
+                (Some(b'!'), Some(b'=')) => CmpOp::Ne,
+                (Some(b'<'), Some(b'=')) => CmpOp::Le,
+                (Some(b'>'), Some(b'=')) => CmpOp::Ge,
+                (Some(b'<'), _) => CmpOp::Lt,
+                (Some(b'>'), _) => CmpOp::Gt,
+                _ => return Err(self.error("expected comparison operator")),
+            };
+            self.pos += if matches!(op, CmpOp::Lt | CmpOp::Gt) { 1 } else { 2 };
+            self.skip_ws();
+            Some((op, self.literal()?))
+        };
+        self.skip_ws();
+        if !self.eat(b')') {
+            return Err(self.error("expected ')'"));
+        }
+        Ok(Segment::Filter(Filter { path, cmp }))
+    }
+
+    fn literal(&mut self) -> Result<Value, DataError> {
+        if matches!(self.peek(), Some(b'\'' | b'"')) {
+            return Ok(Value::String(self.quoted()?));
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|b| !matches!(b, b')' | b' ')) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.s[start..self.pos]).unwrap_or_default();
+        match text {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "null" => Ok(Value::Null),
+            _ => serde_json::from_str::<serde_json::Number>(text)
+                .map(Value::Number)
+                .map_err(|_| self.error("expected number, string, true, false or null")),
+        }
+    }
+}
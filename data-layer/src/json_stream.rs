@@ -1,32 +1,111 @@
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use serde_json::{self as json, Value};
+use crate::checkpoint::{Resumable, SourcePosition};
+use crate::compression;
+use crate::error::{DataError, Location};
+use crate::json_path::JsonPath;
+use crate::record::{timestamp_from_secs, Field, FieldType, Record, RecordSchema, RecordStream};
 use crate::stream::ScalarStream;
+use crate::tail::{TailHandle, TailOptions, TailReader};
 
-/// Extract a floating-point value (`f64`) from a JSON object
-/// by following a dotted key path (e.g., `"metrics.value"`).
+/// Converts a selected JSON value into an `f64`.
 ///
-/// - If the target is a number, it is returned directly.
-/// - If the target is a string, the string is parsed as `f64`.
+/// - If the value is a number, it is returned directly.
+/// - If the value is a string, the string is parsed as `f64`.
 /// - Otherwise, an error is returned.
-fn extract_f64_by_path<'a>(v: &'a Value, path: &str) -> Result<f64, Box<dyn Error + Send + Sync>> {
-    let mut cur = v;
-    if !path.is_empty() {
-        for key in path.split('.') {
-            cur = cur.get(key)
-                .ok_or_else(|| format!("key '{}' not found in JSON object", key))?;
-        }
-    }
+fn value_to_f64(cur: &Value, loc: Location) -> Result<f64, DataError> {
     if let Some(n) = cur.as_f64() {
         return Ok(n);
     }
     if let Some(s) = cur.as_str() {
         return s.parse::<f64>()
-            .map_err(|e| format!("cannot parse '{}' as f64: {}", s, e).into());
+            .map_err(|e| DataError::parse(loc, format!("cannot parse '{}' as f64: {}", s, e)));
+    }
+    Err(DataError::TypeMismatch { loc, expected: "number or numeric string", found: json_type_name(cur).into() })
+}
+
+/// How many values the records of a fan-out path contributed.
+///
+/// Like [`BadRecordReport`](crate::bad_records::BadRecordReport), these counts are
+/// computed from the private data and are for operators, not for release.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FanOutStats {
+    /// Records seen.
+    pub records: u64,
+    /// Values (and value errors) yielded.
+    pub values: u64,
+    /// Largest number of values yielded for one record.
+    pub max_per_record: usize,
+    /// Records whose matches were cut off by the per-record cap.
+    pub truncated: u64,
+    /// Number of records per contribution count (`values per record → records`).
+    pub by_count: BTreeMap<usize, u64>,
+}
+
+/// Applies a [`JsonPath`] to each record and queues the resulting values.
+///
+/// A singular path yields exactly one item per record (value or error, as before
+/// JSONPath support). A fan-out path yields one item per match, at most `cap`.
+pub(crate) struct Extractor {
+    path: JsonPath,
+    pub(crate) cap: Option<usize>,
+    pub(crate) pending: VecDeque<Result<f64, DataError>>,
+    pub(crate) stats: FanOutStats,
+}
+
+impl Extractor {
+    pub(crate) fn new(path: &str) -> Result<Self, DataError> {
+        Ok(Self { path: JsonPath::parse(path)?, cap: None, pending: VecDeque::new(), stats: FanOutStats::default() })
+    }
+
+    /// `loc` describes the record; the key path is added as its column.
+    pub(crate) fn push(&mut self, v: &Value, loc: Location) {
+        let loc = loc.with_column(self.path.as_str());
+        let before = self.pending.len();
+        if self.path.is_singular() {
+            let item = self.path.get(v)
+                .map_err(|key| DataError::missing_key(loc.clone(), key))
+                .and_then(|x| value_to_f64(x, loc));
+            self.pending.push_back(item);
+        } else {
+            let mut matches = self.path.select(v);
+            if let Some(cap) = self.cap.filter(|&cap| matches.len() > cap) {
+                matches.truncate(cap);
+                self.stats.truncated += 1;
+            }
+            self.pending.extend(matches.into_iter().map(|x| value_to_f64(x, loc.clone())));
+        }
+        let n = self.pending.len() - before;
+        self.stats.records += 1;
+        self.stats.values += n as u64;
+        self.stats.max_per_record = self.stats.max_per_record.max(n);
+        *self.stats.by_count.entry(n).or_insert(0) += 1;
+    }
+}
+
+fn json_type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Converts a `serde_json` error into a `DataError`, keeping its line.
+pub(crate) fn json_error(e: json::Error, loc: Location) -> DataError {
+    let loc = if e.line() > 0 && loc.line.is_none() { loc.with_line(e.line() as u64) } else { loc };
+    if e.is_io() {
+        DataError::io(loc, e.into())
+    } else {
+        DataError::parse(loc, e.to_string())
     }
-    Err("value is not f64 or numeric string".into())
 }
 
 /* ----------------------------- NDJSON (newline-delimited JSON) ----------------------------- */
@@ -40,25 +119,150 @@ fn extract_f64_by_path<'a>(v: &'a Value, path: &str) -> Result<f64, Box<dyn Erro
 /// - Errors (e.g., malformed JSON or missing key) are returned
 ///   as `Some(Err(..))` instead of panicking.
 pub struct NdjsonScalarStream {
+    lines: NdjsonLines,
+    extractor: Extractor,
+    /// Values of the current record already returned.
+    emitted: u64,
+    /// Values of the first record to drop (resumed mid-record).
+    skip: u64,
+}
+
+/// Line reader shared by the NDJSON streams: yields one parsed JSON value per non-blank line.
+pub(crate) struct NdjsonLines {
     reader: Box<dyn BufRead + Send>,
-    key_path: String,
     buf: String,
+    pub(crate) path: Option<PathBuf>,
+    line: u64,
+    offset: u64,
+    records: u64,
+    /// Position at the start of the last record returned.
+    record_start: SourcePosition,
+}
+
+impl NdjsonLines {
+    pub(crate) fn new(reader: impl Read + Send + 'static) -> Self {
+        Self {
+            reader: Box::new(BufReader::new(reader)),
+            buf: String::new(),
+            path: None,
+            line: 0,
+            offset: 0,
+            records: 0,
+            record_start: SourcePosition::default(),
+        }
+    }
+
+    /// Continues counting lines, records and bytes from `pos`.
+    fn start_at(&mut self, pos: &SourcePosition) {
+        (self.offset, self.line, self.records) = (pos.offset, pos.line, pos.record);
+    }
+
+    /// The position after the last line read.
+    fn position(&self) -> SourcePosition {
+        SourcePosition { offset: self.offset, line: self.line, record: self.records, skip: 0 }
+    }
+
+    /// The next parsed line and its location, or `Err` if it could not be read or parsed.
+    pub(crate) fn next_value(&mut self) -> Option<Result<(Value, Location), DataError>> {
+        loop {
+            self.buf.clear();
+            let loc = Location { path: self.path.clone(), ..Location::default() }.with_line(self.line + 1);
+            let start = self.position();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None, // EOF
+                Ok(n) => {
+                    self.line += 1;
+                    self.offset += n as u64;
+                    let line = self.buf.trim();
+                    if line.is_empty() { continue; } // skip blanks
+                    self.records += 1;
+                    self.record_start = start;
+                    return Some(match json::from_str::<Value>(line) {
+                        Ok(v) => Ok((v, loc)),
+                        Err(e) => Err(json_error(e, loc)),
+                    });
+                }
+                Err(e) => return Some(Err(DataError::io(loc, e))),
+            }
+        }
+    }
 }
 
 impl NdjsonScalarStream {
     /// Creates a new NDJSON-backed scalar stream.
     ///
+    /// Compressed files are decompressed while streaming (see [`compression`]).
+    ///
     /// # Arguments
     /// * `path` – path to the NDJSON file.
-    /// * `key_path` – dotted key path to extract from each JSON object.
-    pub fn from_path(path: &str, key_path: impl Into<String>) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let file = File::open(path)?;
-        Ok(Self {
-            reader: Box::new(BufReader::new(file)),
-            key_path: key_path.into(),
-            buf: String::new(),
-        })
+    /// * `key_path` – key path to extract from each JSON object (see [`json_path`](crate::json_path)).
+    pub fn from_path(path: impl AsRef<Path>, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(file, key_path)?;
+        s.lines.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+
+    /// Creates a new NDJSON-backed scalar stream from any reader.
+    ///
+    /// Fails only if `key_path` is not a valid path.
+    ///
+    /// # Arguments
+    /// * `reader` – source of NDJSON bytes (stdin, in-memory buffer, ...); buffered internally.
+    /// * `key_path` – key path to extract from each JSON object (see [`json_path`](crate::json_path)).
+    pub fn from_reader(reader: impl Read + Send + 'static, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        Ok(Self { lines: NdjsonLines::new(reader), extractor: Extractor::new(key_path.as_ref())?, emitted: 0, skip: 0 })
+    }
+
+    /// Reopens an NDJSON file at a position taken with [`Resumable::position`].
+    ///
+    /// # Arguments
+    /// * `path` – path to the NDJSON file the position was taken on.
+    /// * `key_path` – the same key path as before.
+    /// * `pos` – where to continue (see [`checkpoint`](crate::checkpoint)).
+    pub fn resume(path: impl AsRef<Path>, key_path: impl AsRef<str>, pos: &SourcePosition) -> Result<Self, DataError> {
+        let file = compression::open_path_at(path.as_ref(), pos.offset)?;
+        let mut s = Self::from_reader(file, key_path)?;
+        s.lines.path = Some(path.as_ref().to_path_buf());
+        s.lines.start_at(pos);
+        s.skip = pos.skip;
+        Ok(s)
+    }
+
+    /// A stream over the bytes `start..end` of an uncompressed file; `start`
+    /// must be at the beginning of a line. Line numbers count from `start`.
+    pub(crate) fn chunk(path: &Path, key_path: &str, start: u64, end: u64) -> Result<Self, DataError> {
+        let loc = Location::default().with_path(path);
+        let mut file = std::fs::File::open(path).map_err(|e| DataError::io(loc.clone(), e))?;
+        file.seek(SeekFrom::Start(start)).map_err(|e| DataError::io(loc, e))?;
+        let mut s = Self::from_reader(file.take(end - start), key_path)?;
+        s.lines.path = Some(path.to_path_buf());
+        s.lines.start_at(&SourcePosition { offset: start, ..SourcePosition::default() });
+        Ok(s)
     }
+
+    /// Creates a stream that follows a growing NDJSON file (see [`tail`](crate::tail)).
+    ///
+    /// The stream only ends after [`TailHandle::stop`]; the handle also provides
+    /// the checkpoint to resume from.
+    ///
+    /// # Arguments
+    /// * `path` – path to the (uncompressed) NDJSON file.
+    /// * `key_path` – key path to extract from each JSON object (see [`json_path`](crate::json_path)).
+    /// * `opts` – start position and poll interval.
+    pub fn tail(path: impl AsRef<Path>, key_path: impl AsRef<str>, opts: TailOptions) -> Result<(Self, TailHandle), DataError> {
+        let reader = TailReader::open(path.as_ref(), opts)?;
+        let handle = reader.handle();
+        let mut s = Self::from_reader(reader, key_path)?;
+        s.lines.path = Some(path.as_ref().to_path_buf());
+        Ok((s, handle))
+    }
+
+    /// Caps the number of values a fan-out path yields per record (the first `k` matches).
+    pub fn max_per_record(mut self, k: usize) -> Self { self.extractor.cap = Some(k); self }
+
+    /// Per-record contribution counts so far; final once the stream has returned `None`.
+    pub fn fan_out(&self) -> &FanOutStats { &self.extractor.stats }
 }
 
 impl ScalarStream for NdjsonScalarStream {
@@ -67,32 +271,50 @@ impl ScalarStream for NdjsonScalarStream {
     /// - `Some(Ok(f64))` → successfully parsed value.
     /// - `Some(Err(e))` → error while reading/parsing/extracting.
     /// - `None` → end of file reached.
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         loop {
-            self.buf.clear();
-            match self.reader.read_line(&mut self.buf) {
-                Ok(0) => return None, // EOF
-                Ok(_) => {
-                    let line = self.buf.trim();
-                    if line.is_empty() { continue; } // skip blanks
-                    match json::from_str::<Value>(line) {
-                        Ok(v) => return Some(extract_f64_by_path(&v, &self.key_path)),
-                        Err(e) => return Some(Err(Box::new(e))),
-                    }
+            if let Some(item) = self.extractor.pending.pop_front() {
+                self.emitted += 1;
+                return Some(item);
+            }
+            match self.lines.next_value()? {
+                Ok((v, loc)) => {
+                    self.extractor.push(&v, loc);
+                    self.emitted = self.skip.min(self.extractor.pending.len() as u64);
+                    self.extractor.pending.drain(..self.emitted as usize);
+                    self.skip = 0;
                 }
-                Err(e) => return Some(Err(Box::new(e))),
+                Err(e) => return Some(Err(e)),
             }
         }
     }
 }
 
+impl Resumable for NdjsonScalarStream {
+    /// After a value of a fan-out path, the position points at the start of its
+    /// record and counts the values already returned in `skip`.
+    fn position(&self) -> SourcePosition {
+        if self.extractor.pending.is_empty() {
+            self.lines.position()
+        } else {
+            SourcePosition { skip: self.emitted, ..self.lines.record_start }
+        }
+    }
+}
+
 /* ----------------------------- JSON Array (streaming) ----------------------------- */
 
 /// A `ScalarStream` implementation for a **JSON array** of objects.
 ///
-/// This uses `serde_json::StreamDeserializer` to iterate element by element,
-/// without loading the entire array into memory. Suitable for very large JSON
-/// arrays when streaming is required.
+/// The top-level array is scanned byte by byte and each element is parsed on
+/// its own, so memory is bounded by the largest element rather than the array.
+/// Suitable for multi-GB JSON arrays.
+///
+/// - A malformed element is a `Some(Err(..))` and the stream continues with the
+///   next one; a broken array structure (e.g. truncated input) ends the stream
+///   after the error.
+/// - Input that does not start with `[` is read as a sequence of whitespace-separated
+///   top-level values, each one element.
 ///
 /// # Example file
 /// ```json
@@ -101,23 +323,269 @@ impl ScalarStream for NdjsonScalarStream {
 ///
 /// With `key_path = "v"`, this stream will yield `1.0`, `-2.0`, and `3.5`.
 pub struct JsonArrayScalarStream<R: Read> {
-    iter: json::StreamDeserializer<'static, json::de::IoRead<R>, Value>,
-    key_path: String,
+    elements: JsonArrayElements<R>,
+    extractor: Extractor,
+}
+
+/// Size of the read buffer of [`JsonArrayElements`].
+const SCAN_BUF: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    /// Before the first non-whitespace byte.
+    Start,
+    /// Inside the top-level array, before an element (`first` = right after `[`).
+    Array { first: bool },
+    /// Input is a sequence of top-level values instead of an array.
+    Sequence,
+    /// After the closing `]`; only whitespace may follow.
+    Closed,
+    /// End of input or after a structural error.
+    Done,
+}
+
+/// Incremental element reader shared by the JSON array streams.
+///
+/// It tracks nesting depth and string state to find where each top-level
+/// element ends, copies just that element into `elem` and parses it with
+/// `serde_json`. Only the read buffer and one element are held in memory.
+struct JsonArrayElements<R: Read> {
+    reader: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    len: usize,
+    elem: Vec<u8>,
+    state: ScanState,
+    line: u64,
+    path: Option<PathBuf>,
+    record: u64,
+}
+
+impl<R: Read> JsonArrayElements<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![0; SCAN_BUF].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+            elem: Vec::new(),
+            state: ScanState::Start,
+            line: 1,
+            path: None,
+            record: 0,
+        }
+    }
+
+    fn loc(&self) -> Location {
+        Location { path: self.path.clone(), ..Location::default() }.with_line(self.line)
+    }
+
+    /// The next byte without consuming it, refilling the buffer as needed.
+    fn peek(&mut self) -> Result<Option<u8>, DataError> {
+        while self.pos == self.len {
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => return Ok(None),
+                Ok(n) => {
+                    self.pos = 0;
+                    self.len = n;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(DataError::io(self.loc(), e)),
+            }
+        }
+        Ok(Some(self.buf[self.pos]))
+    }
+
+    fn bump(&mut self) {
+        if self.buf[self.pos] == b'\n' {
+            self.line += 1;
+        }
+        self.pos += 1;
+    }
+
+    /// Skips whitespace and returns the next byte, if any.
+    fn skip_ws(&mut self) -> Result<Option<u8>, DataError> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                return Ok(Some(b));
+            }
+            self.bump();
+        }
+        Ok(None)
+    }
+
+    /// Ends the stream with a structural error.
+    fn fail(&mut self, msg: &str) -> DataError {
+        self.state = ScanState::Done;
+        DataError::parse(self.loc(), msg.to_string())
+    }
+
+    /// Copies the next top-level element into `self.elem`.
+    ///
+    /// In an array, the element ends at the `,` or `]` at depth 0 (which is consumed;
+    /// the returned flag is true for `]`). In a sequence, it ends when the first value
+    /// is complete.
+    fn scan_element(&mut self, in_array: bool) -> Result<bool, DataError> {
+        self.elem.clear();
+        let (mut depth, mut in_str, mut escaped) = (0usize, false, false);
+        loop {
+            let Some(b) = self.peek()? else {
+                if !in_array && depth == 0 && !in_str && !self.elem.is_empty() {
+                    return Ok(false); // a number or literal ending the input
+                }
+                return Err(self.fail("unexpected end of input inside JSON array"));
+            };
+            if in_str {
+                self.bump();
+                self.elem.push(b);
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_str = false;
+                    if depth == 0 && !in_array {
+                        return Ok(false);
+                    }
+                }
+                continue;
+            }
+            match b {
+                b',' | b']' if depth == 0 && in_array => {
+                    self.bump();
+                    return Ok(b == b']');
+                }
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth > 0 => depth -= 1,
+                b'"' => in_str = true,
+                _ if b.is_ascii_whitespace() && depth == 0 && !in_array && !self.elem.is_empty() => {
+                    return Ok(false);
+                }
+                _ => {}
+            }
+            self.bump();
+            self.elem.push(b);
+            if !in_array && depth == 0 && matches!(b, b'}' | b']') {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Reads up to the first value and decides between array and sequence mode.
+    fn start(&mut self) -> Result<(), DataError> {
+        self.state = match self.skip_ws()? {
+            None => ScanState::Done,
+            Some(b'[') => {
+                self.bump();
+                ScanState::Array { first: true }
+            }
+            Some(_) => ScanState::Sequence,
+        };
+        Ok(())
+    }
+
+    /// Scans the next element into `self.elem`. `Ok(None)` at the end of the array or input;
+    /// otherwise the flag tells whether the element was the last one of the array.
+    fn next_element(&mut self) -> Result<Option<bool>, DataError> {
+        self.skip_ws()?;
+        match self.state {
+            ScanState::Start | ScanState::Closed | ScanState::Done => Ok(None),
+            ScanState::Array { first } => {
+                match self.peek()? {
+                    Some(b']') if first => {
+                        self.bump();
+                        self.state = ScanState::Closed;
+                        return Ok(None);
+                    }
+                    Some(b',' | b']') => return Err(self.fail("expected a JSON value in array")),
+                    _ => {}
+                }
+                let closed = self.scan_element(true)?;
+                self.state = if closed { ScanState::Closed } else { ScanState::Array { first: false } };
+                Ok(Some(closed))
+            }
+            ScanState::Sequence => match self.peek()? {
+                None => {
+                    self.state = ScanState::Done;
+                    Ok(None)
+                }
+                Some(_) => self.scan_element(false).map(Some),
+            },
+        }
+    }
+
+    /// After the closing `]`, only whitespace may follow.
+    fn expect_end(&mut self) -> Result<(), DataError> {
+        match self.skip_ws()? {
+            None => Ok(()),
+            Some(_) => Err(self.fail("trailing characters after JSON array")),
+        }
+    }
+
+    /// The next element and its location, or `Err` if it could not be read or parsed.
+    fn next_value(&mut self) -> Option<Result<(Value, Location), DataError>> {
+        let step = (|| {
+            if self.state == ScanState::Start {
+                self.start()?;
+            }
+            // Skip whitespace first so the location points at the element itself.
+            if matches!(self.state, ScanState::Array { .. } | ScanState::Sequence) {
+                self.skip_ws()?;
+            }
+            let line = self.line;
+            Ok(self.next_element()?.map(|_| line))
+        })();
+        let line = match step {
+            Ok(Some(line)) => line,
+            Ok(None) if self.state == ScanState::Closed => {
+                self.state = ScanState::Done;
+                return self.expect_end().err().map(Err);
+            }
+            Ok(None) => return None,
+            Err(e) => {
+                self.state = ScanState::Done;
+                return Some(Err(e));
+            }
+        };
+        self.record += 1;
+        let loc = Location { path: self.path.clone(), ..Location::default() }.with_record(self.record).with_line(line);
+        Some(json::from_slice::<Value>(&self.elem).map(|v| (v, loc.clone())).map_err(|e| json_error(e, loc)))
+    }
 }
 
-impl JsonArrayScalarStream<File> {
+impl JsonArrayScalarStream<Box<dyn Read + Send>> {
     /// Creates a new JSON-array-backed scalar stream.
     ///
+    /// Compressed files are decompressed while streaming (see [`compression`]).
+    ///
     /// # Arguments
     /// * `path` – path to the JSON file.
-    /// * `key_path` – dotted key path to extract from each JSON element.
-    pub fn from_path(path: &str, key_path: impl Into<String>) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let file = File::open(path)?;
-        // Note: if the top-level is not an array, this will treat each
-        // top-level JSON value as one item instead.
-        let iter = json::Deserializer::from_reader(file).into_iter::<Value>();
-        Ok(Self { iter, key_path: key_path.into() })
+    /// * `key_path` – key path to extract from each JSON element (see [`json_path`](crate::json_path)).
+    pub fn from_path(path: impl AsRef<Path>, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(file, key_path)?;
+        s.elements.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: Read> JsonArrayScalarStream<R> {
+    /// Creates a new JSON-array-backed scalar stream from any reader.
+    ///
+    /// Fails only if `key_path` is not a valid path.
+    ///
+    /// # Arguments
+    /// * `reader` – source of JSON bytes; buffered internally.
+    /// * `key_path` – key path to extract from each JSON element (see [`json_path`](crate::json_path)).
+    pub fn from_reader(reader: R, key_path: impl AsRef<str>) -> Result<Self, DataError> {
+        Ok(Self { elements: JsonArrayElements::new(reader), extractor: Extractor::new(key_path.as_ref())? })
     }
+
+    /// Caps the number of values a fan-out path yields per element (the first `k` matches).
+    pub fn max_per_record(mut self, k: usize) -> Self { self.extractor.cap = Some(k); self }
+
+    /// Per-element contribution counts so far; final once the stream has returned `None`.
+    pub fn fan_out(&self) -> &FanOutStats { &self.extractor.stats }
 }
 
 impl<R: Read> ScalarStream for JsonArrayScalarStream<R> {
@@ -126,11 +594,135 @@ impl<R: Read> ScalarStream for JsonArrayScalarStream<R> {
     /// - `Some(Ok(f64))` → successfully parsed value.
     /// - `Some(Err(e))` → error while reading/parsing/extracting.
     /// - `None` → end of array (or file) reached.
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>> {
-        match self.iter.next() {
-            None => None,
-            Some(Ok(v)) => Some(extract_f64_by_path(&v, &self.key_path)),
-            Some(Err(e)) => Some(Err(Box::new(e))),
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            if let Some(item) = self.extractor.pending.pop_front() {
+                return Some(item);
+            }
+            match self.elements.next_value()? {
+                Ok((v, loc)) => self.extractor.push(&v, loc),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/* ----------------------------- records ----------------------------- */
+
+/// Parses the schema field names as singular JSON paths.
+fn record_paths(schema: &RecordSchema) -> Result<Vec<JsonPath>, DataError> {
+    schema.fields().iter().map(|f| {
+        let path = JsonPath::parse(&f.name)?;
+        if !path.is_singular() {
+            let loc = Location::default().with_column(f.name.clone());
+            return Err(DataError::schema(loc, "record fields need a path without wildcards or filters"));
         }
+        Ok(path)
+    }).collect()
+}
+
+/// Converts the value at each schema key path of `v` into a typed record.
+///
+/// Absent keys and JSON `null` become [`Field::Null`]. Numbers convert to
+/// strings for string fields and count as epoch seconds for timestamp fields;
+/// strings are parsed like CSV cells.
+fn json_record(v: &Value, schema: &Arc<RecordSchema>, paths: &[JsonPath], loc: Location) -> Result<Record, DataError> {
+    let mut values = Vec::with_capacity(schema.len());
+    for (spec, path) in schema.fields().iter().zip(paths) {
+        let field_loc = loc.clone().with_column(spec.name.clone());
+        let cur = path.get(v).ok();
+        let field = match (cur, spec.ty) {
+            (None | Some(Value::Null), _) => Field::Null,
+            (Some(Value::String(s)), ty) => ty.parse(s, &field_loc)?,
+            (Some(Value::Number(n)), FieldType::Num) => Field::Num(n.as_f64().unwrap_or(f64::NAN)),
+            (Some(Value::Number(n)), FieldType::Timestamp) => n.as_f64()
+                .and_then(timestamp_from_secs)
+                .map(Field::Timestamp)
+                .ok_or_else(|| DataError::parse(field_loc.clone(), format!("cannot convert {} to a timestamp", n)))?,
+            (Some(v @ (Value::Number(_) | Value::Bool(_))), FieldType::Str) => Field::Str(v.to_string()),
+            (Some(other), ty) => {
+                return Err(DataError::TypeMismatch { loc: field_loc, expected: ty.name(), found: json_type_name(other).into() });
+            }
+        };
+        values.push(field);
+    }
+    Ok(Record::new(schema.clone(), values, loc))
+}
+
+/// A [`RecordStream`] over an **NDJSON file**; schema field names are singular
+/// [JSON paths](crate::json_path).
+pub struct NdjsonRecordStream {
+    lines: NdjsonLines,
+    schema: Arc<RecordSchema>,
+    paths: Vec<JsonPath>,
+}
+
+impl NdjsonRecordStream {
+    /// Creates a new NDJSON-backed record stream.
+    ///
+    /// # Arguments
+    /// * `path` – path to the NDJSON file (compressed files are decompressed while streaming).
+    /// * `schema` – fields to extract; names are key paths.
+    pub fn from_path(path: impl AsRef<Path>, schema: RecordSchema) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(file, schema)?;
+        s.lines.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
     }
-}
\ No newline at end of file
+
+    /// Creates a new NDJSON-backed record stream from any reader.
+    ///
+    /// Fails only if a field name is not a valid singular path.
+    pub fn from_reader(reader: impl Read + Send + 'static, schema: RecordSchema) -> Result<Self, DataError> {
+        let paths = record_paths(&schema)?;
+        Ok(Self { lines: NdjsonLines::new(reader), schema: Arc::new(schema), paths })
+    }
+}
+
+impl RecordStream for NdjsonRecordStream {
+    fn schema(&self) -> &Arc<RecordSchema> { &self.schema }
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        Some(self.lines.next_value()?.and_then(|(v, loc)| json_record(&v, &self.schema, &self.paths, loc)))
+    }
+}
+
+/// A [`RecordStream`] over a **JSON array** of objects; schema field names are
+/// singular [JSON paths](crate::json_path).
+pub struct JsonArrayRecordStream<R: Read> {
+    elements: JsonArrayElements<R>,
+    schema: Arc<RecordSchema>,
+    paths: Vec<JsonPath>,
+}
+
+impl JsonArrayRecordStream<Box<dyn Read + Send>> {
+    /// Creates a new JSON-array-backed record stream.
+    ///
+    /// # Arguments
+    /// * `path` – path to the JSON file (compressed files are decompressed while streaming).
+    /// * `schema` – fields to extract; names are key paths.
+    pub fn from_path(path: impl AsRef<Path>, schema: RecordSchema) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(file, schema)?;
+        s.elements.path = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: Read> JsonArrayRecordStream<R> {
+    /// Creates a new JSON-array-backed record stream from any reader.
+    ///
+    /// Fails only if a field name is not a valid singular path.
+    pub fn from_reader(reader: R, schema: RecordSchema) -> Result<Self, DataError> {
+        let paths = record_paths(&schema)?;
+        Ok(Self { elements: JsonArrayElements::new(reader), schema: Arc::new(schema), paths })
+    }
+}
+
+impl<R: Read> RecordStream for JsonArrayRecordStream<R> {
+    fn schema(&self) -> &Arc<RecordSchema> { &self.schema }
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        Some(self.elements.next_value()?.and_then(|(v, loc)| json_record(&v, &self.schema, &self.paths, loc)))
+    }
+}
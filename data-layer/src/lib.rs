@@ -1,11 +1,109 @@
+//! Streaming data sources and bounded queries for differentially private pipelines.
+//!
+//! Every source implements [`ScalarStream`] (one `f64` per record) or
+//! [`RecordStream`](record::RecordStream) (typed rows) and reports failures as
+//! [`DataError`]. Most users only need the [`prelude`]:
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//!
+//! let src = CsvScalarStream::from_path_with("sales.csv", CsvOptions::by_name("amount")).unwrap();
+//! let dom = BoundedF64::new(0.0, 500.0);
+//! let (sum, n) = sum_stream(src, dom).unwrap();
+//! println!("sum = {sum} over {n} rows, sensitivity {}", l1_sens_sum(dom));
+//! ```
+
+pub mod error;
+pub mod checkpoint;
 pub mod stream;
-mod csv_stream;
-mod stream_queries;
-mod json_stream;
+pub mod csv_stream;
+pub mod stream_queries;
+pub mod json_stream;
+pub mod json_path;
+pub mod xml_stream;
+pub mod bad_records;
+pub mod iter_stream;
+pub mod compression;
+pub mod multi_file;
+pub mod nulls;
+pub mod record;
+pub mod contribution;
+pub mod tail;
+pub mod parallel;
+pub mod socket;
+pub mod schema;
+#[cfg(feature = "arrow")]
+pub mod arrow_stream;
+#[cfg(feature = "async")]
+pub mod async_stream;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_stream;
+
+pub use error::{DataError, DataErrorKind, Location};
+pub use stream::ScalarStream;
+
+/// Re-exports commonly used pieces.
+pub mod prelude {
+    pub use crate::error::{DataError, DataErrorKind, Location};
+    pub use crate::stream::ScalarStream;
+    pub use crate::record::{Field, FieldType, Record, RecordSchema, RecordStream};
+    pub use crate::csv_stream::{CsvColumn, CsvOptions, CsvRecordStream, CsvScalarStream};
+    pub use crate::json_path::JsonPath;
+    pub use crate::json_stream::{FanOutStats, JsonArrayRecordStream, JsonArrayScalarStream, NdjsonRecordStream, NdjsonScalarStream};
+    pub use crate::xml_stream::XmlScalarStream;
+    pub use crate::iter_stream::{FallibleStream, IterStream, VecStream};
+    pub use crate::multi_file::MultiFileStream;
+    pub use crate::bad_records::{BadRecordFilter, BadRecordPolicy, BadRecordReport};
+    pub use crate::nulls::NullPolicy;
+    pub use crate::tail::{TailCheckpoint, TailHandle, TailOptions, TailReader, TailStart};
+    pub use crate::contribution::{
+        ContributionBounder, ContributionBounds, Selection, UnitProjection, UnitStream,
+        l1_sens_count_unit, l1_sens_hist_count_unit, l1_sens_mean_unit, l1_sens_sum_unit_add_remove, l1_sens_sum_unit_replace,
+    };
+    pub use crate::checkpoint::{Checkpoint, Resumable, SourcePosition};
+    pub use crate::parallel::{Chunk, ParallelScan};
+    pub use crate::socket::{IngestOptions, SocketScalarStream, WireFormat};
+    pub use crate::schema::{ColumnSpec, PublicSample, TableSchema, Validated};
+    pub use crate::stream_queries::{
+        count_stream, histogram_stream, mean_stream, sum_stream, Aggregate, BoundedF64, CountState, ExactSum, HistogramState, SumState,
+        l1_sens_count, l1_sens_hist_count, l1_sens_mean, l1_sens_sum,
+    };
+    #[cfg(feature = "arrow")]
+    pub use crate::arrow_stream::{ArrowIpcScalarStream, ArrowScalarStream};
+    #[cfg(feature = "parquet")]
+    pub use crate::arrow_stream::ParquetScalarStream;
+    #[cfg(feature = "async")]
+    pub use crate::async_stream::{
+        count_stream_async, histogram_stream_async, mean_stream_async, sum_stream_async,
+        AsyncCsvStream, AsyncNdjsonStream, AsyncScalarStream, AsyncToSync, SyncToAsync,
+    };
+    #[cfg(feature = "sqlite")]
+    pub use crate::sqlite_stream::{SqliteRecordStream, SqliteScalarStream};
+}
 
 #[cfg(test)]
 mod tests {
+    mod common;
     mod test_stream_queries;
     mod test_csv_stream;
     mod test_json_stream;
+    mod test_json_path;
+    mod test_xml_stream;
+    mod test_bad_records;
+    mod test_iter_stream;
+    mod test_compression;
+    mod test_multi_file;
+    mod test_record;
+    mod test_contribution;
+    mod test_tail;
+    mod test_checkpoint;
+    mod test_parallel;
+    mod test_socket;
+    mod test_schema;
+    #[cfg(feature = "arrow")]
+    mod test_arrow_stream;
+    #[cfg(feature = "async")]
+    mod test_async_stream;
+    #[cfg(feature = "sqlite")]
+    mod test_sqlite_stream;
 }
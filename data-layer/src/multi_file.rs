@@ -0,0 +1,105 @@
+// src/multi_file.rs
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{DataError, Location};
+use crate::stream::ScalarStream;
+
+/// A `ScalarStream` that concatenates one source per file, e.g. daily partitions.
+///
+/// Files are opened lazily, one at a time, with a caller-supplied factory
+/// (`|p| NdjsonScalarStream::from_path(p, "v")`, `|p| CsvScalarStream::from_path_with(p, opts.clone())`, ...).
+///
+/// - Files are read in a deterministic order: lexicographic for
+///   [`from_glob`](Self::from_glob), the given order for [`from_paths`](Self::from_paths).
+/// - Every error carries the path of the file it came from. If a file cannot be
+///   opened, its error is yielded once and the stream continues with the next file.
+///
+/// # Example
+/// ```no_run
+/// use data_layer::json_stream::NdjsonScalarStream;
+/// use data_layer::multi_file::MultiFileStream;
+/// use data_layer::stream_queries::{sum_stream, BoundedF64};
+///
+/// let days = MultiFileStream::from_glob("data/2026-10-*.ndjson", |p| NdjsonScalarStream::from_path(p, "v")).unwrap();
+/// let (sum, n) = sum_stream(days, BoundedF64::new(0.0, 100.0)).unwrap();
+/// ```
+pub struct MultiFileStream<S, F> {
+    paths: Vec<PathBuf>,
+    next: usize,
+    factory: F,
+    current: Option<S>,
+}
+
+impl<S, F> MultiFileStream<S, F>
+where
+    S: ScalarStream,
+    F: FnMut(&Path) -> Result<S, DataError>,
+{
+    /// Streams the given files in the given order.
+    pub fn from_paths<P: Into<PathBuf>>(paths: impl IntoIterator<Item = P>, factory: F) -> Self {
+        Self { paths: paths.into_iter().map(Into::into).collect(), next: 0, factory, current: None }
+    }
+
+    /// Streams all files matching a glob pattern, sorted by path.
+    ///
+    /// Fails if the pattern is invalid, a directory cannot be read, or nothing matches
+    /// (an empty partition set is almost always a configuration error).
+    pub fn from_glob(pattern: &str, factory: F) -> Result<Self, DataError> {
+        let loc = || Location::default().with_column(pattern);
+        let entries = glob::glob(pattern)
+            .map_err(|e| DataError::parse(loc(), format!("invalid glob pattern: {}", e)))?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| {
+                let loc = Location::default().with_path(e.path());
+                DataError::io(loc, e.into())
+            })?;
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            return Err(DataError::io(loc(), io::Error::new(io::ErrorKind::NotFound, "glob matched no files")));
+        }
+        paths.sort();
+        Ok(Self::from_paths(paths, factory))
+    }
+
+    /// All files of this stream, in reading order.
+    pub fn paths(&self) -> &[PathBuf] { &self.paths }
+
+    /// The file currently being read, if any.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|_| self.paths[self.next - 1].as_path())
+    }
+}
+
+impl<S, F> ScalarStream for MultiFileStream<S, F>
+where
+    S: ScalarStream,
+    F: FnMut(&Path) -> Result<S, DataError>,
+{
+    /// Returns the next value of the current file, moving on to the next file at its end.
+    ///
+    /// - `Some(Ok(f64))` → successfully parsed value.
+    /// - `Some(Err(e))` → error in the current file (or while opening it), tagged with its path.
+    /// - `None` → all files exhausted.
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            if let Some(src) = self.current.as_mut() {
+                let path = &self.paths[self.next - 1];
+                match src.next_val() {
+                    Some(item) => return Some(item.map_err(|e| e.with_path(path))),
+                    None => self.current = None,
+                }
+            }
+            let path = self.paths.get(self.next)?;
+            self.next += 1;
+            match (self.factory)(path) {
+                Ok(src) => self.current = Some(src),
+                Err(e) => return Some(Err(e.with_path(path))),
+            }
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// src/nulls.rs
+
+/// How a source treats missing (null) values in a numeric column.
+///
+/// Like [`BadRecordPolicy`](crate::bad_records::BadRecordPolicy), the choice must be
+/// made before looking at the data. `Skip` drops records and so changes the count;
+/// `Replace` keeps every record and is the safer choice when the count matters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NullPolicy {
+    /// Drop null values silently.
+    Skip,
+    /// Yield a [`DataError::MissingKey`](crate::error::DataError::MissingKey) for each null.
+    Error,
+    /// Substitute a fixed value (e.g. a domain bound or `0.0`).
+    Replace(f64),
+}
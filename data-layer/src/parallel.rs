@@ -0,0 +1,250 @@
+// src/parallel.rs
+//! Parallel scanning of large uncompressed NDJSON and CSV files.
+//!
+//! [`ParallelScan`] splits a file into byte ranges that start at record
+//! boundaries, aggregates each range on its own thread into a mergeable
+//! [`Aggregate`] state and merges the states in file order:
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//!
+//! let dom = BoundedF64::new(0.0, 100.0);
+//! let scan = ParallelScan::csv("big.csv", CsvOptions::by_name("amount")).threads(8);
+//! let (sum, n) = scan.sum(dom).unwrap(); // same exact value as `sum_stream`
+//! ```
+//!
+//! The merged state is the same as the state of a sequential pass over the
+//! file: counts and histograms are integers and sums are exact (see
+//! [`ExactSum`](crate::stream_queries::ExactSum)). Noise is added afterwards,
+//! once, to the merged result.
+//!
+//! - NDJSON is split at line breaks.
+//! - CSV is split at line breaks outside quoted fields. To find them, every
+//!   range is first scanned (in parallel) for quote characters, so fields that
+//!   contain the quote character must be quoted as in RFC 4180.
+//! - Errors end the scan; the one that comes first in the file is returned,
+//!   with the same line and record numbers as in a sequential pass.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::checkpoint::Resumable;
+use crate::compression::{self, Compression};
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::error::{DataError, Location};
+use crate::json_path::JsonPath;
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+use crate::stream_queries::{Aggregate, BoundedF64, CountState, HistogramState, SumState};
+
+/// A byte range `start..end` of the input that starts at a record boundary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Clone, Debug)]
+enum Format {
+    Ndjson { key_path: String },
+    Csv(CsvOptions),
+}
+
+/// Runs queries over one file on several threads; see the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct ParallelScan {
+    path: PathBuf,
+    format: Format,
+    threads: usize,
+}
+
+/// Result of one worker: its state, its first error, and the lines and records it consumed.
+struct Partial<A> {
+    state: A,
+    error: Option<DataError>,
+    lines: u64,
+    records: u64,
+}
+
+const BUF: usize = 64 * 1024;
+
+impl ParallelScan {
+    /// Scans an NDJSON file, extracting `key_path` from each line.
+    ///
+    /// Fails only if `key_path` is not a valid path.
+    pub fn ndjson(path: impl AsRef<Path>, key_path: impl Into<String>) -> Result<Self, DataError> {
+        let key_path = key_path.into();
+        JsonPath::parse(&key_path)?;
+        Ok(Self { path: path.as_ref().to_path_buf(), format: Format::Ndjson { key_path }, threads: default_threads() })
+    }
+
+    /// Scans a CSV file with RFC 4180 parsing, as [`CsvScalarStream::from_path_with`].
+    pub fn csv(path: impl AsRef<Path>, opts: CsvOptions) -> Self {
+        Self { path: path.as_ref().to_path_buf(), format: Format::Csv(opts), threads: default_threads() }
+    }
+
+    /// Number of worker threads (and chunks); defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self { self.threads = threads.max(1); self }
+
+    /// Splits the file into at most `threads` non-empty chunks at record boundaries.
+    pub fn chunks(&self) -> Result<Vec<Chunk>, DataError> {
+        let loc = || Location::default().with_path(&self.path);
+        let (file, _, compression) = compression::open_detect(&self.path)?;
+        if compression != Compression::None {
+            return Err(DataError::io(loc(), io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("parallel scanning needs an uncompressed file, found {:?}", compression),
+            )));
+        }
+        let len = file.metadata().map_err(|e| DataError::io(loc(), e))?.len();
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let n = self.threads as u64;
+        let splits: Vec<u64> = (0..=n).map(|i| len * i / n).collect();
+        let quote = match &self.format {
+            Format::Csv(opts) => Some(opts.quote),
+            Format::Ndjson { .. } => None,
+        };
+        // Whether each split point lies inside a quoted field.
+        let mut in_quotes = vec![false; splits.len()];
+        if let Some(q) = quote {
+            let parities = thread::scope(|scope| {
+                let workers: Vec<_> = splits.windows(2)
+                    .map(|w| scope.spawn(move || quote_parity(&self.path, w[0], w[1], q)))
+                    .collect();
+                workers.into_iter().map(|w| w.join().expect("quote scan panicked")).collect::<io::Result<Vec<_>>>()
+            }).map_err(|e| DataError::io(loc(), e))?;
+            for (i, odd) in parities.into_iter().enumerate() {
+                in_quotes[i + 1] = in_quotes[i] ^ odd;
+            }
+        }
+
+        let mut bounds = vec![0];
+        for i in 1..splits.len() - 1 {
+            let b = next_boundary(&self.path, splits[i], in_quotes[i], quote).map_err(|e| DataError::io(loc(), e))?;
+            bounds.push(b.unwrap_or(len));
+        }
+        bounds.push(len);
+        bounds.dedup();
+        Ok(bounds.windows(2).map(|w| Chunk { start: w[0], end: w[1] }).collect())
+    }
+
+    /// Aggregates every chunk into a clone of `init` and merges the results in file order.
+    pub fn run<A: Aggregate + Clone + Send>(&self, init: A) -> Result<A, DataError> {
+        let chunks = self.chunks()?;
+        let partials = thread::scope(|scope| {
+            let workers: Vec<_> = chunks.iter()
+                .map(|&chunk| {
+                    let init = init.clone();
+                    scope.spawn(move || self.scan_chunk(chunk, init))
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().expect("scan worker panicked")).collect::<Result<Vec<_>, _>>()
+        })?;
+
+        let (mut state, mut lines, mut records) = (init, 0, 0);
+        for p in partials {
+            if let Some(e) = p.error {
+                return Err(e.shifted(lines, records));
+            }
+            state.merge(p.state);
+            lines += p.lines;
+            records += p.records;
+        }
+        Ok(state)
+    }
+
+    /// COUNT, as [`count_stream`](crate::stream_queries::count_stream).
+    pub fn count(&self) -> Result<usize, DataError> {
+        Ok(self.run(CountState::new())?.result())
+    }
+
+    /// SUM with clamping, as [`sum_stream`](crate::stream_queries::sum_stream).
+    pub fn sum(&self, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+        Ok(self.run(SumState::new(dom))?.result())
+    }
+
+    /// MEAN with clamping, as [`mean_stream`](crate::stream_queries::mean_stream).
+    pub fn mean(&self, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+        Ok(self.run(SumState::new(dom))?.mean())
+    }
+
+    /// HISTOGRAM, as [`histogram_stream`](crate::stream_queries::histogram_stream).
+    pub fn histogram(&self, dom: BoundedF64, bins: usize) -> Result<Vec<(f64, f64, usize)>, DataError> {
+        Ok(self.run(HistogramState::new(dom, bins))?.result())
+    }
+
+    fn scan_chunk<A: Aggregate>(&self, chunk: Chunk, init: A) -> Result<Partial<A>, DataError> {
+        match &self.format {
+            Format::Ndjson { key_path } => {
+                let s = NdjsonScalarStream::chunk(&self.path, key_path, chunk.start, chunk.end)?;
+                Ok(aggregate(s, init))
+            }
+            Format::Csv(opts) => {
+                let s = CsvScalarStream::chunk_with(&self.path, opts.clone(), chunk.start, chunk.end)?;
+                Ok(aggregate(s, init))
+            }
+        }
+    }
+}
+
+fn aggregate<S: ScalarStream + Resumable, A: Aggregate>(mut s: S, mut state: A) -> Partial<A> {
+    let error = state.feed(&mut s, usize::MAX).err();
+    let pos = s.position();
+    Partial { state, error, lines: pos.line, records: pos.record }
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Opens `path` positioned at `start`, limited to `len` bytes.
+fn open_range(path: &Path, start: u64, len: u64) -> io::Result<BufReader<io::Take<File>>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    Ok(BufReader::with_capacity(BUF, file.take(len)))
+}
+
+/// Whether `start..end` contains an odd number of `quote` bytes.
+fn quote_parity(path: &Path, start: u64, end: u64, quote: u8) -> io::Result<bool> {
+    let mut r = open_range(path, start, end - start)?;
+    let mut buf = vec![0u8; BUF];
+    let mut odd = false;
+    loop {
+        let n = match r.read(&mut buf) {
+            Ok(0) => return Ok(odd),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        odd ^= buf[..n].iter().filter(|&&b| b == quote).count() % 2 == 1;
+    }
+}
+
+/// The offset just after the first line break at or after `from` that is not
+/// inside a quoted field, or `None` at end of file.
+fn next_boundary(path: &Path, from: u64, mut in_quotes: bool, quote: Option<u8>) -> io::Result<Option<u64>> {
+    let mut r = open_range(path, from, u64::MAX)?;
+    let mut buf = vec![0u8; BUF];
+    let mut pos = from;
+    loop {
+        let n = match r.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for (i, &b) in buf[..n].iter().enumerate() {
+            if Some(b) == quote {
+                in_quotes = !in_quotes;
+            } else if b == b'\n' && !in_quotes {
+                return Ok(Some(pos + i as u64 + 1));
+            }
+        }
+        pos += n as u64;
+    }
+}
@@ -0,0 +1,434 @@
+// src/record.rs
+//! Record-oriented streams: typed multi-column rows instead of single `f64` values.
+//!
+//! A [`RecordStream`] yields [`Record`]s whose fields follow a [`RecordSchema`]
+//! declared up front. Queries that need several fields at once (covariance,
+//! group-by sums, filtering on one column while aggregating another, per-user
+//! bounding) work on records; [`RecordStream::project`] and
+//! [`RecordStream::map_scalar`] turn a record stream back into a [`ScalarStream`]
+//! for `stream_queries`, `preprocessing` and `mechanisms`.
+//!
+//! Sources: [`CsvRecordStream`](crate::csv_stream::CsvRecordStream),
+//! [`NdjsonRecordStream`](crate::json_stream::NdjsonRecordStream) and
+//! [`JsonArrayRecordStream`](crate::json_stream::JsonArrayRecordStream).
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::{DataError, Location};
+use crate::nulls::NullPolicy;
+use crate::stream::ScalarStream;
+
+/// Type of a record field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    /// Numeric value, stored as `f64`.
+    Num,
+    /// String or categorical value.
+    Str,
+    /// Point in time, stored as milliseconds since the Unix epoch (UTC).
+    Timestamp,
+}
+
+impl FieldType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::Num => "number",
+            FieldType::Str => "string",
+            FieldType::Timestamp => "timestamp",
+        }
+    }
+
+    /// Parses a text cell as this type. Empty (after trimming) cells are `Null`.
+    ///
+    /// Timestamps accept RFC 3339 / ISO 8601 (`2026-10-17T08:30:00Z`,
+    /// `2026-10-17 08:30:00.250+02:00`, `2026-10-17`) and plain numbers as
+    /// seconds since the epoch.
+    pub fn parse(&self, raw: &str, loc: &Location) -> Result<Field, DataError> {
+        let s = raw.trim();
+        if s.is_empty() {
+            return Ok(Field::Null);
+        }
+        match self {
+            FieldType::Num => s.parse::<f64>()
+                .map(Field::Num)
+                .map_err(|e| DataError::parse(loc.clone(), format!("cannot parse '{}' as f64: {}", s, e))),
+            FieldType::Str => Ok(Field::Str(raw.to_string())),
+            FieldType::Timestamp => parse_timestamp(s)
+                .or_else(|| s.parse::<f64>().ok().and_then(timestamp_from_secs))
+                .map(Field::Timestamp)
+                .ok_or_else(|| DataError::parse(loc.clone(), format!("cannot parse '{}' as timestamp", s))),
+        }
+    }
+}
+
+/// One typed value of a [`Record`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Field {
+    /// The value is missing (empty cell, JSON `null` or absent key).
+    Null,
+    Num(f64),
+    Str(String),
+    /// Milliseconds since the Unix epoch (UTC).
+    Timestamp(i64),
+}
+
+impl Field {
+    pub fn is_null(&self) -> bool { matches!(self, Field::Null) }
+
+    /// The numeric value of a `Num` field, or a timestamp in epoch milliseconds.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Field::Num(v) => Some(*v),
+            Field::Timestamp(ms) => Some(*ms as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Field::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_timestamp(&self) -> Option<i64> {
+        match self {
+            Field::Timestamp(ms) => Some(*ms),
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Field::Null => "null",
+            Field::Num(_) => FieldType::Num.name(),
+            Field::Str(_) => FieldType::Str.name(),
+            Field::Timestamp(_) => FieldType::Timestamp.name(),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Null => write!(f, "null"),
+            Field::Num(v) => write!(f, "{}", v),
+            Field::Str(s) => write!(f, "{}", s),
+            Field::Timestamp(ms) => write!(f, "{}ms", ms),
+        }
+    }
+}
+
+/// Name and type of one field of a [`RecordSchema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: String,
+    pub ty: FieldType,
+}
+
+/// The ordered list of fields every record of a stream has.
+///
+/// Field names are looked up in the source: CSV header names (or positions
+/// without a header), dotted key paths for JSON.
+///
+/// ```
+/// use data_layer::record::{FieldType, RecordSchema};
+///
+/// let schema = RecordSchema::new().str("user").num("amount").timestamp("ts");
+/// assert_eq!(schema.index_of("amount"), Some(1));
+/// assert_eq!(schema.fields()[2].ty, FieldType::Timestamp);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecordSchema {
+    fields: Vec<FieldSpec>,
+}
+
+impl RecordSchema {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn field(mut self, name: impl Into<String>, ty: FieldType) -> Self {
+        self.fields.push(FieldSpec { name: name.into(), ty });
+        self
+    }
+
+    pub fn num(self, name: impl Into<String>) -> Self { self.field(name, FieldType::Num) }
+    pub fn str(self, name: impl Into<String>) -> Self { self.field(name, FieldType::Str) }
+    pub fn timestamp(self, name: impl Into<String>) -> Self { self.field(name, FieldType::Timestamp) }
+
+    pub fn fields(&self) -> &[FieldSpec] { &self.fields }
+
+    pub fn len(&self) -> usize { self.fields.len() }
+
+    pub fn is_empty(&self) -> bool { self.fields.is_empty() }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.name == name)
+    }
+}
+
+/// A typed row. Fields are in schema order; `loc` tells where the row came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    schema: Arc<RecordSchema>,
+    values: Vec<Field>,
+    loc: Location,
+}
+
+impl Record {
+    /// Builds a record; `values` must have one entry per schema field.
+    pub fn new(schema: Arc<RecordSchema>, values: Vec<Field>, loc: Location) -> Self {
+        assert_eq!(schema.len(), values.len(), "record must have one value per schema field");
+        Self { schema, values, loc }
+    }
+
+    pub fn schema(&self) -> &RecordSchema { &self.schema }
+
+    pub fn values(&self) -> &[Field] { &self.values }
+
+    pub fn location(&self) -> &Location { &self.loc }
+
+    /// The field called `name`, or `None` if the schema has no such field.
+    pub fn get(&self, name: &str) -> Option<&Field> {
+        self.schema.index_of(name).map(|i| &self.values[i])
+    }
+
+    pub fn get_index(&self, i: usize) -> Option<&Field> { self.values.get(i) }
+
+    /// The numeric value of field `name`, with errors located at this record.
+    ///
+    /// Returns `Ok(None)` for a null, and an error if the field does not exist
+    /// or is a string.
+    pub fn num(&self, name: &str) -> Result<Option<f64>, DataError> {
+        let loc = self.loc.clone().with_column(name);
+        let field = self.get(name).ok_or_else(|| DataError::missing_key(loc.clone(), name))?;
+        match field {
+            Field::Null => Ok(None),
+            Field::Str(_) => Err(DataError::TypeMismatch { loc, expected: "number or timestamp", found: field.type_name().into() }),
+            f => Ok(f.as_f64()),
+        }
+    }
+
+    pub fn into_values(self) -> Vec<Field> { self.values }
+}
+
+/// Trait for a stream of typed records, returning each record or an error until the stream ends.
+///
+/// Like [`ScalarStream`], a malformed record is `Some(Err(..))` and the stream
+/// can usually continue with the next record.
+pub trait RecordStream {
+    /// The schema every yielded record follows.
+    fn schema(&self) -> &Arc<RecordSchema>;
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>>;
+
+    /// A `ScalarStream` over one numeric or timestamp field.
+    ///
+    /// Fails if the schema has no field called `name` or it is a string field.
+    fn project(self, name: &str, nulls: NullPolicy) -> Result<Projection<Self>, DataError>
+    where
+        Self: Sized,
+    {
+        let loc = Location::default().with_column(name);
+        let index = self.schema().index_of(name).ok_or_else(|| DataError::missing_key(loc.clone(), name))?;
+        let ty = self.schema().fields()[index].ty;
+        if ty == FieldType::Str {
+            return Err(DataError::TypeMismatch { loc, expected: "number or timestamp", found: ty.name().into() });
+        }
+        Ok(Projection { src: self, index, name: name.to_string(), nulls })
+    }
+
+    /// A `ScalarStream` computed from whole records.
+    ///
+    /// `f` returns `Ok(Some(v))` to yield `v`, `Ok(None)` to drop the record
+    /// (e.g. a filter on another column) and `Err` for a bad record.
+    fn map_scalar<F>(self, f: F) -> MapScalar<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Record) -> Result<Option<f64>, DataError>,
+    {
+        MapScalar { src: self, f }
+    }
+}
+
+impl<R: RecordStream + ?Sized> RecordStream for &mut R {
+    fn schema(&self) -> &Arc<RecordSchema> { (**self).schema() }
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        (**self).next_record()
+    }
+}
+
+impl<R: RecordStream + ?Sized> RecordStream for Box<R> {
+    fn schema(&self) -> &Arc<RecordSchema> { (**self).schema() }
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        (**self).next_record()
+    }
+}
+
+/// One field of a [`RecordStream`] as a [`ScalarStream`]; see [`RecordStream::project`].
+///
+/// Timestamps are yielded as epoch milliseconds. Nulls follow the [`NullPolicy`].
+pub struct Projection<R> {
+    src: R,
+    index: usize,
+    name: String,
+    nulls: NullPolicy,
+}
+
+impl<R> Projection<R> {
+    pub fn into_inner(self) -> R { self.src }
+}
+
+impl<R: RecordStream> ScalarStream for Projection<R> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            let rec = match self.src.next_record()? {
+                Ok(rec) => rec,
+                Err(e) => return Some(Err(e)),
+            };
+            match &rec.values[self.index] {
+                Field::Null => match self.nulls {
+                    NullPolicy::Skip => continue,
+                    NullPolicy::Replace(v) => return Some(Ok(v)),
+                    NullPolicy::Error => {
+                        let loc = rec.loc.with_column(self.name.clone());
+                        return Some(Err(DataError::missing_key(loc, format!("{} (null)", self.name))));
+                    }
+                },
+                f => return Some(f.as_f64().ok_or_else(|| DataError::TypeMismatch {
+                    loc: rec.loc.clone().with_column(self.name.clone()),
+                    expected: "number or timestamp",
+                    found: f.type_name().into(),
+                })),
+            }
+        }
+    }
+}
+
+/// A [`ScalarStream`] computed from whole records; see [`RecordStream::map_scalar`].
+pub struct MapScalar<R, F> {
+    src: R,
+    f: F,
+}
+
+impl<R, F> ScalarStream for MapScalar<R, F>
+where
+    R: RecordStream,
+    F: FnMut(&Record) -> Result<Option<f64>, DataError>,
+{
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            let rec = match self.src.next_record()? {
+                Ok(rec) => rec,
+                Err(e) => return Some(Err(e)),
+            };
+            match (self.f)(&rec) {
+                Ok(Some(v)) => return Some(Ok(v)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/* ----------------------------- timestamps ----------------------------- */
+
+/// Parses `YYYY-MM-DD[(T| )HH:MM[:SS[.fff]]][Z|±HH:MM|±HHMM]` into epoch milliseconds.
+/// A timestamp without offset is taken as UTC.
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let b = s.as_bytes();
+    let num = |r: std::ops::Range<usize>| -> Option<i64> {
+        let part = b.get(r)?;
+        if part.is_empty() || !part.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(part).ok()?.parse().ok()
+    };
+
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    if b.get(4) != Some(&b'-') || b.get(7) != Some(&b'-') || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * 86_400_000;
+    let mut i = 10;
+    if i == b.len() {
+        return Some(ms);
+    }
+
+    if b[i] != b'T' && b[i] != b't' && b[i] != b' ' {
+        return None;
+    }
+    let (hour, minute) = (num(i + 1..i + 3)?, num(i + 4..i + 6)?);
+    if b.get(i + 3) != Some(&b':') || hour > 23 || minute > 59 {
+        return None;
+    }
+    ms += (hour * 3600 + minute * 60) * 1000;
+    i += 6;
+    if b.get(i) == Some(&b':') {
+        let second = num(i + 1..i + 3)?;
+        if second > 60 {
+            return None;
+        }
+        ms += second * 1000;
+        i += 3;
+        if b.get(i) == Some(&b'.') || b.get(i) == Some(&b',') {
+            let start = i + 1;
+            i = start;
+            while b.get(i).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+            }
+            if i == start {
+                return None;
+            }
+            let frac = &s[start..i.min(start + 3)];
+            ms += frac.parse::<i64>().ok()? * 10i64.pow(3 - frac.len() as u32);
+        }
+    }
+
+    match b.get(i) {
+        None => Some(ms),
+        Some(b'Z' | b'z') if i + 1 == b.len() => Some(ms),
+        Some(&sign @ (b'+' | b'-')) => {
+            let oh = num(i + 1..i + 3)?;
+            let om = match b.len() - i {
+                6 if b[i + 3] == b':' => num(i + 4..i + 6)?,
+                5 => num(i + 3..i + 5)?,
+                3 => 0,
+                _ => return None,
+            };
+            if oh > 23 || om > 59 {
+                return None;
+            }
+            let offset = (oh * 60 + om) * 60_000;
+            Some(if sign == b'+' { ms - offset } else { ms + offset })
+        }
+        _ => None,
+    }
+}
+
+/// Epoch seconds (possibly fractional) to epoch milliseconds; `None` for non-finite input.
+pub(crate) fn timestamp_from_secs(secs: f64) -> Option<i64> {
+    secs.is_finite().then(|| (secs * 1000.0).round() as i64)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (H. Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,329 @@
+// src/schema.rs
+//! Schema inference from public samples, and validation of record streams.
+//!
+//! Which columns exist and which of them are numeric is information about the
+//! data. Inferring it from the private dataset and then choosing a query based
+//! on the result leaks that information outside any privacy budget. Inference
+//! therefore only accepts a [`PublicSample`]: a file the caller declares to be
+//! public, synthetic or otherwise safe to inspect (e.g. a published extract with
+//! the same layout). The private data is then read against that schema, and
+//! [`TableSchema::validate`] reports records that do not fit it:
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//!
+//! let sample = PublicSample::declare("docs/sales_example.csv");
+//! let schema = TableSchema::infer_csv(&sample, b',', true, 1000).unwrap();
+//! println!("numeric columns: {:?}", schema.numeric_columns());
+//!
+//! let src = CsvRecordStream::from_path("private/sales.csv", schema.to_record_schema(), b',', true).unwrap();
+//! let src = schema.validate(src).unwrap();
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::compression;
+use crate::csv_stream::csv_error;
+use crate::error::{DataError, Location};
+use crate::json_stream::NdjsonLines;
+use crate::record::{FieldType, Record, RecordSchema, RecordStream};
+
+/// A file the caller declares to be public (or synthetic) data.
+///
+/// Constructing one is an explicit statement that inspecting the file does not
+/// need privacy protection. Never declare the private input itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicSample {
+    path: PathBuf,
+}
+
+impl PublicSample {
+    /// Declares `path` as public or synthetic sample data.
+    pub fn declare(path: impl AsRef<Path>) -> Self { Self { path: path.as_ref().to_path_buf() } }
+
+    pub fn path(&self) -> &Path { &self.path }
+}
+
+/// Name, type and nullability of one column of a [`TableSchema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub ty: FieldType,
+    /// Whether the column may be empty, `null` or absent.
+    pub nullable: bool,
+}
+
+/// Columns of a tabular source, inferred from a [`PublicSample`] or declared by hand.
+///
+/// Column names are CSV header names (positions `"0"`, `"1"`, … without a
+/// header) or JSON paths for NDJSON, as in [`RecordSchema`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TableSchema {
+    columns: Vec<ColumnSpec>,
+}
+
+impl TableSchema {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn column(mut self, name: impl Into<String>, ty: FieldType, nullable: bool) -> Self {
+        self.columns.push(ColumnSpec { name: name.into(), ty, nullable });
+        self
+    }
+
+    pub fn columns(&self) -> &[ColumnSpec] { &self.columns }
+
+    pub fn get(&self, name: &str) -> Option<&ColumnSpec> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Names of the numeric columns, in column order.
+    pub fn numeric_columns(&self) -> Vec<&str> {
+        self.columns.iter().filter(|c| c.ty == FieldType::Num).map(|c| c.name.as_str()).collect()
+    }
+
+    /// The record schema for reading every column with a record source.
+    pub fn to_record_schema(&self) -> RecordSchema {
+        self.columns.iter().fold(RecordSchema::new(), |s, c| s.field(c.name.clone(), c.ty))
+    }
+
+    /// Infers the columns of a CSV sample from its first `max_records` rows.
+    ///
+    /// # Arguments
+    /// * `sample` – the public sample (compressed files are decompressed).
+    /// * `delimiter` – delimiter as a single byte (e.g. `b','`).
+    /// * `has_header` – whether the first row holds the column names.
+    /// * `max_records` – number of rows to inspect after the header.
+    pub fn infer_csv(sample: &PublicSample, delimiter: u8, has_header: bool, max_records: usize) -> Result<Self, DataError> {
+        let loc = Location::default().with_path(sample.path());
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(has_header)
+            .flexible(true)
+            .from_reader(compression::open_path(sample.path())?);
+        let mut inference = Inference::default();
+        if has_header {
+            for name in reader.headers().map_err(|e| csv_error(e, &loc))? {
+                inference.column(name.trim());
+            }
+        }
+        let mut record = csv::StringRecord::new();
+        let mut rows = 0;
+        while rows < max_records && reader.read_record(&mut record).map_err(|e| csv_error(e, &loc))? {
+            rows += 1;
+            let first = inference.columns.len();
+            for (i, cell) in record.iter().enumerate() {
+                let col = match has_header {
+                    true if i >= inference.columns.len() => break,
+                    true => i,
+                    false => inference.column(&i.to_string()),
+                };
+                inference.observe_text(col, cell);
+            }
+            inference.added_since(first, rows);
+            // Columns missing from a short row count as empty.
+            for col in record.len()..inference.columns.len() {
+                inference.columns[col].nulls = true;
+            }
+        }
+        Ok(inference.finish())
+    }
+
+    /// Infers the columns of an NDJSON sample from its first `max_records` objects.
+    ///
+    /// Nested objects are flattened into dotted paths (`a.b`); arrays are not
+    /// inferred (select their elements with a fan-out path instead). A key missing
+    /// from some of the objects makes its column nullable.
+    pub fn infer_ndjson(sample: &PublicSample, max_records: usize) -> Result<Self, DataError> {
+        let mut lines = NdjsonLines::new(compression::open_path(sample.path())?);
+        lines.path = Some(sample.path().to_path_buf());
+        let mut inference = Inference::default();
+        let mut rows = 0;
+        while rows < max_records {
+            let Some(next) = lines.next_value() else { break };
+            let (v, _) = next?;
+            rows += 1;
+            let first = inference.columns.len();
+            let mut seen = vec![false; first];
+            inference.observe_json(&mut seen, &mut Vec::new(), &v);
+            inference.added_since(first, rows);
+            for (col, seen) in seen.into_iter().enumerate() {
+                inference.columns[col].nulls |= !seen;
+            }
+        }
+        Ok(inference.finish())
+    }
+
+    /// Checks that `src` reads every column of this schema with the declared type,
+    /// and returns a stream that reports records with nulls in non-nullable columns.
+    ///
+    /// Columns the stream reads but the schema does not declare are passed through.
+    pub fn validate<R: RecordStream>(&self, src: R) -> Result<Validated<R>, DataError> {
+        let mut required = Vec::new();
+        for c in &self.columns {
+            let loc = Location::default().with_column(c.name.clone());
+            let i = src.schema().index_of(&c.name).ok_or_else(|| DataError::missing_key(loc.clone(), c.name.clone()))?;
+            let ty = src.schema().fields()[i].ty;
+            if ty != c.ty {
+                return Err(DataError::schema(loc, format!("declared as {}, read as {}", c.ty.name(), ty.name())));
+            }
+            if !c.nullable {
+                required.push(i);
+            }
+        }
+        Ok(Validated { src, required })
+    }
+}
+
+/// Per-column observations while inferring.
+#[derive(Debug)]
+struct Observed {
+    name: String,
+    values: usize,
+    nulls: bool,
+    num: bool,
+    timestamp: bool,
+}
+
+#[derive(Debug, Default)]
+struct Inference {
+    columns: Vec<Observed>,
+}
+
+impl Inference {
+    /// Index of column `name`, added if new.
+    fn column(&mut self, name: &str) -> usize {
+        if let Some(i) = self.columns.iter().position(|c| c.name == name) {
+            return i;
+        }
+        self.columns.push(Observed { name: name.to_string(), values: 0, nulls: false, num: true, timestamp: true });
+        self.columns.len() - 1
+    }
+
+    /// Records a text cell; types are tried as by [`FieldType::parse`].
+    fn observe_text(&mut self, col: usize, raw: &str) {
+        let c = &mut self.columns[col];
+        let s = raw.trim();
+        if s.is_empty() {
+            c.nulls = true;
+            return;
+        }
+        c.values += 1;
+        c.num &= s.parse::<f64>().is_ok();
+        c.timestamp &= FieldType::Timestamp.parse(s, &Location::default()).is_ok();
+    }
+
+    fn observe_json(&mut self, seen: &mut Vec<bool>, path: &mut Vec<String>, v: &Value) {
+        if let Value::Object(map) = v {
+            for (key, child) in map {
+                path.push(key.clone());
+                self.observe_json(seen, path, child);
+                path.pop();
+            }
+            return;
+        }
+        if path.is_empty() || v.is_array() {
+            return;
+        }
+        let Some(name) = json_path_of(path) else { return };
+        let col = self.column(&name);
+        seen.resize(self.columns.len(), false);
+        seen[col] = true;
+        match v {
+            Value::Null => self.columns[col].nulls = true,
+            Value::String(s) => self.observe_text(col, s),
+            Value::Number(_) => self.columns[col].values += 1,
+            _ => {
+                let c = &mut self.columns[col];
+                c.values += 1;
+                c.num = false;
+                c.timestamp = false;
+            }
+        }
+    }
+
+    /// Marks columns from index `first` on as nullable if they were first seen
+    /// after row 1 (the earlier rows did not have them).
+    fn added_since(&mut self, first: usize, rows: usize) {
+        if rows > 1 {
+            self.columns[first..].iter_mut().for_each(|c| c.nulls = true);
+        }
+    }
+
+    fn finish(self) -> TableSchema {
+        let columns = self.columns.into_iter().map(|c| {
+            let ty = if c.values == 0 {
+                FieldType::Str
+            } else if c.num {
+                FieldType::Num
+            } else if c.timestamp {
+                FieldType::Timestamp
+            } else {
+                FieldType::Str
+            };
+            ColumnSpec { name: c.name, ty, nullable: c.nulls }
+        });
+        TableSchema { columns: columns.collect() }
+    }
+}
+
+/// A JSON path for a key sequence: dotted where possible, bracketed otherwise.
+/// `None` for keys that cannot be written in either notation.
+fn json_path_of(keys: &[String]) -> Option<String> {
+    let plain = |k: &str| {
+        !k.is_empty() && !k.bytes().all(|b| b.is_ascii_digit()) && k.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    };
+    if keys.iter().all(|k| plain(k)) {
+        return Some(keys.join("."));
+    }
+    let mut path = String::from("$");
+    for k in keys {
+        if plain(k) {
+            path.push('.');
+            path.push_str(k);
+        } else if !k.contains('\'') {
+            path.push_str(&format!("['{}']", k));
+        } else if !k.contains('"') {
+            path.push_str(&format!("[\"{}\"]", k));
+        } else {
+            return None;
+        }
+    }
+    Some(path)
+}
+
+/// A record stream checked against a [`TableSchema`]; see [`TableSchema::validate`].
+pub struct Validated<R> {
+    src: R,
+    required: Vec<usize>,
+}
+
+impl<R> Validated<R> {
+    pub fn into_inner(self) -> R { self.src }
+}
+
+impl<R: RecordStream> RecordStream for Validated<R> {
+    fn schema(&self) -> &std::sync::Arc<RecordSchema> { self.src.schema() }
+
+    /// Returns the next record of the source.
+    ///
+    /// - `Some(Ok(Record))` → record that fits the schema.
+    /// - `Some(Err(e))` → error of the source, or a [`DataError::Schema`] for a
+    ///   null in a non-nullable column (located at that record and column).
+    /// - `None` → source exhausted.
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        let rec = match self.src.next_record()? {
+            Ok(rec) => rec,
+            Err(e) => return Some(Err(e)),
+        };
+        for &i in &self.required {
+            if rec.values()[i].is_null() {
+                let name = &rec.schema().fields()[i].name;
+                let loc = rec.location().clone().with_column(name.clone());
+                return Some(Err(DataError::schema(loc, "null in a non-nullable column")));
+            }
+        }
+        Some(Ok(rec))
+    }
+}
@@ -0,0 +1,232 @@
+// src/socket.rs
+//! Push ingestion over local sockets and pipes.
+//!
+//! A long-lived aggregator accepts connections (TCP, Unix domain sockets) or
+//! opens a named pipe (FIFO) and reads newline-delimited records from it as a
+//! [`ScalarStream`]:
+//!
+//! ```no_run
+//! use std::net::TcpListener;
+//! use data_layer::prelude::*;
+//!
+//! let listener = TcpListener::bind("127.0.0.1:7070").unwrap();
+//! let opts = IngestOptions::new(WireFormat::Numeric).max_records(100);
+//! loop {
+//!     let s = SocketScalarStream::accept_tcp(&listener, opts.clone()).unwrap();
+//!     let (sum, n) = sum_stream(s, BoundedF64::new(0.0, 10.0)).unwrap();
+//!     // ...
+//! }
+//! ```
+//!
+//! - End of stream: the stream ends when the peer closes the connection (or
+//!   the last writer closes the pipe).
+//! - Backpressure: input is read only while the consumer pulls values, with a
+//!   fixed-size buffer in between. A slow consumer fills the kernel buffers and
+//!   blocks the sender instead of growing memory.
+//! - Record limit: with [`IngestOptions::max_records`] the stream ends after
+//!   that many records and the rest of the input is never read. When one
+//!   connection carries one contributor's data, this enforces a per-contributor
+//!   bound independently of what the sender does.
+//!
+//! Records are lines; blank lines are skipped and not counted. CSV fields must
+//! therefore not contain line breaks.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::error::{DataError, Location};
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+
+/// Size of the buffer between the connection and the parser.
+const BUF: usize = 64 * 1024;
+
+/// How the records on the wire are encoded.
+#[derive(Clone, Debug)]
+pub enum WireFormat {
+    /// One number per line.
+    Numeric,
+    /// CSV rows (RFC 4180 quoting, one row per line).
+    Csv(CsvOptions),
+    /// NDJSON objects; `key_path` is extracted from each (see [`json_path`](crate::json_path)).
+    Ndjson { key_path: String },
+}
+
+/// Format and record limit of an ingestion source.
+#[derive(Clone, Debug)]
+pub struct IngestOptions {
+    pub format: WireFormat,
+    pub max_records: Option<u64>,
+}
+
+impl IngestOptions {
+    /// Options for `format` without a record limit.
+    pub fn new(format: WireFormat) -> Self { Self { format, max_records: None } }
+
+    /// Ends the stream after `n` records (the CSV header row is not counted).
+    pub fn max_records(mut self, n: u64) -> Self { self.max_records = Some(n); self }
+}
+
+/// A `Read` that passes through at most `max` non-blank lines of its input, one
+/// line per `read` call, and counts them.
+struct RecordLimit<R> {
+    inner: BufReader<R>,
+    max: Option<u64>,
+    /// Content lines still to pass through without counting (a CSV header).
+    uncounted: u64,
+    line_has_content: bool,
+    records: Arc<AtomicU64>,
+}
+
+impl<R: Read> RecordLimit<R> {
+    fn count_line(&mut self) {
+        if std::mem::take(&mut self.line_has_content) {
+            if self.uncounted > 0 {
+                self.uncounted -= 1;
+            } else {
+                self.records.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for RecordLimit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // The limit is checked between lines, so a counted record is never cut.
+        if !self.line_has_content && self.max.is_some_and(|m| self.records.load(Ordering::Relaxed) >= m) {
+            return Ok(0);
+        }
+        let avail = self.inner.fill_buf()?;
+        if avail.is_empty() {
+            self.count_line();
+            return Ok(0);
+        }
+        let line_end = avail.iter().position(|&b| b == b'\n').map_or(avail.len(), |i| i + 1);
+        let n = line_end.min(buf.len());
+        buf[..n].copy_from_slice(&avail[..n]);
+        self.line_has_content |= avail[..n].iter().any(|b| !b.is_ascii_whitespace());
+        let complete = avail[n - 1] == b'\n';
+        self.inner.consume(n);
+        if complete {
+            self.count_line();
+        }
+        Ok(n)
+    }
+}
+
+/// One number per line; blank lines are skipped.
+struct NumericLines<R> {
+    reader: BufReader<R>,
+    line: u64,
+    buf: String,
+}
+
+impl<R: Read> ScalarStream for NumericLines<R> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            self.buf.clear();
+            let loc = Location::default().with_line(self.line + 1);
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(DataError::io(loc, e))),
+            }
+            let raw = self.buf.trim();
+            if raw.is_empty() {
+                continue;
+            }
+            return Some(raw.parse::<f64>()
+                .map_err(|e| DataError::parse(loc, format!("cannot parse '{}' as f64: {}", raw, e))));
+        }
+    }
+}
+
+/// A `ScalarStream` over the records of one connection or pipe; see the [module docs](self).
+pub struct SocketScalarStream {
+    inner: Box<dyn ScalarStream + Send>,
+    records: Arc<AtomicU64>,
+    max_records: Option<u64>,
+    peer: Option<String>,
+}
+
+impl SocketScalarStream {
+    /// Reads records from any reader, e.g. an already accepted connection.
+    ///
+    /// For CSV with a header row this blocks until the header has arrived, and
+    /// fails if it does not contain the selected column.
+    pub fn from_reader(reader: impl Read + Send + 'static, opts: IngestOptions) -> Result<Self, DataError> {
+        let records = Arc::new(AtomicU64::new(0));
+        let header = matches!(&opts.format, WireFormat::Csv(c) if c.has_header);
+        let limited = RecordLimit {
+            inner: BufReader::with_capacity(BUF, reader),
+            max: opts.max_records,
+            uncounted: header as u64,
+            line_has_content: false,
+            records: records.clone(),
+        };
+        let inner: Box<dyn ScalarStream + Send> = match opts.format {
+            WireFormat::Numeric => Box::new(NumericLines { reader: BufReader::new(limited), line: 0, buf: String::new() }),
+            WireFormat::Csv(csv) => Box::new(CsvScalarStream::from_reader_with(limited, csv)?),
+            WireFormat::Ndjson { key_path } => Box::new(NdjsonScalarStream::from_reader(limited, key_path)?),
+        };
+        Ok(Self { inner, records, max_records: opts.max_records, peer: None })
+    }
+
+    /// Waits for the next TCP connection on `listener` and reads records from it.
+    pub fn accept_tcp(listener: &TcpListener, opts: IngestOptions) -> Result<Self, DataError> {
+        let (conn, addr) = listener.accept().map_err(|e| DataError::io(Location::default(), e))?;
+        Self::from_tcp(conn, addr, opts)
+    }
+
+    /// Reads records from an accepted TCP connection.
+    pub fn from_tcp(conn: TcpStream, addr: SocketAddr, opts: IngestOptions) -> Result<Self, DataError> {
+        let mut s = Self::from_reader(conn, opts)?;
+        s.peer = Some(addr.to_string());
+        Ok(s)
+    }
+
+    /// Waits for the next connection on a Unix domain socket and reads records from it.
+    #[cfg(unix)]
+    pub fn accept_unix(listener: &std::os::unix::net::UnixListener, opts: IngestOptions) -> Result<Self, DataError> {
+        let (conn, addr) = listener.accept().map_err(|e| DataError::io(Location::default(), e))?;
+        let mut s = Self::from_reader(conn, opts)?;
+        s.peer = addr.as_pathname().map(|p| p.display().to_string());
+        Ok(s)
+    }
+
+    /// Opens a named pipe (FIFO) and reads records until the last writer closes it.
+    ///
+    /// Opening blocks until a writer opens the pipe.
+    pub fn open_fifo(path: impl AsRef<Path>, opts: IngestOptions) -> Result<Self, DataError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| DataError::io(Location::default().with_path(path), e))?;
+        let mut s = Self::from_reader(file, opts).map_err(|e| e.with_path(path))?;
+        s.peer = Some(path.display().to_string());
+        Ok(s)
+    }
+
+    /// Address of the peer (TCP), its socket path if bound (Unix) or the pipe path.
+    pub fn peer(&self) -> Option<&str> { self.peer.as_deref() }
+
+    /// Records read so far (blank lines and the CSV header are not counted).
+    pub fn records(&self) -> u64 { self.records.load(Ordering::Relaxed) }
+
+    /// Whether the record limit has been reached; any further input was not read.
+    pub fn limit_reached(&self) -> bool { self.max_records.is_some_and(|m| self.records() >= m) }
+}
+
+impl ScalarStream for SocketScalarStream {
+    /// Returns the next value read from the connection.
+    ///
+    /// - `Some(Ok(f64))` → parsed value.
+    /// - `Some(Err(e))` → malformed record or read failure.
+    /// - `None` → the peer closed the connection or the record limit was reached.
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        self.inner.next_val()
+    }
+}
@@ -0,0 +1,322 @@
+// src/sqlite_stream.rs
+//! SQLite query sources (feature `sqlite`).
+//!
+//! A query runs read-only against a local database file and its rows are
+//! streamed one at a time, either as one numeric column ([`SqliteScalarStream`])
+//! or as typed records ([`SqliteRecordStream`]):
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//!
+//! let src = SqliteScalarStream::from_query(
+//!     "ops.db",
+//!     "SELECT latency_ms FROM requests WHERE day = '2026-10-17'",
+//!     "latency_ms",
+//!     NullPolicy::Skip,
+//! ).unwrap();
+//! let (sum, n) = sum_stream(src, BoundedF64::new(0.0, 5000.0)).unwrap();
+//! ```
+//!
+//! The database is opened with `SQLITE_OPEN_READ_ONLY` and statements that
+//! would modify it are rejected before they run. The query is stepped on a
+//! background thread that owns the connection; rows are handed over through a
+//! bounded buffer, so memory stays bounded however large the result is.
+//! Dropping the stream ends the query.
+//!
+//! SQLite values are dynamically typed. `INTEGER` and `REAL` values are numbers;
+//! `TEXT` and `BLOB` values in a numeric column are a [`DataError::TypeMismatch`]
+//! located at the (1-based) result row and column.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, ErrorCode, OpenFlags, Statement};
+
+use crate::error::{DataError, Location};
+use crate::nulls::NullPolicy;
+use crate::record::{timestamp_from_secs, Field, FieldType, Record, RecordSchema, RecordStream};
+use crate::stream::ScalarStream;
+
+/// Rows buffered between the query thread and the consumer.
+const ROW_BUFFER: usize = 1024;
+
+/// Selected values of the result rows, produced by a query thread.
+struct Rows {
+    rx: Receiver<Result<Vec<Value>, DataError>>,
+    path: PathBuf,
+    row: u64,
+}
+
+impl Rows {
+    /// Opens `path`, prepares `sql` and starts stepping it on a new thread.
+    ///
+    /// Fails if the database cannot be opened, the statement is invalid or not
+    /// read-only, or one of `columns` is not a result column.
+    fn spawn(path: &Path, sql: &str, columns: Vec<String>) -> Result<Self, DataError> {
+        let (ready_tx, ready_rx) = mpsc::sync_channel(1);
+        let (tx, rx) = mpsc::sync_channel(ROW_BUFFER);
+        let (path, sql) = (path.to_path_buf(), sql.to_string());
+        let loc = Location::default().with_path(&path);
+        {
+            let path = path.clone();
+            thread::spawn(move || run_query(&path, &sql, &columns, ready_tx, tx));
+        }
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self { rx, path, row: 0 }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(DataError::io(loc, io::Error::other("SQLite query thread ended unexpectedly"))),
+        }
+    }
+
+    fn loc(&self) -> Location { Location::default().with_path(&self.path).with_record(self.row) }
+
+    fn next(&mut self) -> Option<Result<Vec<Value>, DataError>> {
+        let item = self.rx.recv().ok()?;
+        self.row += 1;
+        Some(item)
+    }
+}
+
+/// Body of the query thread: reports setup success or failure on `ready`, then
+/// sends the selected values of each row until the result or the receiver ends.
+fn run_query(
+    path: &Path,
+    sql: &str,
+    columns: &[String],
+    ready: SyncSender<Result<(), DataError>>,
+    tx: SyncSender<Result<Vec<Value>, DataError>>,
+) {
+    let loc = Location::default().with_path(path);
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI;
+    let conn = match Connection::open_with_flags(path, flags) {
+        Ok(conn) => conn,
+        Err(e) => {
+            let _ = ready.send(Err(sqlite_error(e, loc)));
+            return;
+        }
+    };
+    let (mut stmt, indices) = match prepare(&conn, sql, columns, &loc) {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    if ready.send(Ok(())).is_err() {
+        return;
+    }
+
+    let mut rows = stmt.raw_query();
+    let mut row = 0;
+    loop {
+        row += 1;
+        let item = match rows.next() {
+            Ok(Some(r)) => indices.iter()
+                .map(|&i| r.get_ref(i).map(Value::from))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| sqlite_error(e, loc.clone().with_record(row))),
+            Ok(None) => return,
+            Err(e) => Err(sqlite_error(e, loc.clone().with_record(row))),
+        };
+        let failed = item.is_err();
+        if tx.send(item).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// Prepares `sql` and resolves `columns` to result column indices.
+fn prepare<'c>(conn: &'c Connection, sql: &str, columns: &[String], loc: &Location) -> Result<(Statement<'c>, Vec<usize>), DataError> {
+    let stmt = conn.prepare(sql).map_err(|e| sqlite_error(e, loc.clone()))?;
+    if !stmt.readonly() {
+        return Err(DataError::io(loc.clone(), io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "only read-only statements can be streamed",
+        )));
+    }
+    let names = stmt.column_names();
+    let indices = columns.iter()
+        .map(|c| names.iter().position(|n| n == c)
+            .ok_or_else(|| DataError::missing_key(loc.clone().with_column(c.clone()), c.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((stmt, indices))
+}
+
+fn sqlite_error(e: rusqlite::Error, loc: Location) -> DataError {
+    let io_failure = matches!(
+        e.sqlite_error_code(),
+        Some(ErrorCode::CannotOpen | ErrorCode::SystemIoFailure | ErrorCode::PermissionDenied
+            | ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked | ErrorCode::NotADatabase)
+    );
+    if io_failure {
+        DataError::io(loc, io::Error::other(e))
+    } else {
+        DataError::parse(loc, e.to_string())
+    }
+}
+
+fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "NULL",
+        Value::Integer(_) => "INTEGER",
+        Value::Real(_) => "REAL",
+        Value::Text(_) => "TEXT",
+        Value::Blob(_) => "BLOB",
+    }
+}
+
+/// Double-quotes an SQL identifier.
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/* ----------------------------- scalar ----------------------------- */
+
+/// A `ScalarStream` over one numeric column of an SQLite query result.
+pub struct SqliteScalarStream {
+    rows: Rows,
+    column: String,
+    nulls: NullPolicy,
+}
+
+impl SqliteScalarStream {
+    /// Runs `sql` and streams its result column `column`.
+    ///
+    /// # Arguments
+    /// * `path` – path to the database file (or a `file:` URI).
+    /// * `sql` – a single read-only statement, e.g. a `SELECT`.
+    /// * `column` – name of the numeric result column (use `AS` to name expressions).
+    /// * `nulls` – what to do with `NULL` values.
+    pub fn from_query(
+        path: impl AsRef<Path>,
+        sql: &str,
+        column: impl Into<String>,
+        nulls: NullPolicy,
+    ) -> Result<Self, DataError> {
+        let column = column.into();
+        let rows = Rows::spawn(path.as_ref(), sql, vec![column.clone()])?;
+        Ok(Self { rows, column, nulls })
+    }
+
+    /// Streams `column` of every row of `table`.
+    pub fn from_table(
+        path: impl AsRef<Path>,
+        table: &str,
+        column: impl Into<String>,
+        nulls: NullPolicy,
+    ) -> Result<Self, DataError> {
+        let column = column.into();
+        let sql = format!("SELECT {} FROM {}", quote_ident(&column), quote_ident(table));
+        Self::from_query(path, &sql, column, nulls)
+    }
+}
+
+impl ScalarStream for SqliteScalarStream {
+    /// Returns the value of the next row.
+    ///
+    /// - `Some(Ok(f64))` → `INTEGER` or `REAL` value (or the `Replace` substitute for a `NULL`).
+    /// - `Some(Err(e))` → a `TEXT`/`BLOB` value, a `NULL` under `NullPolicy::Error`,
+    ///   or a failure while stepping the query (which ends the stream).
+    /// - `None` → all rows read.
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            let value = match self.rows.next()? {
+                Ok(mut values) => values.pop().expect("one selected column"),
+                Err(e) => return Some(Err(e)),
+            };
+            let loc = self.rows.loc().with_column(self.column.clone());
+            return Some(match value {
+                Value::Integer(i) => Ok(i as f64),
+                Value::Real(v) => Ok(v),
+                Value::Null => match self.nulls {
+                    NullPolicy::Skip => continue,
+                    NullPolicy::Replace(v) => Ok(v),
+                    NullPolicy::Error => Err(DataError::missing_key(loc, format!("{} (null)", self.column))),
+                },
+                v => Err(DataError::TypeMismatch { loc, expected: "INTEGER or REAL", found: type_name(&v).into() }),
+            });
+        }
+    }
+}
+
+/* ----------------------------- records ----------------------------- */
+
+/// A [`RecordStream`] over an SQLite query result.
+///
+/// Schema fields are looked up by result column name and converted as follows:
+/// - `Num`: `INTEGER` and `REAL`.
+/// - `Str`: `TEXT`; numbers are formatted as text (e.g. integer category codes).
+/// - `Timestamp`: `TEXT` in the formats of [`FieldType::parse`], or numbers as
+///   seconds since the epoch; an infinite or out-of-range `REAL` is a
+///   [`DataError::Parse`] at its row and column.
+///
+/// `NULL` is [`Field::Null`]; the [`NullPolicy`] is chosen when projecting.
+pub struct SqliteRecordStream {
+    rows: Rows,
+    schema: Arc<RecordSchema>,
+}
+
+impl SqliteRecordStream {
+    /// Runs `sql` and streams the schema's columns of each result row.
+    ///
+    /// # Arguments
+    /// * `path` – path to the database file (or a `file:` URI).
+    /// * `sql` – a single read-only statement, e.g. a `SELECT`.
+    /// * `schema` – result columns to read and their types.
+    pub fn from_query(path: impl AsRef<Path>, sql: &str, schema: RecordSchema) -> Result<Self, DataError> {
+        let columns = schema.fields().iter().map(|f| f.name.clone()).collect();
+        let rows = Rows::spawn(path.as_ref(), sql, columns)?;
+        Ok(Self { rows, schema: Arc::new(schema) })
+    }
+
+    /// Streams the schema's columns of every row of `table`.
+    pub fn from_table(path: impl AsRef<Path>, table: &str, schema: RecordSchema) -> Result<Self, DataError> {
+        let columns: Vec<_> = schema.fields().iter().map(|f| quote_ident(&f.name)).collect();
+        let sql = format!("SELECT {} FROM {}", columns.join(", "), quote_ident(table));
+        Self::from_query(path, &sql, schema)
+    }
+}
+
+fn to_field(value: Value, ty: FieldType, loc: &Location) -> Result<Field, DataError> {
+    match (ty, value) {
+        (_, Value::Null) => Ok(Field::Null),
+        (FieldType::Num, Value::Integer(i)) => Ok(Field::Num(i as f64)),
+        (FieldType::Num, Value::Real(v)) => Ok(Field::Num(v)),
+        (FieldType::Str, Value::Text(s)) => Ok(Field::Str(s)),
+        (FieldType::Str, Value::Integer(i)) => Ok(Field::Str(i.to_string())),
+        (FieldType::Str, Value::Real(v)) => Ok(Field::Str(v.to_string())),
+        (FieldType::Timestamp, Value::Text(s)) => ty.parse(&s, loc),
+        (FieldType::Timestamp, Value::Integer(i)) => Ok(Field::Timestamp(i.saturating_mul(1000))),
+        (FieldType::Timestamp, Value::Real(v)) => timestamp_from_secs(v)
+            .filter(|_| (v * 1000.0).abs() < i64::MAX as f64)
+            .map(Field::Timestamp)
+            .ok_or_else(|| DataError::parse(loc.clone(), format!("{} is not a timestamp in seconds", v))),
+        (_, v) => Err(DataError::TypeMismatch { loc: loc.clone(), expected: ty.name(), found: type_name(&v).into() }),
+    }
+}
+
+impl RecordStream for SqliteRecordStream {
+    fn schema(&self) -> &Arc<RecordSchema> { &self.schema }
+
+    /// Returns the next result row.
+    ///
+    /// - `Some(Ok(Record))` → row with one typed field per schema entry.
+    /// - `Some(Err(e))` → a value of the wrong type, or a failure while stepping
+    ///   the query (which ends the stream).
+    /// - `None` → all rows read.
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        let values = match self.rows.next()? {
+            Ok(values) => values,
+            Err(e) => return Some(Err(e)),
+        };
+        let loc = self.rows.loc();
+        let fields = self.schema.fields().iter().zip(values)
+            .map(|(spec, v)| to_field(v, spec.ty, &loc.clone().with_column(spec.name.clone())))
+            .collect::<Result<Vec<_>, _>>();
+        Some(fields.map(|fields| Record::new(self.schema.clone(), fields, loc)))
+    }
+}
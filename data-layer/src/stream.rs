@@ -1,7 +1,19 @@
 // src/stream.rs
-use std::error::Error;
+use crate::error::DataError;
 
 /// Trait for a stream of scalar `f64` values, returning each value or an error until the stream ends.
 pub trait ScalarStream {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>>;
+    fn next_val(&mut self) -> Option<Result<f64, DataError>>;
+}
+
+impl<S: ScalarStream + ?Sized> ScalarStream for &mut S {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        (**self).next_val()
+    }
+}
+
+impl<S: ScalarStream + ?Sized> ScalarStream for Box<S> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        (**self).next_val()
+    }
 }
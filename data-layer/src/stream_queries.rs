@@ -1,49 +1,39 @@
 // src/stream_queries.rs
-use std::error::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::error::DataError;
 use crate::stream::ScalarStream;
 
 /// Closed interval [min, max] used for clamping values to a bounded domain.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BoundedF64 { pub min: f64, pub max: f64 }
 impl BoundedF64 {
     pub fn new(min: f64, max: f64) -> Self { assert!(min < max); Self { min, max } }
-    #[inline] fn clamp(&self, v: f64) -> f64 { v.min(self.max).max(self.min) }
+    #[inline] pub fn clamp(&self, v: f64) -> f64 { v.min(self.max).max(self.min) }
 }
 
 /// COUNT over a streaming source.
 /// Note: Clamping is irrelevant here (count does not depend on value magnitude).
-pub fn count_stream<S: ScalarStream>(mut s: S) -> Result<usize, Box<dyn Error + Send + Sync>> {
-    let mut n = 0usize;
-    while let Some(val) = s.next_val() {
-        val?;
-        n += 1;
-    }
-    Ok(n)
+pub fn count_stream<S: ScalarStream>(mut s: S) -> Result<usize, DataError> {
+    let mut state = CountState::new();
+    state.feed(&mut s, usize::MAX)?;
+    Ok(state.result())
 }
 
 /// SUM over a stream with per-item clamping to ensure bounded influence.
 /// Returns (sum, n) so callers can reuse the count.
-pub fn sum_stream<S: ScalarStream>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), Box<dyn Error + Send + Sync>> {
-    let (mut sum, mut n) = (0.0, 0usize);
-    while let Some(val) = s.next_val() {
-        let v = dom.clamp(val?);
-        sum += v;
-        n += 1;
-    }
-    Ok((sum, n))
+pub fn sum_stream<S: ScalarStream>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+    let mut state = SumState::new(dom);
+    state.feed(&mut s, usize::MAX)?;
+    Ok(state.result())
 }
 
 /// MEAN over a stream with clamping. Deterministic (no noise added here).
 /// Returns (mean, n). If n == 0, mean is defined as 0.0 to avoid NaN.
-pub fn mean_stream<S: ScalarStream>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), Box<dyn Error + Send + Sync>> {
-    let (mut sum, mut n) = (0.0, 0usize);
-    while let Some(val) = s.next_val() {
-        let v = dom.clamp(val?);
-        sum += v;
-        n += 1;
-    }
-    let mean = if n == 0 { 0.0 } else { sum / n as f64 };
-    Ok((mean, n))
+pub fn mean_stream<S: ScalarStream>(mut s: S, dom: BoundedF64) -> Result<(f64, usize), DataError> {
+    let mut state = SumState::new(dom);
+    state.feed(&mut s, usize::MAX)?;
+    Ok(state.mean())
 }
 
 /// HISTOGRAM with `bins` equal-width buckets over [min, max].
@@ -54,32 +44,215 @@ pub fn histogram_stream<S: ScalarStream>(
     mut s: S,
     dom: BoundedF64,
     bins: usize
-) -> Result<Vec<(f64, f64, usize)>, Box<dyn Error + Send + Sync>> {
-    let b = bins.max(1);
-    let width = (dom.max - dom.min) / b as f64;
-    let mut counts = vec![0usize; b];
-
-    while let Some(val) = s.next_val() {
-        let x = dom.clamp(val?);
-        let mut k = ((x - dom.min) / width).floor() as isize;
+) -> Result<Vec<(f64, f64, usize)>, DataError> {
+    let mut state = HistogramState::new(dom, bins);
+    state.feed(&mut s, usize::MAX)?;
+    Ok(state.result())
+}
+
+/* ----------------------------- resumable states ----------------------------- */
+
+// The query functions above run to completion in one call. The states below
+// hold the same exact (noise-free) aggregates, can be fed a bounded number of
+// values at a time, serialized in between (see `crate::checkpoint`) and merged
+// across chunks of one input (see `crate::parallel`).
+
+/// A mergeable, noise-free aggregation state.
+///
+/// Merging the states of disjoint parts of an input gives the state of the
+/// whole input, independent of how it was split.
+pub trait Aggregate {
+    /// Adds one value.
+    fn push(&mut self, v: f64);
+
+    /// Adds the values aggregated in `other`.
+    ///
+    /// # Panics
+    /// If `other` was created with different parameters (domain, bins).
+    fn merge(&mut self, other: Self) where Self: Sized;
+
+    /// Consumes up to `max` values of `s`; returns `true` once `s` is exhausted.
+    /// Values read before an error stay counted.
+    fn feed<S: ScalarStream>(&mut self, s: &mut S, max: usize) -> Result<bool, DataError> where Self: Sized {
+        for _ in 0..max {
+            match s.next_val() {
+                Some(val) => self.push(val?),
+                None => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Exact floating-point sum (Shewchuk's algorithm, as in Python's `math.fsum`).
+///
+/// The result is the correctly rounded sum of all added values, so it does not
+/// depend on their order or on how partial sums were merged. Inputs must be
+/// finite and the sum must stay below `f64::MAX`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExactSum {
+    partials: Vec<f64>,
+}
+
+impl ExactSum {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add(&mut self, mut x: f64) {
+        let mut i = 0;
+        for j in 0..self.partials.len() {
+            let mut y = self.partials[j];
+            if x.abs() < y.abs() {
+                std::mem::swap(&mut x, &mut y);
+            }
+            let hi = x + y;
+            let lo = y - (hi - x);
+            if lo != 0.0 {
+                self.partials[i] = lo;
+                i += 1;
+            }
+            x = hi;
+        }
+        self.partials.truncate(i);
+        self.partials.push(x);
+    }
+
+    pub fn merge(&mut self, other: &ExactSum) {
+        for &p in &other.partials {
+            self.add(p);
+        }
+    }
+
+    /// The correctly rounded sum.
+    pub fn value(&self) -> f64 {
+        let p = &self.partials;
+        let Some(mut n) = p.len().checked_sub(1) else { return 0.0 };
+        let mut hi = p[n];
+        let mut lo = 0.0;
+        while n > 0 {
+            let x = hi;
+            let y = p[n - 1];
+            n -= 1;
+            hi = x + y;
+            lo = y - (hi - x);
+            if lo != 0.0 {
+                break;
+            }
+        }
+        // Round half to even across the remaining partials.
+        if n > 0 && ((lo < 0.0 && p[n - 1] < 0.0) || (lo > 0.0 && p[n - 1] > 0.0)) {
+            let y = lo * 2.0;
+            let x = hi + y;
+            if y == x - hi {
+                hi = x;
+            }
+        }
+        hi
+    }
+}
+
+/// Resumable state of [`count_stream`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CountState {
+    pub n: usize,
+}
+
+impl CountState {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn result(&self) -> usize { self.n }
+}
+
+impl Aggregate for CountState {
+    fn push(&mut self, _v: f64) { self.n += 1; }
+
+    fn merge(&mut self, other: Self) { self.n += other.n; }
+}
+
+/// Resumable state of [`sum_stream`] and [`mean_stream`].
+///
+/// The sum is exact (see [`ExactSum`]), so merged and sequential results agree bit for bit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SumState {
+    pub dom: BoundedF64,
+    pub sum: ExactSum,
+    pub n: usize,
+}
+
+impl SumState {
+    pub fn new(dom: BoundedF64) -> Self { Self { dom, sum: ExactSum::new(), n: 0 } }
+
+    /// (sum, n), as returned by [`sum_stream`].
+    pub fn result(&self) -> (f64, usize) { (self.sum.value(), self.n) }
+
+    /// (mean, n), as returned by [`mean_stream`].
+    pub fn mean(&self) -> (f64, usize) {
+        let mean = if self.n == 0 { 0.0 } else { self.sum.value() / self.n as f64 };
+        (mean, self.n)
+    }
+}
+
+impl Aggregate for SumState {
+    fn push(&mut self, v: f64) {
+        self.sum.add(self.dom.clamp(v));
+        self.n += 1;
+    }
+
+    fn merge(&mut self, other: Self) {
+        assert_eq!(self.dom, other.dom, "cannot merge sums over different domains");
+        self.sum.merge(&other.sum);
+        self.n += other.n;
+    }
+}
+
+/// Resumable state of [`histogram_stream`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistogramState {
+    pub dom: BoundedF64,
+    pub counts: Vec<usize>,
+}
+
+impl HistogramState {
+    /// `bins` equal-width buckets over `dom` (at least one).
+    pub fn new(dom: BoundedF64, bins: usize) -> Self { Self { dom, counts: vec![0; bins.max(1)] } }
+
+    fn width(&self) -> f64 { (self.dom.max - self.dom.min) / self.counts.len() as f64 }
+
+    /// Buckets as returned by [`histogram_stream`].
+    pub fn result(&self) -> Vec<(f64, f64, usize)> {
+        let (b, dom, width) = (self.counts.len(), self.dom, self.width());
+        let mut out = Vec::with_capacity(b);
+        for (i, &count) in self.counts.iter().enumerate() {
+            let left = dom.min + i as f64 * width;
+            let right = if i + 1 == b { dom.max } else { dom.min + (i + 1) as f64 * width };
+            out.push((left, right, count));
+        }
+        out
+    }
+}
+
+impl Aggregate for HistogramState {
+    fn push(&mut self, v: f64) {
+        let b = self.counts.len();
+        let x = self.dom.clamp(v);
+        let mut k = ((x - self.dom.min) / self.width()).floor() as isize;
         if k < 0 { k = 0; }
         if k as usize >= b { k = (b as isize) - 1; }
-        counts[k as usize] += 1;
+        self.counts[k as usize] += 1;
     }
 
-    let mut out = Vec::with_capacity(b);
-    for i in 0..b {
-        let left = dom.min + i as f64 * width;
-        let right = if i + 1 == b { dom.max } else { dom.min + (i + 1) as f64 * width };
-        out.push((left, right, counts[i]));
+    fn merge(&mut self, other: Self) {
+        assert!(
+            self.dom == other.dom && self.counts.len() == other.counts.len(),
+            "cannot merge histograms with different domains or bins"
+        );
+        for (c, o) in self.counts.iter_mut().zip(other.counts) {
+            *c += o;
+        }
     }
-    Ok(out)
 }
 
-
 /// L1 sensitivities for the corresponding streaming queries.
 /// These are used to calibrate DP mechanisms later on.
-
 pub fn l1_sens_count() -> f64 { 1.0 }
 pub fn l1_sens_sum(dom: BoundedF64) -> f64 { dom.max - dom.min }
 pub fn l1_sens_mean(dom: BoundedF64, n: usize) -> f64 { if n == 0 { 0.0 } else { (dom.max - dom.min) / n as f64 } }
@@ -0,0 +1,294 @@
+// src/tail.rs
+//! Follow mode for growing line-based files (NDJSON, CSV).
+//!
+//! A [`TailReader`] is a `Read` over a file that keeps polling at end of file
+//! instead of returning EOF, so a stream built on it never ends on its own:
+//!
+//! ```no_run
+//! use data_layer::json_stream::NdjsonScalarStream;
+//! use data_layer::tail::TailOptions;
+//!
+//! let (mut s, handle) = NdjsonScalarStream::tail("metrics.ndjson", "v", TailOptions::default()).unwrap();
+//! std::thread::spawn(move || {
+//!     std::thread::sleep(std::time::Duration::from_secs(60));
+//!     handle.stop(); // `s` returns `None` once it has caught up
+//! });
+//! while let Some(v) = s.next_val() { /* ... */ }
+//! # use data_layer::stream::ScalarStream;
+//! ```
+//!
+//! - Only complete lines are handed out, one line per `read` call. A stream
+//!   that has just yielded a value has therefore consumed exactly the bytes
+//!   counted in [`TailHandle::checkpoint`], which makes the checkpoint a safe
+//!   resume point: restarting from it neither skips nor double counts records.
+//! - A trailing line without `\n` is held back until it is completed.
+//! - Rotation (the path now names a different file, detected by device and
+//!   inode on Unix) switches to the new file from its start, after draining
+//!   the old one. Truncation (the file became shorter than the current offset)
+//!   restarts from the beginning. Truncation followed by a rewrite past the old
+//!   offset before the next poll cannot be detected.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DataError, Location};
+
+/// Identity of a file across renames (device and inode on Unix).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileId {
+    pub dev: u64,
+    pub ino: u64,
+}
+
+impl FileId {
+    #[cfg(unix)]
+    fn of(meta: &std::fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+        Some(Self { dev: meta.dev(), ino: meta.ino() })
+    }
+
+    #[cfg(not(unix))]
+    fn of(_meta: &std::fs::Metadata) -> Option<Self> { None }
+}
+
+/// Resume point of a [`TailReader`]: the byte offset just after the last line
+/// handed out, and the file it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TailCheckpoint {
+    pub offset: u64,
+    pub file: Option<FileId>,
+}
+
+/// Where a [`TailReader`] starts reading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TailStart {
+    /// From the first byte.
+    Beginning,
+    /// After the current end of the file (only new lines).
+    End,
+    /// From a checkpoint of an earlier run. If the file was rotated or truncated
+    /// in the meantime, reading starts from the beginning of the current file.
+    Checkpoint(TailCheckpoint),
+}
+
+/// Options for [`TailReader::open`].
+#[derive(Clone, Debug)]
+pub struct TailOptions {
+    pub start: TailStart,
+    /// How long to sleep at end of file before polling again.
+    pub poll_interval: Duration,
+    /// The file starts with one header line (CSV). The reader hands it out
+    /// exactly once: also when resuming mid-file, and not again after rotation.
+    pub header: bool,
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self { start: TailStart::Beginning, poll_interval: Duration::from_millis(250), header: false }
+    }
+}
+
+impl TailOptions {
+    pub fn start(mut self, start: TailStart) -> Self { self.start = start; self }
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self { self.poll_interval = poll_interval; self }
+    pub fn header(mut self, header: bool) -> Self { self.header = header; self }
+}
+
+/// Shared state between a [`TailReader`] and its [`TailHandle`]s.
+#[derive(Debug, Default)]
+struct Shared {
+    stop: AtomicBool,
+    offset: AtomicU64,
+    rotations: AtomicU64,
+    file: Mutex<Option<FileId>>,
+}
+
+/// Controls a [`TailReader`] that has been moved into a stream.
+#[derive(Clone, Debug)]
+pub struct TailHandle {
+    shared: Arc<Shared>,
+}
+
+impl TailHandle {
+    /// Asks the reader to stop. It still hands out the complete lines that are
+    /// already in the file, then reports end of file.
+    pub fn stop(&self) { self.shared.stop.store(true, Ordering::SeqCst); }
+
+    pub fn is_stopped(&self) -> bool { self.shared.stop.load(Ordering::SeqCst) }
+
+    /// The current resume point; see the [module docs](self) for when it is exact.
+    pub fn checkpoint(&self) -> TailCheckpoint {
+        TailCheckpoint {
+            offset: self.shared.offset.load(Ordering::SeqCst),
+            file: *self.shared.file.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+
+    /// Number of rotations and truncations handled so far.
+    pub fn rotations(&self) -> u64 { self.shared.rotations.load(Ordering::SeqCst) }
+}
+
+/// A `Read` that follows a growing file; see the [module docs](self).
+pub struct TailReader {
+    path: PathBuf,
+    file: File,
+    id: Option<FileId>,
+    opts: TailOptions,
+    /// Bytes read from the file but not handed out yet (at most one partial line
+    /// plus one read chunk).
+    pending: Vec<u8>,
+    /// File offset of the first byte of `pending`.
+    offset: u64,
+    /// Header line to hand out before anything else (when resuming mid-file).
+    header: Option<Vec<u8>>,
+    /// Drop the first line of the file (header after rotation or truncation).
+    skip_line: bool,
+    shared: Arc<Shared>,
+}
+
+const CHUNK: usize = 64 * 1024;
+
+impl TailReader {
+    /// Opens `path` for following.
+    pub fn open(path: impl AsRef<Path>, opts: TailOptions) -> Result<Self, DataError> {
+        let path = path.as_ref().to_path_buf();
+        let loc = Location::default().with_path(&path);
+        let mut file = File::open(&path).map_err(|e| DataError::io(loc.clone(), e))?;
+        let meta = file.metadata().map_err(|e| DataError::io(loc.clone(), e))?;
+        let id = FileId::of(&meta);
+
+        let offset = match &opts.start {
+            TailStart::Beginning => 0,
+            TailStart::End => meta.len(),
+            TailStart::Checkpoint(cp) => {
+                let same_file = cp.file.is_none() || cp.file == id;
+                if same_file && cp.offset <= meta.len() { cp.offset } else { 0 }
+            }
+        };
+        let header = if opts.header && offset > 0 {
+            Some(read_first_line(&path).map_err(|e| DataError::io(loc.clone(), e))?)
+        } else {
+            None
+        };
+        file.seek(SeekFrom::Start(offset)).map_err(|e| DataError::io(loc, e))?;
+
+        let shared = Arc::new(Shared::default());
+        shared.offset.store(offset, Ordering::SeqCst);
+        *shared.file.lock().unwrap_or_else(|e| e.into_inner()) = id;
+        Ok(Self { path, file, id, opts, pending: Vec::new(), offset, header, skip_line: false, shared })
+    }
+
+    pub fn handle(&self) -> TailHandle { TailHandle { shared: self.shared.clone() } }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Reads more bytes from the current file into `pending`. Returns the number read.
+    fn fill(&mut self) -> io::Result<usize> {
+        let start = self.pending.len();
+        self.pending.resize(start + CHUNK, 0);
+        let n = loop {
+            match self.file.read(&mut self.pending[start..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                r => break r,
+            }
+        };
+        self.pending.truncate(start + *n.as_ref().unwrap_or(&0));
+        n
+    }
+
+    /// At end of file: switches to a rotated file or restarts a truncated one.
+    /// Returns true if reading should continue immediately.
+    fn check_rotation(&mut self) -> io::Result<bool> {
+        let meta = match std::fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            // Rotation in progress: keep the old file until the new one appears.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let id = FileId::of(&meta);
+        let end = self.offset + self.pending.len() as u64;
+        if id != self.id {
+            self.file = File::open(&self.path)?;
+            self.id = id;
+        } else if meta.len() < end {
+            self.file.seek(SeekFrom::Start(0))?;
+        } else {
+            return Ok(false);
+        }
+        // The partial line of the old file will never be completed.
+        self.pending.clear();
+        self.offset = 0;
+        self.skip_line = self.opts.header;
+        self.shared.offset.store(0, Ordering::SeqCst);
+        *self.shared.file.lock().unwrap_or_else(|e| e.into_inner()) = id;
+        self.shared.rotations.fetch_add(1, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    /// Hands out up to `n` bytes of `pending`.
+    fn take(&mut self, buf: &mut [u8], n: usize) -> usize {
+        let n = n.min(buf.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        self.offset += n as u64;
+        self.shared.offset.store(self.offset, Ordering::SeqCst);
+        n
+    }
+}
+
+impl Read for TailReader {
+    /// Returns the next complete line (or as much of it as fits into `buf`),
+    /// blocking until one is available. Returns `Ok(0)` only after [`TailHandle::stop`].
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if let Some(header) = self.header.as_mut() {
+            let n = header.len().min(buf.len());
+            buf[..n].copy_from_slice(&header[..n]);
+            header.drain(..n);
+            if header.is_empty() {
+                self.header = None;
+            }
+            return Ok(n);
+        }
+        loop {
+            if let Some(nl) = self.pending.iter().position(|&b| b == b'\n') {
+                if self.skip_line {
+                    self.skip_line = false;
+                    self.pending.drain(..=nl);
+                    self.offset += nl as u64 + 1;
+                    self.shared.offset.store(self.offset, Ordering::SeqCst);
+                    continue;
+                }
+                return Ok(self.take(buf, nl + 1));
+            }
+            if self.pending.len() >= buf.len() && !self.skip_line {
+                // A line longer than the caller's buffer: hand out a piece.
+                return Ok(self.take(buf, buf.len()));
+            }
+            if self.fill()? > 0 {
+                continue;
+            }
+            if self.check_rotation()? {
+                continue;
+            }
+            if self.shared.stop.load(Ordering::SeqCst) {
+                return Ok(0);
+            }
+            std::thread::sleep(self.opts.poll_interval);
+        }
+    }
+}
+
+fn read_first_line(path: &Path) -> io::Result<Vec<u8>> {
+    let mut line = Vec::new();
+    io::BufRead::read_until(&mut io::BufReader::new(File::open(path)?), b'\n', &mut line)?;
+    Ok(line)
+}
@@ -0,0 +1,20 @@
+// data-layer/src/tests/common.rs
+//! Fixtures shared by the test modules.
+
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+
+use crate::stream::ScalarStream;
+
+/// All values of `s`; panics on the first error.
+pub fn collect(mut s: impl ScalarStream) -> Vec<f64> {
+    std::iter::from_fn(|| s.next_val()).map(Result::unwrap).collect()
+}
+
+/// A temporary file holding `text`.
+pub fn file(text: &str) -> NamedTempFile {
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(text.as_bytes()).unwrap();
+    tmp
+}
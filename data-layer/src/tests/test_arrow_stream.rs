@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{DataType, Field, Schema};
+use tempfile::NamedTempFile;
+
+use crate::arrow_stream::{ArrowIpcScalarStream, ArrowScalarStream};
+use crate::error::DataError;
+use crate::nulls::NullPolicy;
+use crate::stream::ScalarStream;
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("v", DataType::Int64, true),
+        Field::new("w", DataType::Float64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+/// Two batches: v = [1, null, 3] and [null, 5].
+fn batches() -> Vec<RecordBatch> {
+    let b1 = RecordBatch::try_new(schema(), vec![
+        Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+        Arc::new(Float64Array::from(vec![0.5, 1.5, 2.5])),
+        Arc::new(StringArray::from(vec!["a", "b", "c"])),
+    ]).unwrap();
+    let b2 = RecordBatch::try_new(schema(), vec![
+        Arc::new(Int64Array::from(vec![None, Some(5)])) as ArrayRef,
+        Arc::new(Float64Array::from(vec![3.5, 4.5])),
+        Arc::new(StringArray::from(vec!["d", "e"])),
+    ]).unwrap();
+    vec![b1, b2]
+}
+
+fn collect(mut s: impl ScalarStream) -> Vec<Result<f64, DataError>> {
+    std::iter::from_fn(|| s.next_val()).collect()
+}
+
+fn ok_values(s: impl ScalarStream) -> Vec<f64> {
+    collect(s).into_iter().map(|r| r.unwrap()).collect()
+}
+
+#[test]
+fn ipc_file_column_with_null_policies() {
+    let tmp = NamedTempFile::new().unwrap();
+    let mut w = FileWriter::try_new(tmp.reopen().unwrap(), &schema()).unwrap();
+    for b in batches() { w.write(&b).unwrap(); }
+    w.finish().unwrap();
+
+    let skip = ArrowIpcScalarStream::from_path(tmp.path(), "v", NullPolicy::Skip).unwrap();
+    assert_eq!(ok_values(skip), vec![1.0, 3.0, 5.0]);
+
+    let replace = ArrowIpcScalarStream::from_path(tmp.path(), "v", NullPolicy::Replace(0.0)).unwrap();
+    assert_eq!(ok_values(replace), vec![1.0, 0.0, 3.0, 0.0, 5.0]);
+
+    let items = collect(ArrowIpcScalarStream::from_path(tmp.path(), "v", NullPolicy::Error).unwrap());
+    assert_eq!(items.len(), 5);
+    match &items[3] {
+        Err(DataError::MissingKey { loc, .. }) => {
+            assert_eq!(loc.record, Some(4));
+            assert_eq!(loc.path.as_deref(), Some(tmp.path()));
+        }
+        other => panic!("expected missing key for null, got {other:?}"),
+    }
+
+    let w = ArrowIpcScalarStream::from_path(tmp.path(), "w", NullPolicy::Error).unwrap();
+    assert_eq!(ok_values(w), vec![0.5, 1.5, 2.5, 3.5, 4.5]);
+}
+
+#[test]
+fn ipc_stream_rejects_missing_and_non_numeric_columns() {
+    let mut buf = Vec::new();
+    {
+        let mut w = StreamWriter::try_new(&mut buf, &schema()).unwrap();
+        for b in batches() { w.write(&b).unwrap(); }
+        w.finish().unwrap();
+    }
+
+    let items = collect(ArrowScalarStream::from_ipc_stream(&buf[..], "name", NullPolicy::Skip).unwrap());
+    assert_eq!(items.len(), 2); // one error per batch
+    assert!(matches!(&items[0], Err(DataError::TypeMismatch { .. })));
+    match &items[1] {
+        Err(DataError::TypeMismatch { loc, .. }) => assert_eq!(loc.record, Some(4)),
+        other => panic!("expected type mismatch, got {other:?}"),
+    }
+
+    let items = collect(ArrowScalarStream::from_ipc_stream(&buf[..], "nope", NullPolicy::Skip).unwrap());
+    assert!(matches!(&items[0], Err(DataError::MissingKey { .. })));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_reads_one_column_across_row_groups() {
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+
+    use crate::arrow_stream::ParquetScalarStream;
+    use crate::stream_queries::{BoundedF64, sum_stream};
+
+    let tmp = NamedTempFile::new().unwrap();
+    let props = WriterProperties::builder().set_max_row_group_size(2).build();
+    let mut w = ArrowWriter::try_new(tmp.reopen().unwrap(), schema(), Some(props)).unwrap();
+    for b in batches() { w.write(&b).unwrap(); }
+    w.close().unwrap();
+
+    let s = ParquetScalarStream::from_path(tmp.path(), "v", NullPolicy::Replace(-1.0), 1).unwrap();
+    assert_eq!(ok_values(s), vec![1.0, -1.0, 3.0, -1.0, 5.0]);
+
+    let s = ParquetScalarStream::from_path(tmp.path(), "w", NullPolicy::Error, 4).unwrap();
+    let (sum, n) = sum_stream(s, BoundedF64::new(0.0, 4.0)).unwrap();
+    assert_eq!(n, 5);
+    assert!((sum - 12.0).abs() < 1e-12);
+
+    let err = ParquetScalarStream::from_path(tmp.path(), "missing", NullPolicy::Skip, 8).err().unwrap();
+    assert!(matches!(err, DataError::MissingKey { .. }));
+}
@@ -0,0 +1,127 @@
+// data-layer/src/tests/test_async_stream.rs
+use crate::async_stream::{
+    count_stream_async, histogram_stream_async, mean_stream_async, next_val_async, sum_stream_async,
+    AsyncCsvStream, AsyncNdjsonStream, AsyncToSync, SyncToAsync,
+};
+use crate::csv_stream::{CsvColumn, CsvOptions, CsvScalarStream};
+use crate::error::DataError;
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+use crate::stream_queries::{histogram_stream, sum_stream, BoundedF64};
+use crate::tests::common::file;
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().build().unwrap()
+}
+
+#[test]
+fn ndjson_matches_sync_stream() {
+    let text = "{\"v\": 1.5}\n\n{\"v\": 12}\n{\"w\": 3}\n{\"v\": -2}\n";
+    let tmp = file(text);
+    let dom = BoundedF64::new(0.0, 10.0);
+    let sync = sum_stream(NdjsonScalarStream::from_path(tmp.path(), "v").unwrap(), dom);
+
+    runtime().block_on(async {
+        let s = AsyncNdjsonStream::from_path(tmp.path(), "v").await.unwrap();
+        assert_eq!(sum_stream_async(s, dom).await.unwrap_err().to_string(), sync.unwrap_err().to_string());
+
+        let mut s = AsyncNdjsonStream::from_path(tmp.path(), "v").await.unwrap();
+        assert_eq!(next_val_async(&mut s).await.unwrap().unwrap(), 1.5);
+        assert_eq!(next_val_async(&mut s).await.unwrap().unwrap(), 12.0);
+        match next_val_async(&mut s).await.unwrap().unwrap_err() {
+            DataError::MissingKey { loc, .. } => {
+                assert_eq!(loc.line, Some(4));
+                assert_eq!(loc.path.as_deref(), Some(tmp.path()));
+            }
+            other => panic!("expected missing key, got {other:?}"),
+        }
+        assert_eq!(next_val_async(&mut s).await.unwrap().unwrap(), -2.0);
+        assert!(next_val_async(&mut s).await.is_none());
+    });
+}
+
+#[test]
+fn ndjson_fan_out() {
+    let text = "{\"xs\": [1, 2, 3]}\n{\"xs\": [4]}\n";
+    runtime().block_on(async {
+        let mut s = AsyncNdjsonStream::new(text.as_bytes(), "xs[*]").unwrap().max_per_record(2);
+        let mut vals = Vec::new();
+        while let Some(v) = next_val_async(&mut s).await {
+            vals.push(v.unwrap());
+        }
+        assert_eq!(vals, vec![1.0, 2.0, 4.0]);
+        assert_eq!(s.fan_out().max_per_record, 2);
+        assert_eq!(s.fan_out().truncated, 1);
+    });
+}
+
+#[test]
+fn csv_matches_sync_stream() {
+    let mut text = String::from("note,amount\n");
+    for i in 0..200 {
+        text.push_str(&format!("\"row {i}, with a\nline break\",{}\n", i as f64 * 0.1));
+    }
+    text.push_str("empty,\n");
+    let tmp = file(&text);
+    let dom = BoundedF64::new(0.0, 15.0);
+    let sync = || CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+
+    runtime().block_on(async {
+        // A small reader buffer exercises records split across reads.
+        let r = tokio::io::BufReader::with_capacity(7, text.as_bytes());
+        let s = AsyncCsvStream::new(r, CsvOptions::by_name("amount")).unwrap();
+        assert_eq!(sum_stream_async(s, dom).await.unwrap(), sum_stream(sync(), dom).unwrap());
+
+        let s = AsyncCsvStream::from_path(tmp.path(), CsvOptions::by_name("amount")).await.unwrap();
+        assert_eq!(histogram_stream_async(s, dom, 4).await.unwrap(), histogram_stream(sync(), dom, 4).unwrap());
+    });
+}
+
+#[test]
+fn csv_errors_carry_locations() {
+    let text = "a;b\n1;2\n3\n4;x\n";
+    runtime().block_on(async {
+        let opts = CsvOptions::new(CsvColumn::Index(1)).delimiter(b';').has_header(true);
+        let mut s = AsyncCsvStream::new(text.as_bytes(), opts).unwrap();
+        assert_eq!(next_val_async(&mut s).await.unwrap().unwrap(), 2.0);
+        match next_val_async(&mut s).await.unwrap().unwrap_err() {
+            DataError::Schema { loc, .. } => assert_eq!((loc.line, loc.record), (Some(3), Some(2))),
+            other => panic!("expected schema error, got {other:?}"),
+        }
+        match next_val_async(&mut s).await.unwrap().unwrap_err() {
+            DataError::Parse { loc, .. } => {
+                assert_eq!((loc.line, loc.record, loc.column.as_deref()), (Some(4), Some(3), Some("1")));
+            }
+            other => panic!("expected parse error, got {other:?}"),
+        }
+        assert!(next_val_async(&mut s).await.is_none());
+
+        let mut s = AsyncCsvStream::new(text.as_bytes(), CsvOptions::by_name("c").delimiter(b';')).unwrap();
+        assert!(matches!(next_val_async(&mut s).await.unwrap(), Err(DataError::MissingKey { .. })));
+        assert!(next_val_async(&mut s).await.is_none());
+    });
+
+    let opts = CsvOptions::by_name("a").has_header(false);
+    assert!(matches!(AsyncCsvStream::new(text.as_bytes(), opts), Err(DataError::Schema { .. })));
+}
+
+#[test]
+fn bridges_in_both_directions() {
+    let tmp = file("{\"v\": 1}\n{\"v\": 2}\n{\"v\": 3}\n");
+    let rt = runtime();
+
+    // Sync → async, with a buffer smaller than the input.
+    let s = SyncToAsync::new(NdjsonScalarStream::from_path(tmp.path(), "v").unwrap(), 1);
+    let dom = BoundedF64::new(0.0, 10.0);
+    assert_eq!(rt.block_on(mean_stream_async(s, dom)).unwrap(), (2.0, 3));
+
+    // Async → sync, driven from this (non-runtime) thread.
+    let s = AsyncNdjsonStream::new(&b"{\"v\": 4}\n{\"v\": 5}\n"[..], "v").unwrap();
+    let mut s = AsyncToSync::new(s, rt.handle().clone());
+    let vals: Vec<f64> = std::iter::from_fn(|| s.next_val()).map(Result::unwrap).collect();
+    assert_eq!(vals, vec![4.0, 5.0]);
+
+    // Round trip keeps errors.
+    let s = SyncToAsync::new(CsvScalarStream::from_path(file("1\nx\n").path(), 0, b',').unwrap(), 4);
+    assert!(rt.block_on(count_stream_async(s)).is_err());
+}
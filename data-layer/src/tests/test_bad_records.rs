@@ -0,0 +1,93 @@
+#![allow(clippy::write_literal)]
+
+use approx::assert_relative_eq;
+use tempfile::NamedTempFile;
+use std::io::Write;
+
+use crate::bad_records::{BadRecordFilter, BadRecordPolicy};
+use crate::error::{DataError, DataErrorKind};
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+use crate::stream_queries::{BoundedF64, sum_stream};
+
+fn messy_ndjson() -> NamedTempFile {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "{}", r#"{"v": 1.5}"#).unwrap();
+    writeln!(tmp, "{}", r#"{"v": "oops"}"#).unwrap();  // parse error
+    writeln!(tmp, "{}", r#"{"w": 2}"#).unwrap();       // missing key
+    writeln!(tmp, "{}", r#"{"v": 2.5}"#).unwrap();
+    writeln!(tmp, "{}", r#"{"v": [1]}"#).unwrap();     // type mismatch
+    writeln!(tmp, "{}", r#"{"v": 3}"#).unwrap();
+    tmp
+}
+
+#[test]
+fn fail_policy_passes_errors_through() {
+    let tmp = messy_ndjson();
+    let src = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    let mut f = BadRecordFilter::new(src, BadRecordPolicy::Fail).unwrap();
+
+    let err = sum_stream(&mut f, BoundedF64::new(0.0, 10.0)).unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Parse);
+    assert_eq!(f.report().rejected, 0);
+}
+
+#[test]
+fn skip_policy_drops_and_counts_bad_records() {
+    let tmp = messy_ndjson();
+    let src = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    let mut f = BadRecordFilter::new(src, BadRecordPolicy::Skip).unwrap();
+
+    let (sum, n) = sum_stream(&mut f, BoundedF64::new(0.0, 10.0)).unwrap();
+    assert_eq!(n, 3);
+    assert_relative_eq!(sum, 7.0, epsilon = 1e-12);
+
+    let report = f.into_report();
+    assert_eq!(report.accepted, 3);
+    assert_eq!(report.rejected, 3);
+    assert_eq!(report.by_kind[&DataErrorKind::Parse], 1);
+    assert_eq!(report.by_kind[&DataErrorKind::MissingKey], 1);
+    assert_eq!(report.by_kind[&DataErrorKind::TypeMismatch], 1);
+    assert_eq!(report.first.unwrap().line, Some(2));
+}
+
+#[test]
+fn quarantine_policy_writes_rejected_records() {
+    let tmp = messy_ndjson();
+    let quarantine = NamedTempFile::new().unwrap();
+    let src = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    let policy = BadRecordPolicy::Quarantine(quarantine.path().to_path_buf());
+    let mut f = BadRecordFilter::new(src, policy).unwrap();
+
+    let out: Vec<f64> = std::iter::from_fn(|| f.next_val()).map(|r| r.unwrap()).collect();
+    assert_eq!(out, vec![1.5, 2.5, 3.0]);
+    assert_eq!(f.report().rejected, 3);
+
+    let lines = std::fs::read_to_string(quarantine.path()).unwrap();
+    let lines: Vec<&str> = lines.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("line 2") && lines[0].contains("oops"));
+    assert!(lines[1].contains("line 3") && lines[1].contains("not found"));
+}
+
+/// Yields one I/O error, then a value.
+struct IoFailure(usize);
+impl ScalarStream for IoFailure {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        self.0 += 1;
+        match self.0 {
+            1 => Some(Err(std::io::Error::other("disk gone").into())),
+            2 => Some(Ok(1.0)),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn io_errors_are_never_skipped() {
+    let mut f = BadRecordFilter::new(IoFailure(0), BadRecordPolicy::Skip).unwrap();
+    assert!(matches!(f.next_val(), Some(Err(DataError::Io { .. }))));
+    assert_relative_eq!(f.next_val().unwrap().unwrap(), 1.0);
+    assert!(f.next_val().is_none());
+    assert_eq!(f.report().rejected, 0);
+}
@@ -0,0 +1,164 @@
+// data-layer/src/tests/test_checkpoint.rs
+use crate::checkpoint::{Checkpoint, Resumable, SourcePosition};
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::error::DataError;
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+use crate::stream_queries::{histogram_stream, sum_stream, Aggregate, BoundedF64, CountState, HistogramState, SumState};
+use crate::tests::common::{collect, file};
+
+/// Runs a sum over `path` in chunks of `chunk` values, saving a checkpoint and
+/// reopening the file after every chunk.
+fn chunked_sum<S: ScalarStream + Resumable>(
+    ckpt: &std::path::Path,
+    dom: BoundedF64,
+    chunk: usize,
+    open: impl Fn(Option<&SourcePosition>) -> S,
+) -> (f64, usize) {
+    loop {
+        let (mut s, mut state) = match Checkpoint::<SumState>::load(ckpt).unwrap() {
+            Some(ck) => (open(Some(&ck.source)), ck.state),
+            None => (open(None), SumState::new(dom)),
+        };
+        if state.feed(&mut s, chunk).unwrap() {
+            return state.result();
+        }
+        Checkpoint::new(state, s.position()).save(ckpt).unwrap();
+    }
+}
+
+#[test]
+fn ndjson_sum_resumed_in_chunks_matches_single_pass() {
+    let tmp = file("{\"v\": 1}\n\n{\"v\": 2}\n{\"v\": 30}\n{\"v\": 4}\n{\"v\": 5}\n");
+    let dom = BoundedF64::new(0.0, 10.0);
+    let dir = tempfile::tempdir().unwrap();
+    let ckpt = dir.path().join("job.ckpt");
+
+    let resumed = chunked_sum(&ckpt, dom, 2, |pos| match pos {
+        Some(pos) => NdjsonScalarStream::resume(tmp.path(), "v", pos).unwrap(),
+        None => NdjsonScalarStream::from_path(tmp.path(), "v").unwrap(),
+    });
+    let single = sum_stream(NdjsonScalarStream::from_path(tmp.path(), "v").unwrap(), dom).unwrap();
+    assert_eq!(resumed, single);
+    assert_eq!(resumed, (22.0, 5));
+}
+
+#[test]
+fn fan_out_resumes_mid_record() {
+    let tmp = file("{\"xs\": [1, 2, 3]}\n{\"xs\": [4]}\n");
+    let mut s = NdjsonScalarStream::from_path(tmp.path(), "xs[*]").unwrap();
+    assert_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    let pos = s.position();
+    assert_eq!(pos, SourcePosition { offset: 0, line: 0, record: 0, skip: 1 });
+
+    let rest = collect(NdjsonScalarStream::resume(tmp.path(), "xs[*]", &pos).unwrap());
+    assert_eq!(rest, vec![2.0, 3.0, 4.0]);
+
+    // After the last value of a record the position moves past it.
+    s.next_val();
+    s.next_val();
+    assert_eq!(s.position(), SourcePosition { offset: 18, line: 1, record: 1, skip: 0 });
+}
+
+#[test]
+fn csv_header_resume_keeps_column_and_locations() {
+    let tmp = file("ts,v\n1,10\n2,20\n3,x\n4,40\n");
+    let mut s = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("v")).unwrap();
+    assert_eq!(s.next_val().unwrap().unwrap(), 10.0);
+    let pos = s.position();
+    assert_eq!(pos, SourcePosition { offset: 10, line: 2, record: 1, skip: 0 });
+
+    let mut s = CsvScalarStream::resume_with(tmp.path(), CsvOptions::by_name("v"), &pos).unwrap();
+    assert_eq!(s.next_val().unwrap().unwrap(), 20.0);
+    match s.next_val().unwrap().unwrap_err() {
+        DataError::Parse { loc, .. } => {
+            assert_eq!((loc.line, loc.record, loc.column.as_deref()), (Some(4), Some(3), Some("v")));
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert_eq!(s.next_val().unwrap().unwrap(), 40.0);
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn csv_split_mode_resume() {
+    let tmp = file("1;a\n2;b\n3;c\n");
+    let mut s = CsvScalarStream::from_path(tmp.path(), 0, b';').unwrap();
+    let mut state = CountState::new();
+    assert!(!state.feed(&mut s, 2).unwrap());
+    let pos = s.position();
+    assert_eq!((pos.offset, pos.line), (8, 2));
+
+    let s = CsvScalarStream::resume(tmp.path(), 0, b';', &pos).unwrap();
+    assert_eq!(collect(s), vec![3.0]);
+}
+
+#[test]
+fn csv_split_mode_counts_records_without_blank_lines() {
+    let tmp = file("1;a\n\n2;b\n\r\nx;c\n4;d\n");
+    let mut s = CsvScalarStream::from_path(tmp.path(), 0, b';').unwrap();
+    assert_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    assert_eq!(s.next_val().unwrap().unwrap(), 2.0);
+    let pos = s.position();
+    assert_eq!((pos.line, pos.record), (3, 2));
+
+    let mut s = CsvScalarStream::resume(tmp.path(), 0, b';', &pos).unwrap();
+    match s.next_val().unwrap().unwrap_err() {
+        DataError::Parse { loc, .. } => assert_eq!((loc.line, loc.record), (Some(5), Some(3))),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert_eq!(s.next_val().unwrap().unwrap(), 4.0);
+    assert_eq!(s.position().record, 4);
+}
+
+#[test]
+fn histogram_state_round_trips_through_json() {
+    let tmp = file("{\"v\": 0.5}\n{\"v\": 9}\n{\"v\": 4}\n{\"v\": 12}\n");
+    let dom = BoundedF64::new(0.0, 10.0);
+    let mut s = NdjsonScalarStream::from_path(tmp.path(), "v").unwrap();
+    let mut state = HistogramState::new(dom, 2);
+    state.feed(&mut s, 2).unwrap();
+
+    let ck = Checkpoint::new(state, s.position());
+    let ck: Checkpoint<HistogramState> = serde_json::from_str(&serde_json::to_string(&ck).unwrap()).unwrap();
+    let (mut state, pos) = (ck.state, ck.source);
+    assert!(state.feed(&mut NdjsonScalarStream::resume(tmp.path(), "v", &pos).unwrap(), usize::MAX).unwrap());
+
+    let single = histogram_stream(NdjsonScalarStream::from_path(tmp.path(), "v").unwrap(), dom, 2).unwrap();
+    assert_eq!(state.result(), single);
+}
+
+#[test]
+fn missing_checkpoint_and_bad_offsets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(Checkpoint::<SumState>::load(dir.path().join("none")).unwrap().is_none());
+
+    std::fs::write(dir.path().join("bad"), "{").unwrap();
+    assert!(matches!(Checkpoint::<SumState>::load(dir.path().join("bad")), Err(DataError::Parse { .. })));
+
+    let tmp = file("{\"v\": 1}\n");
+    let pos = SourcePosition { offset: 100, ..SourcePosition::default() };
+    match NdjsonScalarStream::resume(tmp.path(), "v", &pos) {
+        Err(DataError::Io { source, .. }) => assert_eq!(source.kind(), std::io::ErrorKind::UnexpectedEof),
+        Err(other) => panic!("expected I/O error, got {other:?}"),
+        Ok(_) => panic!("expected I/O error"),
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn resumes_compressed_input() {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(b"{\"v\": 1}\n{\"v\": 2}\n{\"v\": 3}\n").unwrap();
+    let mut tmp = tempfile::Builder::new().suffix(".ndjson.gz").tempfile().unwrap();
+    tmp.write_all(&enc.finish().unwrap()).unwrap();
+
+    let mut s = NdjsonScalarStream::from_path(tmp.path(), "v").unwrap();
+    s.next_val();
+    let rest = collect(NdjsonScalarStream::resume(tmp.path(), "v", &s.position()).unwrap());
+    assert_eq!(rest, vec![2.0, 3.0]);
+}
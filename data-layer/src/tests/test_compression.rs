@@ -0,0 +1,102 @@
+use std::io::Write;
+use std::path::Path;
+
+use tempfile::NamedTempFile;
+
+use crate::compression::Compression;
+use crate::tests::common::collect;
+use crate::json_stream::NdjsonScalarStream;
+
+const NDJSON: &str = "{\"v\": 1.5}\n{\"v\": 2.5}\n{\"v\": 3}\n";
+
+#[test]
+fn detects_magic_bytes_and_extensions() {
+    assert_eq!(Compression::from_magic(&[0x1f, 0x8b, 0x08, 0x00]), Compression::Gzip);
+    assert_eq!(Compression::from_magic(&[0x28, 0xb5, 0x2f, 0xfd]), Compression::Zstd);
+    assert_eq!(Compression::from_magic(b"BZh9"), Compression::Bzip2);
+    assert_eq!(Compression::from_magic(b"{\"v"), Compression::None);
+    assert_eq!(Compression::from_magic(b""), Compression::None);
+
+    assert_eq!(Compression::from_extension(Path::new("d/2026-10-01.ndjson.gz")), Compression::Gzip);
+    assert_eq!(Compression::from_extension(Path::new("x.csv.zst")), Compression::Zstd);
+    assert_eq!(Compression::from_extension(Path::new("x.csv.bz2")), Compression::Bzip2);
+    assert_eq!(Compression::from_extension(Path::new("x.csv")), Compression::None);
+}
+
+#[test]
+fn plain_files_pass_through() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    write!(tmp, "{}", NDJSON).unwrap();
+    let s = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5, 3.0]);
+}
+
+#[test]
+fn plain_content_wins_over_a_compression_extension() {
+    let mut tmp = NamedTempFile::with_suffix(".ndjson.gz").unwrap();
+    write!(tmp, "{}", NDJSON).unwrap();
+    let s = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5, 3.0]);
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn compressed_input_without_feature_is_unsupported() {
+    use crate::error::DataError;
+
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&[0x1f, 0x8b, 0x08, 0x00, 0x00]).unwrap();
+    let err = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").err().unwrap();
+    match err {
+        DataError::Io { loc, source } => {
+            assert_eq!(source.kind(), std::io::ErrorKind::Unsupported);
+            assert_eq!(loc.path.as_deref(), Some(tmp.path()));
+        }
+        other => panic!("expected I/O error, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_detected_by_magic_bytes() {
+    use flate2::{write::GzEncoder, Compression as Level};
+
+    use crate::csv_stream::{CsvOptions, CsvScalarStream};
+
+    // No .gz extension: detection must come from the content.
+    let mut tmp = NamedTempFile::new().unwrap();
+    let mut enc = GzEncoder::new(Vec::new(), Level::default());
+    enc.write_all(NDJSON.as_bytes()).unwrap();
+    tmp.write_all(&enc.finish().unwrap()).unwrap();
+    let s = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5, 3.0]);
+
+    let mut tmp = NamedTempFile::with_suffix(".csv.gz").unwrap();
+    let mut enc = GzEncoder::new(Vec::new(), Level::default());
+    enc.write_all(b"id,value\n1,\"4\"\n2,5\n").unwrap();
+    tmp.write_all(&enc.finish().unwrap()).unwrap();
+    let s = CsvScalarStream::from_path_with(tmp.path().to_str().unwrap(), CsvOptions::by_name("value")).unwrap();
+    assert_eq!(collect(s), vec![4.0, 5.0]);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_detected_by_magic_bytes() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&zstd::encode_all(NDJSON.as_bytes(), 3).unwrap()).unwrap();
+    let s = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5, 3.0]);
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn bzip2_detected_by_magic_bytes() {
+    use bzip2::{write::BzEncoder, Compression as Level};
+
+    let mut tmp = NamedTempFile::new().unwrap();
+    let mut enc = BzEncoder::new(Vec::new(), Level::default());
+    enc.write_all(NDJSON.as_bytes()).unwrap();
+    tmp.write_all(&enc.finish().unwrap()).unwrap();
+    let s = NdjsonScalarStream::from_path(tmp.path().to_str().unwrap(), "v").unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5, 3.0]);
+}
@@ -0,0 +1,158 @@
+// data-layer/src/tests/test_contribution.rs
+use std::sync::Arc;
+
+use approx::assert_relative_eq;
+
+use crate::contribution::{
+    l1_sens_count_unit, l1_sens_mean_unit, l1_sens_sum_unit_add_remove, l1_sens_sum_unit_replace, ContributionBounder, ContributionBounds, Selection,
+    UnitProjection, UnitStream,
+};
+use crate::error::{DataError, Location};
+use crate::json_stream::NdjsonRecordStream;
+use crate::nulls::NullPolicy;
+use crate::record::{Field, Record, RecordSchema, RecordStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{l1_sens_sum, sum_stream, BoundedF64};
+use crate::tests::common::collect;
+
+/// u1 emits five events, u2 two, u3 one.
+const EVENTS: &str = concat!(
+    "{\"user\": \"u1\", \"v\": 1}\n",
+    "{\"user\": \"u2\", \"v\": 2}\n",
+    "{\"user\": \"u1\", \"v\": 3}\n",
+    "{\"user\": \"u1\", \"v\": 5}\n",
+    "{\"user\": \"u3\", \"v\": 50}\n",
+    "{\"user\": \"u1\", \"v\": 7}\n",
+    "{\"user\": \"u2\", \"v\": null}\n",
+    "{\"user\": \"u1\", \"v\": 9}\n",
+);
+
+fn pairs(nulls: NullPolicy) -> UnitProjection<NdjsonRecordStream> {
+    let records = NdjsonRecordStream::from_reader(EVENTS.as_bytes(), RecordSchema::new().str("user").num("v")).unwrap();
+    UnitProjection::new(records, "user", "v", nulls).unwrap()
+}
+
+#[test]
+fn first_k_keeps_earliest_rows_per_unit() {
+    let dom = BoundedF64::new(0.0, 10.0);
+    let s = ContributionBounder::new(pairs(NullPolicy::Replace(0.0)), dom, ContributionBounds::rows(2).unwrap(), Selection::FirstK).unwrap();
+    // u1: 1, 3 (5, 7, 9 dropped); u2: 2, 0; u3: 50 clamped to 10.
+    assert_eq!(collect(s), vec![1.0, 2.0, 3.0, 10.0, 0.0]);
+}
+
+#[test]
+fn total_cap_shrinks_the_value_that_crosses_it() {
+    let dom = BoundedF64::new(0.0, 10.0);
+    let bounds = ContributionBounds::rows(10).unwrap().max_total(8.0).unwrap();
+    let s = ContributionBounder::new(pairs(NullPolicy::Skip), dom, bounds, Selection::FirstK).unwrap();
+    // u1: 1 + 3 + 4 (of 5) reaches 8, then 0, 0; u3: 10 capped to 8.
+    assert_eq!(collect(s), vec![1.0, 2.0, 3.0, 4.0, 8.0, 0.0, 0.0]);
+
+    let s = ContributionBounder::new(pairs(NullPolicy::Skip), dom, bounds, Selection::FirstK).unwrap();
+    let (sum, _) = sum_stream(s, dom).unwrap();
+    assert_relative_eq!(sum, 18.0);
+}
+
+#[test]
+fn reservoir_keeps_k_rows_per_unit_in_stream_order() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    let run = |seed| collect(ContributionBounder::new(
+        pairs(NullPolicy::Skip),
+        dom,
+        ContributionBounds::rows(3).unwrap(),
+        Selection::Reservoir { seed: Some(seed) },
+    ).unwrap());
+
+    let out = run(7);
+    assert_eq!(out, run(7), "same seed, same sample");
+    // 3 of u1's rows, u2's single non-null row and u3's row.
+    assert_eq!(out.len(), 5);
+    let u1: Vec<f64> = out.iter().copied().filter(|v| [1.0, 3.0, 5.0, 7.0, 9.0].contains(v)).collect();
+    assert_eq!(u1.len(), 3);
+    assert!(u1.windows(2).all(|w| w[0] < w[1]), "kept rows stay in stream order");
+    assert!(out.contains(&2.0) && out.contains(&50.0));
+
+    // Every one of u1's rows gets sampled for some seed.
+    let mut seen = std::collections::BTreeSet::new();
+    for seed in 0..50 {
+        seen.extend(run(seed).into_iter().map(|v| v as i64));
+    }
+    assert!([1, 3, 5, 7, 9].iter().all(|v| seen.contains(v)));
+}
+
+#[test]
+fn unit_projection_rejects_null_units_and_string_values() {
+    let data = "{\"user\": null, \"v\": 1}\n{\"user\": 42, \"v\": 2}\n";
+    let schema = || RecordSchema::new().str("user").num("v");
+    let records = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    let mut s = ContributionBounder::new(
+        UnitProjection::new(records, "user", "v", NullPolicy::Error).unwrap(),
+        BoundedF64::new(0.0, 10.0),
+        ContributionBounds::rows(1).unwrap(),
+        Selection::FirstK,
+    ).unwrap();
+    assert!(matches!(s.next_val(), Some(Err(DataError::MissingKey { loc, .. })) if loc.line == Some(1)));
+    assert_eq!(s.next_val().unwrap().unwrap(), 2.0);
+    assert!(s.next_val().is_none());
+
+    let records = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    assert!(matches!(UnitProjection::new(records, "v", "user", NullPolicy::Skip), Err(DataError::TypeMismatch { .. })));
+}
+
+/// A third-party source whose records do not follow its own schema.
+struct Mistyped(Arc<RecordSchema>, Vec<Record>);
+
+impl RecordStream for Mistyped {
+    fn schema(&self) -> &Arc<RecordSchema> { &self.0 }
+
+    fn next_record(&mut self) -> Option<Result<Record, DataError>> {
+        (!self.1.is_empty()).then(|| Ok(self.1.remove(0)))
+    }
+}
+
+#[test]
+fn unit_projection_reports_values_that_break_the_schema() {
+    let schema = Arc::new(RecordSchema::new().str("user").num("v"));
+    let rec = |v: Field, line| Record::new(schema.clone(), vec![Field::Str("u1".into()), v], Location::default().with_line(line));
+    let records = Mistyped(schema.clone(), vec![rec(Field::Str("oops".into()), 1), rec(Field::Num(4.0), 2)]);
+    let mut s = UnitProjection::new(records, "user", "v", NullPolicy::Error).unwrap();
+    match s.next_unit_val() {
+        Some(Err(DataError::TypeMismatch { loc, .. })) => {
+            assert_eq!((loc.line, loc.column.as_deref()), (Some(1), Some("v")));
+        }
+        other => panic!("expected a type mismatch, got {other:?}"),
+    }
+    assert_eq!(s.next_unit_val().unwrap().unwrap(), ("u1".to_string(), 4.0));
+}
+
+#[test]
+fn invalid_bounds_are_errors() {
+    assert!(matches!(ContributionBounds::rows(0), Err(DataError::InvalidParam(_))));
+    for cap in [0.0, -1.0, f64::NAN] {
+        assert!(matches!(ContributionBounds::rows(1).unwrap().max_total(cap), Err(DataError::InvalidParam(_))));
+    }
+    // A total cap shrinks values towards 0, which must lie in the domain.
+    let bounds = ContributionBounds::rows(2).unwrap().max_total(5.0).unwrap();
+    let res = ContributionBounder::new(pairs(NullPolicy::Skip), BoundedF64::new(1.0, 10.0), bounds, Selection::FirstK);
+    assert!(matches!(res, Err(DataError::InvalidParam(_))));
+    assert!(ContributionBounder::new(pairs(NullPolicy::Skip), BoundedF64::new(1.0, 10.0), ContributionBounds::rows(2).unwrap(), Selection::FirstK).is_ok());
+}
+
+#[test]
+fn unit_sensitivities_follow_the_caps() {
+    let dom = BoundedF64::new(0.0, 10.0);
+    assert_eq!(l1_sens_sum_unit_replace(dom, &ContributionBounds::rows(1).unwrap()), l1_sens_sum(dom));
+    assert_eq!(l1_sens_sum_unit_replace(dom, &ContributionBounds::rows(5).unwrap()), 50.0);
+    assert_eq!(l1_sens_sum_unit_replace(dom, &ContributionBounds::rows(5).unwrap().max_total(20.0).unwrap()), 20.0);
+    assert_eq!(l1_sens_sum_unit_replace(BoundedF64::new(-1.0, 2.0), &ContributionBounds::rows(3).unwrap().max_total(4.0).unwrap()), 7.0);
+    assert_eq!(l1_sens_count_unit(&ContributionBounds::rows(5).unwrap()), 5.0);
+
+    // Add/remove: the largest absolute unit total. Equal to replace-one for a
+    // domain starting at 0, smaller once it straddles 0.
+    assert_eq!(l1_sens_sum_unit_add_remove(dom, &ContributionBounds::rows(5).unwrap()), 50.0);
+    let straddling = BoundedF64::new(-5.0, 10.0);
+    assert_eq!(l1_sens_sum_unit_replace(straddling, &ContributionBounds::rows(2).unwrap()), 30.0);
+    assert_eq!(l1_sens_sum_unit_add_remove(straddling, &ContributionBounds::rows(2).unwrap()), 20.0);
+    assert_eq!(l1_sens_sum_unit_add_remove(BoundedF64::new(-1.0, 2.0), &ContributionBounds::rows(3).unwrap().max_total(4.0).unwrap()), 4.0);
+    assert_relative_eq!(l1_sens_mean_unit(dom, &ContributionBounds::rows(5).unwrap(), 100), 0.5);
+}
@@ -3,8 +3,9 @@ use approx::assert_relative_eq;
 use tempfile::NamedTempFile;
 use std::io::Write;
 
+use crate::error::DataError;
 use crate::stream::ScalarStream;
-use crate::csv_stream::CsvScalarStream;
+use crate::csv_stream::{CsvColumn, CsvOptions, CsvScalarStream};
 use crate::stream_queries::{BoundedF64, mean_stream, count_stream};
 
 #[test]
@@ -27,7 +28,12 @@ fn csv_stream_reads_column_and_handles_empty_cells() {
     // 1st call: header row "value" -> parse error
     match csv.next_val().unwrap() {
         Ok(_) => panic!("expected parse error from header row"),
-        Err(e) => assert!(e.to_string().contains("ParseFloatError") || e.to_string().contains("invalid float")),
+        Err(DataError::Parse { loc, .. }) => {
+            assert_eq!(loc.line, Some(1));
+            assert_eq!(loc.column.as_deref(), Some("1"));
+            assert_eq!(loc.path.as_deref(), Some(tmp.path()));
+        }
+        Err(e) => panic!("unexpected error kind: {e}"),
     }
 
     // 2nd: "1.5"
@@ -71,7 +77,7 @@ fn csv_with_pipeline_mean() {
     // Use mean_stream with clamping [-10,10].
     let dom = BoundedF64::new(-10.0, 10.0);
     let err = mean_stream(csv, dom).unwrap_err();
-    assert!(err.to_string().contains("invalid") || err.to_string().contains("ParseFloat"));
+    assert!(matches!(err, DataError::Parse { .. }));
     
     let mut tmp2 = NamedTempFile::new().unwrap();
     writeln!(tmp2, "1,-100").unwrap();
@@ -115,4 +121,67 @@ fn csv_with_pipeline_count() {
     let csv2 = CsvScalarStream::from_path(&path2, 0, b',').unwrap();
     let n = count_stream(csv2).unwrap();
     assert_eq!(n, 3);
-}
\ No newline at end of file
+}
+
+#[test]
+fn csv_rfc4180_handles_quotes_escapes_and_multiline_fields() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "id,comment,value").unwrap();
+    writeln!(tmp, "1,\"plain\",1.5").unwrap();
+    writeln!(tmp, "2,\"with, comma\",2.5").unwrap();
+    writeln!(tmp, "3,\"say \"\"hi\"\"\",\"3.5\"").unwrap();
+    writeln!(tmp, "4,\"two\nlines\",4.5").unwrap();
+    writeln!(tmp, "5,empty,").unwrap(); // empty -> skipped
+
+    let path = tmp.path().to_str().unwrap();
+    let opts = CsvOptions::by_name("value");
+    let mut csv = CsvScalarStream::from_path_with(path, opts).unwrap();
+    let out: Vec<f64> = std::iter::from_fn(|| csv.next_val()).map(|r| r.unwrap()).collect();
+    assert_eq!(out, vec![1.5, 2.5, 3.5, 4.5]);
+}
+
+#[test]
+fn csv_rfc4180_reports_positions_for_short_rows_and_bad_cells() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "id;value").unwrap();
+    writeln!(tmp, "1;\"a;b\"").unwrap();
+    writeln!(tmp, "2").unwrap();
+    writeln!(tmp, "3;7").unwrap();
+
+    let path = tmp.path().to_str().unwrap();
+    let opts = CsvOptions::new(CsvColumn::Index(1)).delimiter(b';').has_header(true);
+    let mut csv = CsvScalarStream::from_path_with(path, opts).unwrap();
+
+    match csv.next_val().unwrap() {
+        Err(DataError::Parse { loc, msg }) => {
+            assert_eq!((loc.line, loc.record), (Some(2), Some(1)));
+            assert!(msg.contains("'a;b'"), "{msg}");
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+
+    match csv.next_val().unwrap() {
+        Err(DataError::Schema { loc, .. }) => {
+            assert_eq!((loc.line, loc.record), (Some(3), Some(2)));
+            assert_eq!(loc.column.as_deref(), Some("1"));
+        }
+        other => panic!("expected schema error, got {other:?}"),
+    }
+
+    assert_relative_eq!(csv.next_val().unwrap().unwrap(), 7.0);
+    assert!(csv.next_val().is_none());
+}
+
+#[test]
+fn csv_rfc4180_column_name_requires_header() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "id,value").unwrap();
+    let path = tmp.path().to_str().unwrap();
+
+    let no_header = CsvOptions::by_name("value").has_header(false);
+    assert!(CsvScalarStream::from_path_with(path, no_header).is_err());
+
+    let unknown = CsvOptions::by_name("missing");
+    let err = CsvScalarStream::from_path_with(path, unknown).err().unwrap();
+    assert!(matches!(err, DataError::MissingKey { ref key, .. } if key == "missing"));
+}
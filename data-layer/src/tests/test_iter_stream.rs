@@ -0,0 +1,98 @@
+use std::io::Cursor;
+
+use approx::assert_relative_eq;
+
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::error::{DataError, DataErrorKind, Location};
+use crate::iter_stream::{IterStream, VecStream};
+use crate::json_stream::{JsonArrayScalarStream, NdjsonScalarStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{BoundedF64, count_stream, mean_stream, sum_stream};
+use crate::tests::common::collect;
+use crate::xml_stream::XmlScalarStream;
+
+#[test]
+fn iter_stream_over_plain_values() {
+    let dom = BoundedF64::new(-10.0, 10.0);
+    let (mean, n) = mean_stream(VecStream::from(vec![-100.0, -2.0, 0.0, 3.5, 200.0]), dom).unwrap();
+    assert_eq!(n, 5);
+    assert_relative_eq!(mean, 0.3, epsilon = 1e-12);
+
+    let n = count_stream(IterStream::new([1.0, 2.0, 3.0])).unwrap();
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn iter_stream_over_results_keeps_error_kinds() {
+    let items: Vec<Result<f64, DataError>> = vec![
+        Ok(1.0),
+        Err(DataError::parse(Location::default().with_record(2), "bad")),
+        Ok(3.0),
+    ];
+    let mut s = IterStream::new(items);
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    let err = s.next_val().unwrap().unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Parse);
+    assert_eq!(err.location().unwrap().record, Some(2));
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 3.0);
+    assert!(s.next_val().is_none());
+
+    let io: Vec<Result<f64, std::io::Error>> = vec![Err(std::io::Error::other("closed"))];
+    let err = IterStream::new(io).next_val().unwrap().unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Io);
+}
+
+#[test]
+fn iter_stream_over_foreign_errors() {
+    let items: Vec<Result<f64, String>> = vec![Ok(1.0), Err("sensor offline".to_string()), Ok(2.0)];
+    let mut s = IterStream::from_fallible(items);
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    let err = s.next_val().unwrap().unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Other);
+    assert_eq!(err.to_string(), "sensor offline");
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 2.0);
+    assert!(s.next_val().is_none());
+
+    // Any std error type works too, e.g. from parsing.
+    let parsed = ["1.5", "x"].into_iter().map(|t| t.parse::<f64>());
+    let mut s = IterStream::from_fallible(parsed);
+    let kinds: Vec<_> = std::iter::from_fn(|| s.next_val()).map(|r| r.map_err(|e| e.kind())).collect();
+    assert_eq!(kinds, vec![Ok(1.5), Err(DataErrorKind::Other)]);
+
+    // Boxed errors convert directly.
+    let boxed: Vec<Result<f64, Box<dyn std::error::Error + Send + Sync>>> = vec![Err("closed".into())];
+    let err = IterStream::new(boxed).next_val().unwrap().unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Other);
+}
+
+#[test]
+fn csv_from_in_memory_reader() {
+    let data = "id,value\n1,\"1.5\"\n2,2.5\n";
+    let s = CsvScalarStream::from_reader_with(Cursor::new(data), CsvOptions::by_name("value")).unwrap();
+    assert_eq!(collect(s), vec![1.5, 2.5]);
+
+    let s = CsvScalarStream::from_reader(Cursor::new("1;4\n2;5\n"), 1, b';');
+    let (sum, _) = sum_stream(s, BoundedF64::new(0.0, 10.0)).unwrap();
+    assert_relative_eq!(sum, 9.0);
+}
+
+#[test]
+fn json_and_xml_from_in_memory_readers() {
+    let nd = NdjsonScalarStream::from_reader(Cursor::new("{\"v\":1}\n\n{\"v\":\"2\"}\n"), "v").unwrap();
+    assert_eq!(collect(nd), vec![1.0, 2.0]);
+
+    let arr = JsonArrayScalarStream::from_reader(Cursor::new(r#"{"a":{"b":3}} {"a":{"b":4}}"#), "a.b").unwrap();
+    assert_eq!(collect(arr), vec![3.0, 4.0]);
+
+    let xml = XmlScalarStream::from_reader(Cursor::new("<r><x v='5'/><x v='6'/></r>"), "x", "@v").unwrap();
+    assert_eq!(collect(xml), vec![5.0, 6.0]);
+}
+
+#[test]
+fn reader_errors_have_no_path() {
+    let mut nd = NdjsonScalarStream::from_reader(Cursor::new("{\"w\":1}\n"), "v").unwrap();
+    let err = nd.next_val().unwrap().unwrap_err();
+    let loc = err.location().unwrap();
+    assert_eq!(loc.path, None);
+    assert_eq!(loc.line, Some(1));
+}
@@ -0,0 +1,112 @@
+// data-layer/src/tests/test_json_path.rs
+use std::io::Cursor;
+
+use serde_json::{json, Value};
+
+use crate::contribution::{l1_sens_sum_unit_replace, ContributionBounds};
+use crate::error::DataError;
+use crate::json_path::JsonPath;
+use crate::json_stream::{JsonArrayScalarStream, NdjsonScalarStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{sum_stream, BoundedF64};
+
+fn doc() -> Value {
+    json!({
+        "id": 7,
+        "a.b": 1.5,
+        "metrics": {"value": 2},
+        "items": [
+            {"price": 10, "kind": "food"},
+            {"price": 20, "kind": "tool"},
+            {"price": 30, "kind": "food"}
+        ],
+        "a/b": {"~x": 4}
+    })
+}
+
+fn select(path: &str) -> Vec<Value> {
+    JsonPath::parse(path).unwrap().select(&doc()).into_iter().cloned().collect()
+}
+
+#[test]
+fn singular_paths_in_all_notations() {
+    let d = doc();
+    let get = |p: &str| JsonPath::parse(p).unwrap().get(&d).cloned();
+    assert_eq!(get("metrics.value"), Ok(json!(2)));
+    assert_eq!(get("$.metrics.value"), Ok(json!(2)));
+    assert_eq!(get("items[1].price"), Ok(json!(20)));
+    assert_eq!(get("items.1.price"), Ok(json!(20)));
+    assert_eq!(get("$.items[-1].kind"), Ok(json!("food")));
+    assert_eq!(get("$['a.b']"), Ok(json!(1.5)));
+    assert_eq!(get("[\"a.b\"]"), Ok(json!(1.5)));
+    assert_eq!(get("/items/0/price"), Ok(json!(10)));
+    assert_eq!(get("/a~1b/~0x"), Ok(json!(4)));
+    assert_eq!(get(""), Ok(d.clone()));
+    assert_eq!(get("items[5].price"), Err("[5]".to_string()));
+    assert_eq!(get("metrics.nope"), Err("nope".to_string()));
+    assert!(JsonPath::parse("items[0].price").unwrap().is_singular());
+    assert!(!JsonPath::parse("items[*].price").unwrap().is_singular());
+}
+
+#[test]
+fn wildcards_and_filters_fan_out() {
+    assert_eq!(select("items[*].price"), vec![json!(10), json!(20), json!(30)]);
+    assert_eq!(select("$.items.*.kind"), vec![json!("food"), json!("tool"), json!("food")]);
+    assert_eq!(select("$.items[?(@.kind == 'food')].price"), vec![json!(10), json!(30)]);
+    assert_eq!(select("$.items[?(@.price >= 20)].price"), vec![json!(20), json!(30)]);
+    assert_eq!(select("$.items[?(@.kind != \"food\")].price"), vec![json!(20)]);
+    assert_eq!(select("$.items[?(@.missing)].price"), Vec::<Value>::new());
+    assert_eq!(select("$.nothing[*]"), Vec::<Value>::new());
+}
+
+#[test]
+fn invalid_paths_are_parse_errors() {
+    for bad in ["$..price", "items[", "items[x]", "$.items[?(@.price ~ 1)]", "$['open", "a..b"] {
+        match JsonPath::parse(bad) {
+            Err(DataError::Parse { loc, .. }) => assert_eq!(loc.column.as_deref(), Some(bad)),
+            other => panic!("expected parse error for {bad:?}, got {other:?}"),
+        }
+    }
+    assert!(NdjsonScalarStream::from_reader(Cursor::new(""), "$..v").is_err());
+}
+
+#[test]
+fn ndjson_fan_out_reports_contributions_and_caps() {
+    let data = concat!(
+        "{\"readings\": [{\"v\": 1}, {\"v\": 2}, {\"v\": 3}]}\n",
+        "{\"readings\": []}\n",
+        "{\"readings\": [{\"v\": \"4\"}, {\"v\": true}]}\n",
+    );
+    let mut s = NdjsonScalarStream::from_reader(Cursor::new(data), "readings[*].v").unwrap();
+    let items: Vec<_> = std::iter::from_fn(|| s.next_val()).collect();
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[3].as_ref().unwrap(), &4.0);
+    match &items[4] {
+        Err(DataError::TypeMismatch { loc, found, .. }) => {
+            assert_eq!(found, "bool");
+            assert_eq!(loc.line, Some(3));
+            assert_eq!(loc.column.as_deref(), Some("readings[*].v"));
+        }
+        other => panic!("expected type mismatch, got {other:?}"),
+    }
+    let stats = s.fan_out();
+    assert_eq!((stats.records, stats.values, stats.max_per_record, stats.truncated), (3, 5, 3, 0));
+    assert_eq!(stats.by_count.get(&0), Some(&1));
+
+    // With a cap of 2 values per record, a record's influence is bounded like a unit's rows.
+    let capped = NdjsonScalarStream::from_reader(Cursor::new(data), "readings[*].v").unwrap().max_per_record(2);
+    let mut capped = crate::bad_records::BadRecordFilter::new(capped, crate::bad_records::BadRecordPolicy::Skip).unwrap();
+    let dom = BoundedF64::new(0.0, 10.0);
+    let (sum, n) = sum_stream(&mut capped, dom).unwrap();
+    assert_eq!((sum, n), (7.0, 3));
+    assert_eq!(l1_sens_sum_unit_replace(dom, &ContributionBounds::rows(2).unwrap()), 20.0);
+}
+
+#[test]
+fn json_array_stream_with_filter_path() {
+    let data = r#"{"items": [{"k": "a", "v": 1}, {"k": "b", "v": 5}]} {"items": [{"k": "a", "v": 2}]}"#;
+    let mut s = JsonArrayScalarStream::from_reader(Cursor::new(data), "$.items[?(@.k == 'a')].v").unwrap();
+    let vals: Vec<f64> = std::iter::from_fn(|| s.next_val()).map(Result::unwrap).collect();
+    assert_eq!(vals, vec![1.0, 2.0]);
+    assert_eq!(s.fan_out().by_count.get(&1), Some(&2));
+}
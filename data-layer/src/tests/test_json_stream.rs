@@ -1,8 +1,11 @@
+#![allow(clippy::write_literal)]
+
 use approx::assert_relative_eq;
 use tempfile::NamedTempFile;
 use std::io::Write;
 
-use crate::json_stream::NdjsonScalarStream;
+use crate::error::DataError;
+use crate::json_stream::{JsonArrayScalarStream, NdjsonScalarStream};
 use crate::stream::ScalarStream;
 use crate::stream_queries::{BoundedF64, mean_stream};
 
@@ -30,6 +33,14 @@ fn ndjson_missing_key_path_is_error() {
     let mut s = NdjsonScalarStream::from_path(path, "metrics.value").unwrap();
     let err = s.next_val().unwrap().unwrap_err();
     assert!(err.to_string().contains("not found"));
+    match err {
+        DataError::MissingKey { loc, key } => {
+            assert_eq!(key, "metrics");
+            assert_eq!(loc.line, Some(1));
+            assert_eq!(loc.column.as_deref(), Some("metrics.value"));
+        }
+        other => panic!("expected missing key, got {other:?}"),
+    }
 }
 
 #[test]
@@ -49,4 +60,120 @@ fn ndjson_mean_integration_with_clamping() {
 
     assert_eq!(n, 5);
     assert_relative_eq!(mean, 0.3, epsilon = 1e-12);
-}
\ No newline at end of file
+}
+
+#[test]
+fn ndjson_error_kinds_carry_line_numbers() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "{}", r#"{"v": 1}"#).unwrap();
+    writeln!(tmp, "{}", r#"{"v": {"nested": true}}"#).unwrap();
+    writeln!(tmp, "{}", r#"{"v": "#).unwrap();
+    let path = tmp.path().to_str().unwrap();
+
+    let mut s = NdjsonScalarStream::from_path(path, "v").unwrap();
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    match s.next_val().unwrap().unwrap_err() {
+        DataError::TypeMismatch { loc, found, .. } => {
+            assert_eq!(loc.line, Some(2));
+            assert_eq!(found, "object");
+        }
+        other => panic!("expected type mismatch, got {other:?}"),
+    }
+    match s.next_val().unwrap().unwrap_err() {
+        DataError::Parse { loc, .. } => assert_eq!(loc.line, Some(3)),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(s.next_val().is_none());
+}
+
+fn collect(mut s: impl ScalarStream) -> Vec<Result<f64, DataError>> {
+    std::iter::from_fn(|| s.next_val()).collect()
+}
+
+#[test]
+fn json_array_yields_one_value_per_element() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    write!(tmp, "{}", "[\n  {\"v\": 1.0, \"s\": \"a],}\\\"[\"},\n  {\"v\": -2.0, \"nested\": [1, {\"x\": [2]}]},\n  {\"v\": \"3.5\"}\n]\n").unwrap();
+    let s = JsonArrayScalarStream::from_path(tmp.path(), "v").unwrap();
+    let vals: Vec<f64> = collect(s).into_iter().map(Result::unwrap).collect();
+    assert_eq!(vals, vec![1.0, -2.0, 3.5]);
+
+    // Top-level scalars and an empty array.
+    let vals = collect(JsonArrayScalarStream::from_reader(&b"[1, \"2\", 3e1]"[..], "").unwrap());
+    assert_eq!(vals.into_iter().map(Result::unwrap).collect::<Vec<_>>(), vec![1.0, 2.0, 30.0]);
+    assert!(collect(JsonArrayScalarStream::from_reader(&b" [ ] "[..], "v").unwrap()).is_empty());
+}
+
+#[test]
+fn json_array_bad_element_does_not_stop_the_stream() {
+    let data = "[{\"v\": 1},\n {\"v\": },\n {\"w\": 3},\n {\"v\": 4}]";
+    let items = collect(JsonArrayScalarStream::from_reader(data.as_bytes(), "v").unwrap());
+    assert_eq!(items.len(), 4);
+    match &items[1] {
+        Err(DataError::Parse { loc, .. }) => assert_eq!((loc.record, loc.line), (Some(2), Some(2))),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(matches!(&items[2], Err(DataError::MissingKey { loc, .. }) if loc.record == Some(3) && loc.line == Some(3)));
+    assert_eq!(items[3].as_ref().unwrap(), &4.0);
+}
+
+#[test]
+fn json_array_structural_errors_end_the_stream() {
+    // Truncated input: the complete elements come first, then one error.
+    let items = collect(JsonArrayScalarStream::from_reader(&b"[{\"v\": 1}, {\"v\": 2"[..], "v").unwrap());
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].as_ref().unwrap(), &1.0);
+    assert!(matches!(&items[1], Err(DataError::Parse { msg, .. }) if msg.contains("unexpected end")));
+
+    // Trailing garbage after the array is reported after the last element.
+    let items = collect(JsonArrayScalarStream::from_reader(&b"[{\"v\": 1}] x"[..], "v").unwrap());
+    assert_eq!(items.len(), 2);
+    assert!(items[1].is_err());
+
+    let items = collect(JsonArrayScalarStream::from_reader(&b"[1,,2]"[..], "").unwrap());
+    assert_eq!(items.len(), 2);
+    assert!(items[1].is_err());
+}
+
+/// An endless `[{"v":0},{"v":1},...` array that counts the bytes handed out.
+struct EndlessArray {
+    next: u64,
+    pending: Vec<u8>,
+    produced: std::rc::Rc<std::cell::Cell<u64>>,
+}
+
+impl std::io::Read for EndlessArray {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pending.len() < buf.len() {
+            let sep = if self.next == 0 { "[" } else { "," };
+            self.pending.extend_from_slice(format!("{}{{\"v\":{},\"pad\":\"{}\"}}", sep, self.next % 100, "x".repeat(64)).as_bytes());
+            self.next += 1;
+        }
+        let n = buf.len();
+        buf.copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        self.produced.set(self.produced.get() + n as u64);
+        Ok(n)
+    }
+}
+
+#[test]
+fn json_array_streams_with_bounded_memory() {
+    let produced = std::rc::Rc::new(std::cell::Cell::new(0));
+    let src = EndlessArray { next: 0, pending: Vec::new(), produced: produced.clone() };
+    let mut s = JsonArrayScalarStream::from_reader(src, "v").unwrap();
+
+    // The array never ends, so a reader that materializes it would never return.
+    let n = 200_000u64;
+    let mut sum = 0.0;
+    for i in 0..n {
+        let v = s.next_val().unwrap().unwrap();
+        assert_eq!(v, (i % 100) as f64);
+        sum += v;
+    }
+    assert_eq!(sum, (n / 100 * 4950) as f64);
+    // Bytes of the elements consumed so far (plus the closing of the last one).
+    let consumed: u64 = (0..=n).map(|i| format!(",{{\"v\":{},\"pad\":\"{}\"}}", i % 100, "x".repeat(64)).len() as u64).sum();
+    assert!(produced.get() >= consumed - 100);
+    assert!(produced.get() <= consumed + 64 * 1024, "read {} bytes for {} elements", produced.get(), n);
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::Path;
+
+use tempfile::tempdir;
+
+use crate::csv_stream::CsvScalarStream;
+use crate::error::DataError;
+use crate::json_stream::NdjsonScalarStream;
+use crate::multi_file::MultiFileStream;
+use crate::stream::ScalarStream;
+use crate::stream_queries::count_stream;
+
+#[test]
+fn glob_concatenates_partitions_in_sorted_order() {
+    let dir = tempdir().unwrap();
+    // Written out of order on purpose.
+    fs::write(dir.path().join("2026-10-03.ndjson"), "{\"v\": 3}\n").unwrap();
+    fs::write(dir.path().join("2026-10-01.ndjson"), "{\"v\": 1}\n{\"v\": 1.5}\n").unwrap();
+    fs::write(dir.path().join("2026-10-02.ndjson"), "{\"v\": 2}\n").unwrap();
+    fs::write(dir.path().join("2026-11-01.ndjson"), "{\"v\": 99}\n").unwrap();
+
+    let pattern = format!("{}/2026-10-*.ndjson", dir.path().display());
+    let mut s = MultiFileStream::from_glob(&pattern, |p| NdjsonScalarStream::from_path(p, "v")).unwrap();
+    assert_eq!(s.paths().len(), 3);
+    assert!(s.paths()[0].ends_with("2026-10-01.ndjson"));
+
+    let mut out = Vec::new();
+    while let Some(v) = s.next_val() {
+        out.push(v.unwrap());
+    }
+    assert_eq!(out, vec![1.0, 1.5, 2.0, 3.0]);
+    assert!(s.current_path().is_none());
+}
+
+#[test]
+fn errors_are_tagged_with_their_file() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("a.csv");
+    let bad = dir.path().join("b.csv");
+    fs::write(&good, "1\n2\n").unwrap();
+    fs::write(&bad, "3\nx\n").unwrap();
+
+    // from_reader sources know nothing about paths; the multi-file stream adds them.
+    let open = |p: &Path| Ok(CsvScalarStream::from_reader(fs::File::open(p)?, 0, b','));
+    let mut s = MultiFileStream::from_paths([&good, &dir.path().join("missing.csv"), &bad], open);
+
+    assert_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    assert_eq!(s.next_val().unwrap().unwrap(), 2.0);
+    match s.next_val().unwrap() {
+        Err(DataError::Io { loc, .. }) => assert!(loc.path.unwrap().ends_with("missing.csv")),
+        other => panic!("expected I/O error, got {other:?}"),
+    }
+    assert_eq!(s.next_val().unwrap().unwrap(), 3.0);
+    assert!(s.current_path().unwrap().ends_with("b.csv"));
+    match s.next_val().unwrap() {
+        Err(DataError::Parse { loc, .. }) => {
+            assert_eq!(loc.path.as_deref(), Some(bad.as_path()));
+            assert_eq!(loc.line, Some(2));
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn glob_without_matches_is_an_error() {
+    let dir = tempdir().unwrap();
+    let pattern = format!("{}/*.ndjson", dir.path().display());
+    let res = MultiFileStream::from_glob(&pattern, |p| NdjsonScalarStream::from_path(p, "v"));
+    assert!(matches!(res, Err(DataError::Io { .. })));
+
+    let s = MultiFileStream::from_paths(Vec::<&Path>::new(), |p| NdjsonScalarStream::from_path(p, "v"));
+    assert_eq!(count_stream(s).unwrap(), 0);
+}
@@ -0,0 +1,141 @@
+// data-layer/src/tests/test_parallel.rs
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::error::DataError;
+use crate::json_stream::NdjsonScalarStream;
+use crate::parallel::ParallelScan;
+use crate::stream_queries::{
+    count_stream, histogram_stream, mean_stream, sum_stream, Aggregate, BoundedF64, ExactSum, SumState,
+};
+use crate::tests::common::file;
+
+/// Values whose naive floating-point sum depends on the order of addition.
+fn value(i: usize) -> f64 {
+    let x = (i * 7919 % 1000) as f64 * 0.1 + 1e-7;
+    if i.is_multiple_of(3) { -x } else { x }
+}
+
+fn ndjson(rows: usize) -> NamedTempFile {
+    let mut text = String::new();
+    for i in 0..rows {
+        text.push_str(&format!("{{\"v\": {}}}\n", value(i)));
+        if i.is_multiple_of(50) {
+            text.push('\n');
+        }
+    }
+    file(&text)
+}
+
+#[test]
+fn exact_sum_is_order_independent() {
+    let xs = [1e16, 1.0, -1e16, 0.1, 0.2, -0.3];
+    let mut a = ExactSum::new();
+    xs.iter().for_each(|&x| a.add(x));
+    let mut b = ExactSum::new();
+    xs.iter().rev().for_each(|&x| b.add(x));
+    assert_eq!(a.value(), b.value());
+    assert_eq!(a.value(), 1.0 + (0.1 + 0.2 - 0.3));
+
+    let (mut left, mut right) = (ExactSum::new(), ExactSum::new());
+    xs[..3].iter().for_each(|&x| left.add(x));
+    xs[3..].iter().for_each(|&x| right.add(x));
+    left.merge(&right);
+    assert_eq!(left.value(), a.value());
+    assert_eq!(ExactSum::new().value(), 0.0);
+}
+
+#[test]
+fn ndjson_parallel_matches_sequential() {
+    let tmp = ndjson(2000);
+    let dom = BoundedF64::new(-50.0, 50.0);
+    let seq = || NdjsonScalarStream::from_path(tmp.path(), "v").unwrap();
+
+    for threads in [1, 2, 3, 8] {
+        let scan = ParallelScan::ndjson(tmp.path(), "v").unwrap().threads(threads);
+        let chunks = scan.chunks().unwrap();
+        assert_eq!(chunks.len(), threads);
+        assert_eq!(scan.count().unwrap(), count_stream(seq()).unwrap());
+        assert_eq!(scan.sum(dom).unwrap(), sum_stream(seq(), dom).unwrap());
+        assert_eq!(scan.mean(dom).unwrap(), mean_stream(seq(), dom).unwrap());
+        assert_eq!(scan.histogram(dom, 7).unwrap(), histogram_stream(seq(), dom, 7).unwrap());
+    }
+}
+
+#[test]
+fn csv_chunks_respect_quoted_line_breaks() {
+    let mut text = String::from("note,amount\n");
+    for i in 0..500usize {
+        if i.is_multiple_of(4) {
+            text.push_str(&format!("\"multi\nline, \"\"quoted\"\"\",{}\n", value(i)));
+        } else {
+            text.push_str(&format!("plain,{}\n", value(i)));
+        }
+    }
+    let tmp = file(&text);
+    let dom = BoundedF64::new(-100.0, 100.0);
+    let seq = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let expected = sum_stream(seq, dom).unwrap();
+
+    for threads in [2, 5, 16] {
+        let scan = ParallelScan::csv(tmp.path(), CsvOptions::by_name("amount")).threads(threads);
+        for c in scan.chunks().unwrap().iter().skip(1) {
+            // Every chunk after the first starts at a new `plain` or quoted row.
+            let head = &text.as_bytes()[c.start as usize..];
+            assert!(head.starts_with(b"plain,") || head.starts_with(b"\"multi"), "bad split at {}", c.start);
+        }
+        assert_eq!(scan.sum(dom).unwrap(), expected);
+    }
+}
+
+#[test]
+fn first_error_has_sequential_location() {
+    let mut text = String::from("amount\n");
+    for i in 0..300 {
+        text.push_str(if i == 250 { "oops\n" } else { "1\n" });
+    }
+    text.push_str("also-bad\n");
+    let tmp = file(&text);
+
+    let mut seq = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let expected = SumState::new(BoundedF64::new(0.0, 1.0)).feed(&mut seq, usize::MAX).unwrap_err();
+    let err = ParallelScan::csv(tmp.path(), CsvOptions::by_name("amount")).threads(4).count().unwrap_err();
+    match (&err, &expected) {
+        (DataError::Parse { loc, .. }, DataError::Parse { loc: want, .. }) => {
+            assert_eq!(loc, want);
+            assert_eq!((loc.line, loc.record), (Some(252), Some(251)));
+        }
+        other => panic!("expected parse errors, got {other:?}"),
+    }
+
+    let tmp = file("{\"v\": 1}\n{\"v\": 2}\n{\"v\": 3}\n{\"v\": x}\n");
+    let err = ParallelScan::ndjson(tmp.path(), "v").unwrap().threads(3).count().unwrap_err();
+    assert_eq!(err.location().unwrap().line, Some(4));
+}
+
+#[test]
+fn empty_and_tiny_inputs() {
+    let tmp = file("");
+    let scan = ParallelScan::ndjson(tmp.path(), "v").unwrap().threads(4);
+    assert!(scan.chunks().unwrap().is_empty());
+    assert_eq!(scan.count().unwrap(), 0);
+
+    // More threads than lines: chunks collapse.
+    let tmp = file("{\"v\": 1}\n{\"v\": 2}");
+    let scan = ParallelScan::ndjson(tmp.path(), "v").unwrap().threads(16);
+    assert_eq!(scan.chunks().unwrap().len(), 2);
+    assert_eq!(scan.sum(BoundedF64::new(0.0, 10.0)).unwrap(), (3.0, 2));
+}
+
+#[test]
+fn rejects_compressed_input_and_bad_paths() {
+    let mut tmp = tempfile::Builder::new().suffix(".gz").tempfile().unwrap();
+    tmp.write_all(&[0x1f, 0x8b, 0, 0]).unwrap();
+    match ParallelScan::ndjson(tmp.path(), "v").unwrap().count() {
+        Err(DataError::Io { source, .. }) => assert_eq!(source.kind(), std::io::ErrorKind::Unsupported),
+        other => panic!("expected unsupported input, got {other:?}"),
+    }
+    assert!(matches!(ParallelScan::ndjson(tmp.path(), "a[").unwrap_err(), DataError::Parse { .. }));
+}
@@ -0,0 +1,152 @@
+// data-layer/src/tests/test_record.rs
+use std::io::Write;
+
+use approx::assert_relative_eq;
+use tempfile::NamedTempFile;
+
+use crate::csv_stream::CsvRecordStream;
+use crate::error::DataError;
+use crate::json_stream::{JsonArrayRecordStream, NdjsonRecordStream};
+use crate::nulls::NullPolicy;
+use crate::record::{parse_timestamp, Field, RecordSchema, RecordStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{sum_stream, BoundedF64};
+
+fn schema() -> RecordSchema {
+    RecordSchema::new().str("user").num("amount").timestamp("ts")
+}
+
+#[test]
+fn csv_records_are_typed_and_matched_by_header() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "ts,amount,user,extra").unwrap();
+    writeln!(tmp, "2026-10-17T00:00:00Z,1.5,alice,x").unwrap();
+    writeln!(tmp, "1792195200,,\"bob, jr\",y").unwrap();
+    writeln!(tmp, "2026-10-17,oops,carol,z").unwrap();
+    writeln!(tmp, "2026-10-17").unwrap();
+
+    let mut s = CsvRecordStream::from_path(tmp.path(), schema(), b',', true).unwrap();
+
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.values(), &[Field::Str("alice".into()), Field::Num(1.5), Field::Timestamp(1_792_195_200_000)]);
+    assert_eq!(r.location().record, Some(1));
+    assert_eq!(r.get("user").and_then(Field::as_str), Some("alice"));
+
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.get("user"), Some(&Field::Str("bob, jr".into())));
+    assert!(r.get("amount").unwrap().is_null());
+    assert_eq!(r.get("ts").and_then(Field::as_timestamp), Some(1_792_195_200_000));
+
+    match s.next_record().unwrap() {
+        Err(DataError::Parse { loc, .. }) => {
+            assert_eq!(loc.record, Some(3));
+            assert_eq!(loc.column.as_deref(), Some("amount"));
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(matches!(s.next_record().unwrap(), Err(DataError::Schema { .. })));
+    assert!(s.next_record().is_none());
+}
+
+#[test]
+fn csv_records_missing_header_column_fails_at_open() {
+    let err = CsvRecordStream::from_reader(&b"user,amount\na,1\n"[..], schema(), b',', true).err().unwrap();
+    assert!(matches!(err, DataError::MissingKey { ref key, .. } if key == "ts"));
+
+    // Without a header, schema field i is column i.
+    let mut s = CsvRecordStream::from_reader(&b"a;2;0\n"[..], schema(), b';', false).unwrap();
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.values(), &[Field::Str("a".into()), Field::Num(2.0), Field::Timestamp(0)]);
+}
+
+#[test]
+fn ndjson_records_with_nested_keys_and_nulls() {
+    let data = concat!(
+        r#"{"user": "a", "m": {"amount": 2}, "ts": "2026-10-17T02:00:00+02:00"}"#, "\n",
+        "\n",
+        r#"{"user": 7, "m": {"amount": "3.5"}, "ts": 1.25}"#, "\n",
+        r#"{"user": "c", "m": {"amount": null}}"#, "\n",
+        r#"{"user": "d", "m": {"amount": [1]}}"#, "\n",
+    );
+    let schema = RecordSchema::new().str("user").num("m.amount").timestamp("ts");
+    let mut s = NdjsonRecordStream::from_reader(data.as_bytes(), schema).unwrap();
+
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.values(), &[Field::Str("a".into()), Field::Num(2.0), Field::Timestamp(1_792_195_200_000)]);
+    assert_eq!(r.location().line, Some(1));
+
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.values(), &[Field::Str("7".into()), Field::Num(3.5), Field::Timestamp(1250)]);
+    assert_eq!(r.location().line, Some(3));
+
+    let r = s.next_record().unwrap().unwrap();
+    assert!(r.get("m.amount").unwrap().is_null() && r.get("ts").unwrap().is_null());
+
+    match s.next_record().unwrap() {
+        Err(DataError::TypeMismatch { loc, found, .. }) => {
+            assert_eq!(found, "array");
+            assert_eq!(loc.line, Some(5));
+        }
+        other => panic!("expected type mismatch, got {other:?}"),
+    }
+    assert!(s.next_record().is_none());
+}
+
+#[test]
+fn projection_feeds_scalar_queries() {
+    let data = "{\"g\": \"x\", \"v\": 1}\n{\"g\": \"y\", \"v\": 20}\n{\"g\": \"x\", \"v\": null}\n{\"g\": \"x\", \"v\": 4}\n";
+    let schema = || RecordSchema::new().str("g").num("v");
+
+    let skip = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap().project("v", NullPolicy::Skip).unwrap();
+    let (sum, n) = sum_stream(skip, BoundedF64::new(0.0, 10.0)).unwrap();
+    assert_eq!((sum, n), (15.0, 3));
+
+    let mut replace = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap().project("v", NullPolicy::Replace(0.0)).unwrap();
+    let vals: Vec<f64> = std::iter::from_fn(|| replace.next_val()).map(Result::unwrap).collect();
+    assert_eq!(vals, vec![1.0, 20.0, 0.0, 4.0]);
+
+    let mut error = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap().project("v", NullPolicy::Error).unwrap();
+    let items: Vec<_> = std::iter::from_fn(|| error.next_val()).collect();
+    assert!(matches!(&items[2], Err(DataError::MissingKey { loc, .. }) if loc.line == Some(3)));
+
+    // Filter on one column while aggregating another.
+    let only_x = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap()
+        .map_scalar(|r| Ok(if r.get("g").and_then(Field::as_str) == Some("x") { r.num("v")? } else { None }));
+    let (sum, n) = sum_stream(only_x, BoundedF64::new(0.0, 10.0)).unwrap();
+    assert_relative_eq!(sum, 5.0);
+    assert_eq!(n, 2);
+
+    let src = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    assert!(matches!(src.project("g", NullPolicy::Skip).err().unwrap(), DataError::TypeMismatch { .. }));
+    let src = NdjsonRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    assert!(matches!(src.project("nope", NullPolicy::Skip).err().unwrap(), DataError::MissingKey { .. }));
+}
+
+#[test]
+fn json_array_records_from_concatenated_values() {
+    let data = r#"{"user": "a", "amount": 1, "ts": 0} {"user": "b", "amount": 2, "ts": "1970-01-01T00:00:02Z"}"#;
+    let mut s = JsonArrayRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    assert_eq!(s.next_record().unwrap().unwrap().get("amount"), Some(&Field::Num(1.0)));
+    let r = s.next_record().unwrap().unwrap();
+    assert_eq!(r.get("ts"), Some(&Field::Timestamp(2000)));
+    assert_eq!(r.location().record, Some(2));
+    assert!(s.next_record().is_none());
+
+    let data = r#"[{"user": "a", "amount": 1, "ts": 0}, {"user": "b", "amount": null, "ts": null}]"#;
+    let mut s = JsonArrayRecordStream::from_reader(data.as_bytes(), schema()).unwrap();
+    assert_eq!(s.next_record().unwrap().unwrap().get("user"), Some(&Field::Str("a".into())));
+    assert!(s.next_record().unwrap().unwrap().get("amount").unwrap().is_null());
+    assert!(s.next_record().is_none());
+}
+
+#[test]
+fn timestamps_parse_common_iso_forms() {
+    assert_eq!(parse_timestamp("1970-01-01"), Some(0));
+    assert_eq!(parse_timestamp("1970-01-01T00:00:01.5Z"), Some(1500));
+    assert_eq!(parse_timestamp("1970-01-01 01:00+0100"), Some(0));
+    assert_eq!(parse_timestamp("1969-12-31T23:59:59.999Z"), Some(-1));
+    assert_eq!(parse_timestamp("2024-02-29T12:00:00-05:30"), Some(1_709_227_800_000));
+    assert_eq!(parse_timestamp("2023-02-29"), None);
+    assert_eq!(parse_timestamp("2026-10-17T25:00:00Z"), None);
+    assert_eq!(parse_timestamp("2026-10-17Tgarbage"), None);
+}
@@ -0,0 +1,90 @@
+// data-layer/src/tests/test_schema.rs
+use crate::csv_stream::CsvRecordStream;
+use crate::error::DataError;
+use crate::json_stream::NdjsonRecordStream;
+use crate::record::{Field, FieldType, RecordStream};
+use crate::schema::{ColumnSpec, PublicSample, TableSchema};
+use crate::tests::common::file;
+
+fn spec(name: &str, ty: FieldType, nullable: bool) -> ColumnSpec {
+    ColumnSpec { name: name.into(), ty, nullable }
+}
+
+#[test]
+fn infers_csv_columns_from_a_public_sample() {
+    let tmp = file("user, amount ,ts,note\nalice,12.5,2026-10-17T08:00:00Z,x\nbob,,2026-10-17,\ncarol,3,2026-10-18,\"a, b\"\n");
+    let schema = TableSchema::infer_csv(&PublicSample::declare(tmp.path()), b',', true, 100).unwrap();
+    assert_eq!(schema.columns(), &[
+        spec("user", FieldType::Str, false),
+        spec("amount", FieldType::Num, true),
+        spec("ts", FieldType::Timestamp, false),
+        spec("note", FieldType::Str, true),
+    ]);
+    assert_eq!(schema.numeric_columns(), vec!["amount"]);
+
+    // Only the first `max_records` rows are inspected.
+    let tmp = file("v\n1\n2\nthree\n");
+    let sample = PublicSample::declare(tmp.path());
+    assert_eq!(TableSchema::infer_csv(&sample, b',', true, 2).unwrap().columns()[0].ty, FieldType::Num);
+    assert_eq!(TableSchema::infer_csv(&sample, b',', true, 3).unwrap().columns()[0].ty, FieldType::Str);
+}
+
+#[test]
+fn headerless_csv_uses_positions() {
+    let tmp = file("1;a\n2;b;7\n");
+    let schema = TableSchema::infer_csv(&PublicSample::declare(tmp.path()), b';', false, 10).unwrap();
+    assert_eq!(schema.columns(), &[
+        spec("0", FieldType::Num, false),
+        spec("1", FieldType::Str, false),
+        spec("2", FieldType::Num, true),
+    ]);
+}
+
+#[test]
+fn infers_ndjson_paths() {
+    let tmp = file(concat!(
+        "{\"id\": 1, \"m\": {\"v\": 2.5, \"unit\": \"ms\"}, \"tags\": [1], \"at\": \"2026-10-17\"}\n",
+        "{\"id\": 2, \"m\": {\"v\": null}, \"key.dot\": true, \"at\": 1792224000}\n",
+    ));
+    let schema = TableSchema::infer_ndjson(&PublicSample::declare(tmp.path()), 100).unwrap();
+    assert_eq!(schema.columns(), &[
+        spec("at", FieldType::Timestamp, false),
+        spec("id", FieldType::Num, false),
+        spec("m.unit", FieldType::Str, true),
+        spec("m.v", FieldType::Num, true),
+        spec("$['key.dot']", FieldType::Str, true),
+    ]);
+
+    // The inferred paths read the same file.
+    let mut src = NdjsonRecordStream::from_path(tmp.path(), schema.to_record_schema()).unwrap();
+    src.next_record();
+    let second = src.next_record().unwrap().unwrap();
+    assert_eq!(second.get("$['key.dot']"), Some(&Field::Str("true".into())));
+    assert_eq!(second.get("at"), Some(&Field::Timestamp(1_792_224_000_000)));
+}
+
+#[test]
+fn validates_streams_against_a_declared_schema() {
+    let tmp = file("user,amount\nalice,10\n,20\nbob,\n");
+    let declared = TableSchema::new()
+        .column("user", FieldType::Str, false)
+        .column("amount", FieldType::Num, true);
+    let src = CsvRecordStream::from_path(tmp.path(), declared.to_record_schema(), b',', true).unwrap();
+    let mut src = declared.validate(src).unwrap();
+
+    assert!(src.next_record().unwrap().is_ok());
+    match src.next_record().unwrap().unwrap_err() {
+        DataError::Schema { loc, .. } => assert_eq!((loc.record, loc.column.as_deref()), (Some(2), Some("user"))),
+        other => panic!("expected schema error, got {other:?}"),
+    }
+    assert!(src.next_record().unwrap().is_ok());
+    assert!(src.next_record().is_none());
+
+    // The stream must read the declared columns with the declared types.
+    let wrong = TableSchema::new().column("amount", FieldType::Str, true);
+    let src = CsvRecordStream::from_path(tmp.path(), declared.to_record_schema(), b',', true).unwrap();
+    assert!(matches!(wrong.validate(src), Err(DataError::Schema { .. })));
+    let missing = TableSchema::new().column("ts", FieldType::Timestamp, true);
+    let src = CsvRecordStream::from_path(tmp.path(), declared.to_record_schema(), b',', true).unwrap();
+    assert!(matches!(missing.validate(src), Err(DataError::MissingKey { .. })));
+}
@@ -0,0 +1,108 @@
+// data-layer/src/tests/test_socket.rs
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use crate::csv_stream::CsvOptions;
+use crate::error::DataError;
+use crate::socket::{IngestOptions, SocketScalarStream, WireFormat};
+use crate::stream::ScalarStream;
+
+fn collect(s: &mut impl ScalarStream) -> Vec<Result<f64, DataError>> {
+    std::iter::from_fn(|| s.next_val()).collect()
+}
+
+fn values(s: &mut impl ScalarStream) -> Vec<f64> {
+    collect(s).into_iter().map(Result::unwrap).collect()
+}
+
+#[test]
+fn tcp_connection_close_ends_the_stream() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let writer = thread::spawn(move || {
+        let mut conn = TcpStream::connect(addr).unwrap();
+        conn.write_all(b"1.5\n\n2\n").unwrap();
+        conn.write_all(b"x\n4").unwrap();
+    });
+
+    let mut s = SocketScalarStream::accept_tcp(&listener, IngestOptions::new(WireFormat::Numeric)).unwrap();
+    assert!(s.peer().unwrap().starts_with("127.0.0.1:"));
+    let items = collect(&mut s);
+    writer.join().unwrap();
+
+    assert_eq!(items.len(), 4);
+    assert_eq!(*items[1].as_ref().unwrap(), 2.0);
+    match &items[2] {
+        Err(DataError::Parse { loc, .. }) => assert_eq!(loc.line, Some(4)),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert_eq!(*items[3].as_ref().unwrap(), 4.0);
+    assert_eq!(s.records(), 4);
+    assert!(!s.limit_reached());
+}
+
+#[test]
+fn record_limit_stops_reading() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let writer = thread::spawn(move || {
+        let mut conn = TcpStream::connect(addr).unwrap();
+        // The sender may fail once the receiver has closed the connection.
+        for i in 0..10_000 {
+            if writeln!(conn, "{{\"v\": {}}}", i % 7).is_err() {
+                break;
+            }
+        }
+    });
+
+    let opts = IngestOptions::new(WireFormat::Ndjson { key_path: "v".into() }).max_records(3);
+    let mut s = SocketScalarStream::accept_tcp(&listener, opts).unwrap();
+    assert_eq!(values(&mut s), vec![0.0, 1.0, 2.0]);
+    assert!(s.limit_reached());
+    assert_eq!(s.records(), 3);
+    drop(s);
+    writer.join().unwrap();
+}
+
+#[test]
+fn csv_header_is_not_counted() {
+    let text = "ts,v\n1,10\n\n2,20\n3,30\n";
+    let opts = IngestOptions::new(WireFormat::Csv(CsvOptions::by_name("v"))).max_records(2);
+    let mut s = SocketScalarStream::from_reader(text.as_bytes(), opts).unwrap();
+    assert_eq!(values(&mut s), vec![10.0, 20.0]);
+    assert!(s.limit_reached());
+
+    let opts = IngestOptions::new(WireFormat::Csv(CsvOptions::by_name("w")));
+    assert!(matches!(SocketScalarStream::from_reader(text.as_bytes(), opts), Err(DataError::MissingKey { .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket_and_fifo() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let dir = tempfile::tempdir().unwrap();
+    let sock = dir.path().join("ingest.sock");
+    let listener = UnixListener::bind(&sock).unwrap();
+    let writer = thread::spawn({
+        let sock = sock.clone();
+        move || UnixStream::connect(sock).unwrap().write_all(b"{\"v\": 1}\n{\"v\": 2}\n").unwrap()
+    });
+    let opts = IngestOptions::new(WireFormat::Ndjson { key_path: "v".into() });
+    let mut s = SocketScalarStream::accept_unix(&listener, opts).unwrap();
+    assert_eq!(values(&mut s), vec![1.0, 2.0]);
+    writer.join().unwrap();
+
+    let fifo = dir.path().join("ingest.fifo");
+    let status = std::process::Command::new("mkfifo").arg(&fifo).status().unwrap();
+    assert!(status.success());
+    let writer = thread::spawn({
+        let fifo = fifo.clone();
+        move || std::fs::OpenOptions::new().write(true).open(fifo).unwrap().write_all(b"5\n6\n7\n").unwrap()
+    });
+    let mut s = SocketScalarStream::open_fifo(&fifo, IngestOptions::new(WireFormat::Numeric).max_records(2)).unwrap();
+    assert_eq!(s.peer(), Some(fifo.display().to_string().as_str()));
+    assert_eq!(values(&mut s), vec![5.0, 6.0]);
+    writer.join().unwrap();
+}
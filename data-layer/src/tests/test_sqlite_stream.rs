@@ -0,0 +1,148 @@
+// data-layer/src/tests/test_sqlite_stream.rs
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+use crate::error::DataError;
+use crate::nulls::NullPolicy;
+use crate::record::{Field, RecordSchema, RecordStream};
+use crate::sqlite_stream::{SqliteRecordStream, SqliteScalarStream};
+use crate::stream::ScalarStream;
+use crate::stream_queries::{sum_stream, BoundedF64};
+
+fn db(sql: &str) -> (TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ops.db");
+    Connection::open(&path).unwrap().execute_batch(sql).unwrap();
+    (dir, path)
+}
+
+const REQUESTS: &str = "
+    CREATE TABLE requests (id INTEGER, user TEXT, latency REAL, ts TEXT);
+    INSERT INTO requests VALUES (1, 'a', 12.5, '2026-10-17T08:00:00Z');
+    INSERT INTO requests VALUES (2, 'b', NULL, '2026-10-17T08:00:01Z');
+    INSERT INTO requests VALUES (3, 'a', 40, NULL);
+    INSERT INTO requests VALUES (4, 'c', 'slow', '2026-10-17');
+";
+
+fn collect(mut s: impl ScalarStream) -> Vec<Result<f64, DataError>> {
+    std::iter::from_fn(|| s.next_val()).collect()
+}
+
+#[test]
+fn streams_a_numeric_column_with_null_policies() {
+    let (_dir, path) = db(REQUESTS);
+    let sql = "SELECT latency FROM requests WHERE id < 4 ORDER BY id";
+
+    let vals: Vec<f64> = collect(SqliteScalarStream::from_query(&path, sql, "latency", NullPolicy::Skip).unwrap())
+        .into_iter().map(Result::unwrap).collect();
+    assert_eq!(vals, vec![12.5, 40.0]);
+
+    let s = SqliteScalarStream::from_query(&path, sql, "latency", NullPolicy::Replace(0.0)).unwrap();
+    assert_eq!(sum_stream(s, BoundedF64::new(0.0, 100.0)).unwrap(), (52.5, 3));
+
+    let items = collect(SqliteScalarStream::from_query(&path, sql, "latency", NullPolicy::Error).unwrap());
+    match &items[1] {
+        Err(DataError::MissingKey { loc, .. }) => assert_eq!(loc.record, Some(2)),
+        other => panic!("expected missing key, got {other:?}"),
+    }
+    assert_eq!(items.len(), 3);
+}
+
+#[test]
+fn type_errors_carry_the_row_number() {
+    let (_dir, path) = db(REQUESTS);
+    let items = collect(SqliteScalarStream::from_table(&path, "requests", "latency", NullPolicy::Skip).unwrap());
+    assert_eq!(items.len(), 3);
+    match &items[2] {
+        Err(DataError::TypeMismatch { loc, found, .. }) => {
+            assert_eq!(found, "TEXT");
+            assert_eq!((loc.record, loc.column.as_deref()), (Some(4), Some("latency")));
+            assert_eq!(loc.path.as_deref(), Some(path.as_path()));
+        }
+        other => panic!("expected type mismatch, got {other:?}"),
+    }
+}
+
+#[test]
+fn invalid_real_timestamps_are_errors() {
+    let (_dir, path) = db("
+        CREATE TABLE events (ts REAL);
+        INSERT INTO events VALUES (1.5), (9e999), (1e300), (-9e999);
+    ");
+    let schema = RecordSchema::new().timestamp("ts");
+    let mut s = SqliteRecordStream::from_table(&path, "events", schema).unwrap();
+    assert_eq!(s.next_record().unwrap().unwrap().get("ts"), Some(&Field::Timestamp(1500)));
+    for record in 2..=4 {
+        match s.next_record().unwrap() {
+            Err(DataError::Parse { loc, .. }) => assert_eq!((loc.record, loc.column.as_deref()), (Some(record), Some("ts"))),
+            other => panic!("expected parse error, got {other:?}"),
+        }
+    }
+    assert!(s.next_record().is_none());
+}
+
+#[test]
+fn streams_typed_records() {
+    let (_dir, path) = db(REQUESTS);
+    let schema = RecordSchema::new().num("id").str("user").timestamp("ts");
+    let mut s = SqliteRecordStream::from_table(&path, "requests", schema).unwrap();
+
+    let first = s.next_record().unwrap().unwrap();
+    assert_eq!(first.get("user"), Some(&Field::Str("a".into())));
+    assert_eq!(first.get("ts"), Some(&Field::Timestamp(1_792_224_000_000)));
+    assert_eq!(first.location().record, Some(1));
+    s.next_record();
+    assert_eq!(s.next_record().unwrap().unwrap().get("ts"), Some(&Field::Null));
+    assert!(s.next_record().unwrap().is_ok());
+    assert!(s.next_record().is_none());
+
+    let schema = RecordSchema::new().str("user").num("latency");
+    let sql = "SELECT user, latency FROM requests";
+    let total: Vec<_> = {
+        let mut p = SqliteRecordStream::from_query(&path, sql, schema).unwrap().project("latency", NullPolicy::Skip).unwrap();
+        std::iter::from_fn(|| p.next_val()).collect()
+    };
+    assert_eq!(total.len(), 3);
+    assert!(matches!(&total[2], Err(DataError::TypeMismatch { loc, .. }) if loc.record == Some(4)));
+}
+
+#[test]
+fn rejects_writes_bad_sql_and_missing_columns() {
+    let (_dir, path) = db(REQUESTS);
+    match SqliteScalarStream::from_query(&path, "DELETE FROM requests", "id", NullPolicy::Skip) {
+        Err(DataError::Io { source, .. }) => assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied),
+        Err(other) => panic!("expected permission error, got {other:?}"),
+        Ok(_) => panic!("write statement accepted"),
+    }
+    assert!(matches!(
+        SqliteScalarStream::from_query(&path, "SELEC id FROM requests", "id", NullPolicy::Skip),
+        Err(DataError::Parse { .. })
+    ));
+    assert!(matches!(
+        SqliteScalarStream::from_query(&path, "SELECT id AS n FROM requests", "id", NullPolicy::Skip),
+        Err(DataError::MissingKey { .. })
+    ));
+    assert!(matches!(
+        SqliteScalarStream::from_table(path.with_file_name("none.db"), "requests", "id", NullPolicy::Skip),
+        Err(DataError::Io { .. })
+    ));
+
+    // The table is unchanged.
+    let n = Connection::open(&path).unwrap().query_row("SELECT COUNT(*) FROM requests", [], |r| r.get::<_, i64>(0)).unwrap();
+    assert_eq!(n, 4);
+}
+
+#[test]
+fn large_results_stream_through_a_bounded_buffer() {
+    let (_dir, path) = db("CREATE TABLE t (v INTEGER);");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch("WITH RECURSIVE c(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM c WHERE i < 5000) INSERT INTO t SELECT i FROM c;").unwrap();
+
+    let s = SqliteScalarStream::from_table(&path, "t", "v", NullPolicy::Error).unwrap();
+    assert_eq!(sum_stream(s, BoundedF64::new(0.0, 1e9)).unwrap(), (12_502_500.0, 5000));
+
+    // Dropping a stream early ends its query thread.
+    let mut s = SqliteScalarStream::from_table(&path, "t", "v", NullPolicy::Error).unwrap();
+    assert_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    drop(s);
+}
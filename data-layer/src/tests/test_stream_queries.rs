@@ -1,6 +1,7 @@
 // data-layer/src/tests/test_stream_queries.rs
 use approx::assert_relative_eq;
 
+use crate::error::{DataError, Location};
 use crate::stream::ScalarStream;
 use crate::stream_queries::{
     BoundedF64, count_stream, sum_stream, mean_stream, histogram_stream,
@@ -9,7 +10,7 @@ use crate::stream_queries::{
 
 /// Minimal in-memory stream for tests.
 struct VecScalarStream {
-    data: Vec<Result<f64, Box<dyn std::error::Error + Send + Sync>>>,
+    data: Vec<Result<f64, String>>,
     idx: usize,
 }
 impl VecScalarStream {
@@ -20,7 +21,7 @@ impl VecScalarStream {
         }
     }
     fn with_error_at(vals: &[f64], err_at: usize) -> Self {
-        let mut data: Vec<Result<f64, Box<dyn std::error::Error + Send + Sync>>> =
+        let mut data: Vec<Result<f64, String>> =
             vals.iter().copied().map(|v| Ok(v) as _).collect();
         if err_at < data.len() {
             data[err_at] = Err("parse error".into());
@@ -29,9 +30,10 @@ impl VecScalarStream {
     }
 }
 impl ScalarStream for VecScalarStream {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         if self.idx >= self.data.len() { return None; }
-        let out = Some(self.data[self.idx].as_ref().map(|v| *v).map_err(|e| e.to_string().into()));
+        let loc = Location::default().with_record(self.idx as u64 + 1);
+        let out = Some(self.data[self.idx].clone().map_err(|e| DataError::parse(loc, e)));
         self.idx += 1;
         out
     }
@@ -107,4 +109,8 @@ fn error_propagates() {
     let s = VecScalarStream::with_error_at(&[1.0, 2.0, 3.0], 1);
     let err = mean_stream(s, dom).unwrap_err();
     assert!(err.to_string().contains("parse error"));
+    match err {
+        DataError::Parse { loc, .. } => assert_eq!(loc.record, Some(2)),
+        other => panic!("expected parse error, got {other:?}"),
+    }
 }
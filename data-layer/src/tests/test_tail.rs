@@ -0,0 +1,182 @@
+// data-layer/src/tests/test_tail.rs
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::csv_stream::{CsvOptions, CsvScalarStream};
+use crate::json_stream::NdjsonScalarStream;
+use crate::stream::ScalarStream;
+use crate::tail::{TailOptions, TailReader, TailStart};
+
+fn opts() -> TailOptions {
+    TailOptions::default().poll_interval(Duration::from_millis(5))
+}
+
+fn append(path: &Path, text: &str) {
+    let mut f = OpenOptions::new().create(true).append(true).open(path).unwrap();
+    f.write_all(text.as_bytes()).unwrap();
+}
+
+fn next(s: &mut impl ScalarStream) -> f64 {
+    s.next_val().unwrap().unwrap()
+}
+
+#[test]
+fn follows_appends_and_stops_after_draining() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n{\"v\": 2}\n");
+
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts()).unwrap();
+    assert_eq!((next(&mut s), next(&mut s)), (1.0, 2.0));
+
+    let writer = {
+        let path = path.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            append(&path, "{\"v\": 3}\n{\"v\"");
+            thread::sleep(Duration::from_millis(20));
+            append(&path, ": 4}\n");
+        })
+    };
+    // Blocks until the writer has appended; the split line arrives whole.
+    assert_eq!((next(&mut s), next(&mut s)), (3.0, 4.0));
+    writer.join().unwrap();
+
+    append(&path, "{\"v\": 5}\n{\"v\": 6");
+    handle.stop();
+    // Complete lines already in the file are still handed out, the partial one is not.
+    assert_eq!(next(&mut s), 5.0);
+    assert!(s.next_val().is_none());
+    assert_eq!(handle.checkpoint().offset, fs::metadata(&path).unwrap().len() - "{\"v\": 6".len() as u64);
+}
+
+#[test]
+fn resumes_from_checkpoint_without_double_counting() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n{\"v\": 2}\n{\"v\": 3}\n");
+
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts()).unwrap();
+    assert_eq!((next(&mut s), next(&mut s)), (1.0, 2.0));
+    let cp = handle.checkpoint();
+    assert_eq!(cp.offset, 18);
+    drop(s);
+
+    // The checkpoint survives a restart (serialized by the job).
+    let cp = serde_json::from_str(&serde_json::to_string(&cp).unwrap()).unwrap();
+    append(&path, "{\"v\": 4}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts().start(TailStart::Checkpoint(cp))).unwrap();
+    handle.stop();
+    let rest: Vec<f64> = std::iter::from_fn(|| s.next_val()).map(Result::unwrap).collect();
+    assert_eq!(rest, vec![3.0, 4.0]);
+}
+
+#[test]
+fn checkpoint_of_a_replaced_file_restarts_from_the_beginning() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n{\"v\": 2}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts()).unwrap();
+    next(&mut s);
+    let cp = handle.checkpoint();
+    drop(s);
+
+    fs::rename(&path, dir.path().join("m.ndjson.1")).unwrap();
+    append(&path, "{\"v\": 10}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts().start(TailStart::Checkpoint(cp))).unwrap();
+    handle.stop();
+    assert_eq!(next(&mut s), 10.0);
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn start_at_end_skips_existing_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts().start(TailStart::End)).unwrap();
+    append(&path, "{\"v\": 2}\n");
+    handle.stop();
+    assert_eq!(next(&mut s), 2.0);
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn handles_truncation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n{\"v\": 2}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts()).unwrap();
+    assert_eq!((next(&mut s), next(&mut s)), (1.0, 2.0));
+
+    fs::write(&path, "{\"v\": 7}\n").unwrap();
+    assert_eq!(next(&mut s), 7.0);
+    assert_eq!(handle.rotations(), 1);
+    assert_eq!(handle.checkpoint().offset, 9);
+}
+
+#[cfg(unix)]
+#[test]
+fn follows_rotation_after_draining_the_old_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.ndjson");
+    append(&path, "{\"v\": 1}\n");
+    let (mut s, handle) = NdjsonScalarStream::tail(&path, "v", opts()).unwrap();
+    assert_eq!(next(&mut s), 1.0);
+    let before = handle.checkpoint();
+
+    // Lines written just before the rename are still read from the old file.
+    append(&path, "{\"v\": 2}\n");
+    fs::rename(&path, dir.path().join("m.ndjson.1")).unwrap();
+    append(&path, "{\"v\": 3}\n");
+
+    assert_eq!((next(&mut s), next(&mut s)), (2.0, 3.0));
+    assert_eq!(handle.rotations(), 1);
+    let after = handle.checkpoint();
+    assert_ne!(after.file, before.file);
+    assert_eq!(after.offset, 9);
+}
+
+#[cfg(unix)]
+#[test]
+fn csv_header_is_read_once_across_resume_and_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.csv");
+    append(&path, "ts,v\n1,10\n2,20\n");
+
+    let (mut s, handle) = CsvScalarStream::tail_with(&path, CsvOptions::by_name("v"), opts()).unwrap();
+    assert_eq!(next(&mut s), 10.0);
+    let cp = handle.checkpoint();
+    drop(s);
+
+    let (mut s, handle) =
+        CsvScalarStream::tail_with(&path, CsvOptions::by_name("v"), opts().start(TailStart::Checkpoint(cp))).unwrap();
+    assert_eq!(next(&mut s), 20.0);
+
+    fs::rename(&path, dir.path().join("m.csv.1")).unwrap();
+    append(&path, "ts,v\n3,30\n");
+    assert_eq!(next(&mut s), 30.0);
+    handle.stop();
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn reader_hands_out_one_line_per_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("m.txt");
+    append(&path, "a\nbb\nccc");
+    let mut r = TailReader::open(&path, opts()).unwrap();
+    let handle = r.handle();
+    let mut buf = [0u8; 64];
+    let n = r.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"a\n");
+    assert_eq!(handle.checkpoint().offset, 2);
+    let n = r.read(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"bb\n");
+    handle.stop();
+    assert_eq!(r.read(&mut buf).unwrap(), 0);
+    assert_eq!(handle.checkpoint().offset, 5);
+}
@@ -0,0 +1,109 @@
+use approx::assert_relative_eq;
+use tempfile::NamedTempFile;
+use std::io::Write;
+
+use crate::error::DataError;
+use crate::stream::ScalarStream;
+use crate::stream_queries::{BoundedF64, sum_stream};
+use crate::xml_stream::XmlScalarStream;
+
+fn sensor_export() -> NamedTempFile {
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(br#"<?xml version="1.0"?>
+<export>
+  <meta><value>999</value></meta>
+  <record id="1"><reading v="1.5"><value>10</value></reading></record>
+  <record id="2"><reading v="-2"><value> 2.5e1 </value></reading></record>
+  <record id="3"><reading v="3"/></record>
+  <record id="4"><reading v="x"><value><![CDATA[40]]></value></reading></record>
+</export>"#).unwrap();
+    tmp
+}
+
+#[test]
+fn xml_streams_element_text_by_path() {
+    let tmp = sensor_export();
+    let path = tmp.path().to_str().unwrap();
+    let mut s = XmlScalarStream::from_path(path, "record", "reading/value").unwrap();
+
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 10.0);
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 25.0);
+    // record 3 has no <value> element -> error item, stream continues
+    match s.next_val().unwrap().unwrap_err() {
+        DataError::MissingKey { loc, key } => {
+            assert_eq!(key, "reading/value");
+            assert_eq!(loc.record, Some(3));
+        }
+        other => panic!("expected missing key, got {other:?}"),
+    }
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 40.0);
+    assert!(s.next_val().is_none());
+}
+
+#[test]
+fn xml_streams_attributes() {
+    let tmp = sensor_export();
+    let path = tmp.path().to_str().unwrap();
+
+    let mut ids = XmlScalarStream::from_path(path, "record", "@id").unwrap();
+    let out: Vec<f64> = std::iter::from_fn(|| ids.next_val()).map(|r| r.unwrap()).collect();
+    assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+
+    let mut vs = XmlScalarStream::from_path(path, "record", "reading/@v").unwrap();
+    assert_relative_eq!(vs.next_val().unwrap().unwrap(), 1.5);
+    assert_relative_eq!(vs.next_val().unwrap().unwrap(), -2.0);
+    assert_relative_eq!(vs.next_val().unwrap().unwrap(), 3.0);
+    assert!(vs.next_val().unwrap().is_err()); // "x" is not numeric
+    assert!(vs.next_val().is_none());
+}
+
+#[test]
+fn xml_attribute_must_be_the_last_path_segment() {
+    for path in ["@a/b", "@x/@y", "reading/@v/value"] {
+        match XmlScalarStream::from_reader("<r/>".as_bytes(), "r", path) {
+            Err(DataError::Parse { loc, .. }) => assert_eq!(loc.column.as_deref(), Some(path)),
+            Err(e) => panic!("{path}: unexpected error {e}"),
+            Ok(_) => panic!("{path}: accepted"),
+        }
+    }
+}
+
+#[test]
+fn xml_record_text_and_entities() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    write!(tmp, "<rows><r>&#49;.5</r><r>-&#x32;</r></rows>").unwrap();
+    let path = tmp.path().to_str().unwrap();
+
+    let s = XmlScalarStream::from_path(path, "r", "").unwrap();
+    let (sum, n) = sum_stream(s, BoundedF64::new(-10.0, 10.0)).unwrap();
+    assert_eq!(n, 2);
+    assert_relative_eq!(sum, -0.5, epsilon = 1e-12);
+}
+
+#[test]
+fn xml_mixed_content_keeps_the_target_text() {
+    let xml = "<rows>\
+        <r><value>5<unit>ms</unit></value></r>\
+        <r><value><b/>6</value></r>\
+        <r><value><i>x</i>7<i>y</i></value></r>\
+    </rows>";
+    let mut s = XmlScalarStream::from_reader(xml.as_bytes(), "r", "value").unwrap();
+    let out: Vec<f64> = std::iter::from_fn(|| s.next_val()).map(|r| r.unwrap()).collect();
+    assert_eq!(out, vec![5.0, 6.0, 7.0]);
+
+    // The record element itself as target.
+    let mut s = XmlScalarStream::from_reader("<rows><r>8<note>n</note></r></rows>".as_bytes(), "r", "").unwrap();
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 8.0);
+}
+
+#[test]
+fn xml_malformed_document_is_error_then_end() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    write!(tmp, "<rows><r>1</r><r>2</x></rows>").unwrap();
+    let path = tmp.path().to_str().unwrap();
+
+    let mut s = XmlScalarStream::from_path(path, "r", "").unwrap();
+    assert_relative_eq!(s.next_val().unwrap().unwrap(), 1.0);
+    assert!(matches!(s.next_val().unwrap(), Err(DataError::Parse { .. })));
+    assert!(s.next_val().is_none());
+}
@@ -0,0 +1,269 @@
+// src/xml_stream.rs
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::compression;
+use crate::error::{DataError, Location};
+use crate::stream::ScalarStream;
+
+/// Where the value lives inside a record element.
+///
+/// Parsed from a slash-separated path relative to the record element:
+/// - `"value"` → text content of `<record><value>..</value></record>`.
+/// - `"reading/value"` → text content of a nested element.
+/// - `"reading/@v"` → attribute `v` of `<reading>`.
+/// - `"@v"` → attribute `v` of the record element itself.
+/// - `""` → text content of the record element itself.
+///
+/// An `@attr` segment may only appear once, as the last segment.
+struct XmlValuePath {
+    elements: Vec<Vec<u8>>,
+    attr: Option<Vec<u8>>,
+}
+
+impl XmlValuePath {
+    fn parse(path: &str) -> Result<Self, DataError> {
+        let mut elements = Vec::new();
+        let mut attr = None;
+        for seg in path.split('/').filter(|s| !s.is_empty()) {
+            if attr.is_some() {
+                let msg = format!("invalid XML value path: `{}` after an attribute segment", seg);
+                return Err(DataError::parse(Location::default().with_column(path), msg));
+            }
+            match seg.strip_prefix('@') {
+                Some(name) => attr = Some(name.as_bytes().to_vec()),
+                None => elements.push(seg.as_bytes().to_vec()),
+            }
+        }
+        Ok(Self { elements, attr })
+    }
+
+    /// True if the element stack (relative to the record) points at the target element.
+    fn matches(&self, stack: &[Vec<u8>]) -> bool {
+        stack == self.elements.as_slice()
+    }
+}
+
+/// A `ScalarStream` implementation for **XML documents** with repeated record elements.
+///
+/// The document is read event by event with `quick-xml`, so only the current
+/// record is held in memory. Each element named `record_tag` (matched by local
+/// name, at any depth) yields one value, extracted by a slash-separated path
+/// relative to the record (see the examples below).
+///
+/// - Only the first match inside a record is used.
+/// - Errors (e.g., malformed XML, missing element/attribute or a non-numeric value)
+///   are returned as `Some(Err(..))` instead of panicking.
+/// - After a malformed-XML error the stream ends, since the reader cannot resync.
+///
+/// # Example file
+/// ```xml
+/// <export>
+///   <record id="1"><reading v="1.5"><value>10</value></reading></record>
+///   <record id="2"><reading v="-2"><value>20</value></reading></record>
+/// </export>
+/// ```
+///
+/// With `record_tag = "record"`, `value_path = "reading/value"` yields `10.0`, `20.0`;
+/// `value_path = "reading/@v"` yields `1.5`, `-2.0`; `value_path = "@id"` yields `1.0`, `2.0`.
+pub struct XmlScalarStream<R: BufRead> {
+    reader: Reader<R>,
+    record_tag: Vec<u8>,
+    value_path: String,
+    path: XmlValuePath,
+    buf: Vec<u8>,
+    done: bool,
+    file: Option<PathBuf>,
+    record: u64,
+}
+
+impl XmlScalarStream<BufReader<Box<dyn Read + Send>>> {
+    /// Creates a new XML-backed scalar stream.
+    ///
+    /// Compressed files are decompressed while streaming (see [`compression`]).
+    ///
+    /// # Arguments
+    /// * `path` – path to the XML file.
+    /// * `record_tag` – local name of the repeated record element (e.g. `"record"`).
+    /// * `value_path` – slash-separated element path, optionally ending in `@attr`.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        record_tag: impl Into<String>,
+        value_path: impl Into<String>,
+    ) -> Result<Self, DataError> {
+        let file = compression::open_path(path.as_ref())?;
+        let mut s = Self::from_reader(BufReader::new(file), record_tag, value_path)?;
+        s.file = Some(path.as_ref().to_path_buf());
+        Ok(s)
+    }
+}
+
+impl<R: BufRead> XmlScalarStream<R> {
+    /// Creates a new XML-backed scalar stream from any buffered reader.
+    ///
+    /// Fails only if `value_path` is not a valid path.
+    ///
+    /// # Arguments
+    /// * `inner` – source of XML bytes (e.g. `BufReader::new(std::io::stdin())`).
+    /// * `record_tag` – local name of the repeated record element (e.g. `"record"`).
+    /// * `value_path` – slash-separated element path, optionally ending in `@attr`.
+    pub fn from_reader(inner: R, record_tag: impl Into<String>, value_path: impl Into<String>) -> Result<Self, DataError> {
+        let mut reader = Reader::from_reader(inner);
+        reader.config_mut().expand_empty_elements = true;
+        let value_path = value_path.into();
+        Ok(Self {
+            reader,
+            record_tag: record_tag.into().into_bytes(),
+            path: XmlValuePath::parse(&value_path)?,
+            value_path,
+            buf: Vec::new(),
+            done: false,
+            file: None,
+            record: 0,
+        })
+    }
+
+    /// Location of the current record.
+    fn loc(&self) -> Location {
+        Location { path: self.file.clone(), ..Location::default() }
+            .with_record(self.record)
+            .with_column(self.value_path.clone())
+    }
+
+    /// Wraps a `quick-xml` error with the current location and byte offset.
+    fn xml_error(&self, e: impl std::fmt::Display) -> DataError {
+        DataError::parse(self.loc(), format!("{} (byte offset {})", e, self.reader.buffer_position()))
+    }
+
+    /// Reads the attribute named by the value path from a start tag, if present.
+    fn attr_value(&self, start: &BytesStart) -> Result<Option<String>, DataError> {
+        let Some(name) = &self.path.attr else { return Ok(None); };
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| self.xml_error(e))?;
+            if attr.key.local_name().as_ref() == name.as_slice() {
+                let v = attr.decode_and_unescape_value(self.reader.decoder()).map_err(|e| self.xml_error(e))?;
+                return Ok(Some(v.into_owned()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Consumes events until the end of the current record and returns the raw value text.
+    ///
+    /// Called right after the record's start tag has been read.
+    fn read_record(&mut self, start: &BytesStart) -> Result<Option<String>, DataError> {
+        let mut stack: Vec<Vec<u8>> = Vec::new();
+        let mut found: Option<String> = if self.path.matches(&stack) { self.attr_value(start)? } else { None };
+        let mut text = String::new();
+        // Depth of the matched target element while its text is being collected.
+        // Only its own text counts, not that of child elements (mixed content).
+        let mut target = (self.path.attr.is_none() && self.path.matches(&stack)).then_some(0);
+
+        loop {
+            self.buf.clear();
+            let event = match self.reader.read_event_into(&mut self.buf) {
+                Ok(event) => event,
+                Err(e) => {
+                    let e = e.to_string();
+                    return Err(self.xml_error(e));
+                }
+            };
+            let in_text = target == Some(stack.len());
+            match event {
+                Event::Start(e) => {
+                    stack.push(e.local_name().as_ref().to_vec());
+                    if found.is_none() && target.is_none() && self.path.matches(&stack) {
+                        if self.path.attr.is_some() {
+                            let e = e.into_owned();
+                            found = self.attr_value(&e)?;
+                        } else {
+                            target = Some(stack.len());
+                        }
+                    }
+                }
+                Event::End(_) => {
+                    if in_text {
+                        found = Some(std::mem::take(&mut text));
+                        target = None;
+                    }
+                    if stack.pop().is_none() {
+                        // End of the record element itself.
+                        return Ok(found);
+                    }
+                }
+                Event::Text(t) if in_text => match t.decode() {
+                    Ok(t) => text.push_str(&t),
+                    Err(e) => return Err(DataError::parse(self.loc(), e.to_string())),
+                },
+                Event::CData(t) if in_text => match t.decode() {
+                    Ok(t) => text.push_str(&t),
+                    Err(e) => return Err(DataError::parse(self.loc(), e.to_string())),
+                },
+                Event::GeneralRef(r) if in_text => {
+                    let resolved = match r.resolve_char_ref() {
+                        Ok(Some(ch)) => Some(ch.to_string()),
+                        Ok(None) => r.decode().ok()
+                            .and_then(|name| resolve_predefined_entity(&name).map(str::to_string)),
+                        Err(_) => None,
+                    };
+                    match resolved {
+                        Some(v) => text.push_str(&v),
+                        None => {
+                            let msg = format!("unknown XML entity '&{};'", String::from_utf8_lossy(&r));
+                            return Err(DataError::parse(self.loc(), msg));
+                        }
+                    }
+                }
+                Event::Eof => return Err(DataError::parse(self.loc(), "unexpected end of XML inside record element")),
+                _ => {}
+            }
+        }
+    }
+
+    /// Finds the next record element and extracts its value as `f64`.
+    fn next_record(&mut self) -> Option<Result<f64, DataError>> {
+        loop {
+            self.buf.clear();
+            let start = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Eof) => return None,
+                Ok(Event::Start(e)) if e.local_name().as_ref() == self.record_tag.as_slice() => e.into_owned(),
+                Ok(_) => continue,
+                Err(e) => {
+                    self.done = true;
+                    let e = e.to_string();
+                    return Some(Err(self.xml_error(e)));
+                }
+            };
+            self.record += 1;
+            let raw = match self.read_record(&start) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            return Some(match raw {
+                Some(s) => s.trim().parse::<f64>()
+                    .map_err(|e| DataError::parse(self.loc(), format!("cannot parse '{}' as f64: {}", s.trim(), e))),
+                None => Err(DataError::missing_key(self.loc(), self.value_path.clone())),
+            });
+        }
+    }
+}
+
+impl<R: BufRead> ScalarStream for XmlScalarStream<R> {
+    /// Reads the next record element and returns the extracted value.
+    ///
+    /// - `Some(Ok(f64))` → successfully parsed value.
+    /// - `Some(Err(e))` → error while reading/parsing/extracting.
+    /// - `None` → end of document reached (or the XML was malformed).
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        if self.done {
+            return None;
+        }
+        self.next_record()
+    }
+}
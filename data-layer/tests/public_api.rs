@@ -0,0 +1,32 @@
+// data-layer/tests/public_api.rs
+//! Builds sources and queries through the prelude only, as a downstream crate would.
+
+use std::io::Write;
+
+use data_layer::prelude::*;
+use tempfile::NamedTempFile;
+
+#[test]
+fn csv_sum_and_histogram_through_prelude() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "id,amount").unwrap();
+    for (i, v) in [1.0, 4.0, 7.5, 12.0].iter().enumerate() {
+        writeln!(tmp, "{},{}", i, v).unwrap();
+    }
+    let dom = BoundedF64::new(0.0, 10.0);
+
+    let src = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let (sum, n) = sum_stream(src, dom).unwrap();
+    assert_eq!((sum, n), (22.5, 4));
+    assert_eq!(l1_sens_sum(dom), 10.0);
+
+    let src = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let hist = histogram_stream(src, dom, 2).unwrap();
+    assert_eq!(hist.iter().map(|&(_, _, c)| c).collect::<Vec<_>>(), vec![2, 2]);
+}
+
+#[test]
+fn errors_are_reexported_at_the_root() {
+    let err: data_layer::DataError = count_stream(IterStream::new(vec![Ok(1.0), Err(std::io::Error::other("boom"))])).unwrap_err();
+    assert_eq!(err.kind(), DataErrorKind::Io);
+}
@@ -1,12 +1,24 @@
 use data_layer::stream::ScalarStream;
-use rand::{rngs::StdRng, SeedableRng, Rng};
+use data_layer::stream_queries::{Aggregate, BoundedF64, SumState};
+use rand::{rngs::StdRng, SeedableRng};
 use rand_distr::{Distribution, Normal};
 use crate::error::MechError;
+use crate::noise::sample_laplace;
 
-/// Sample Laplace noise (same as in noise.rs)
-fn sample_laplace<R: Rng>(rng: &mut R, b: f64) -> f64 {
-    let u: f64 = rng.gen::<f64>() - 0.5;
-    -b * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+/// Sums `src` for the entry points that take no domain, with the same exact
+/// summation as [`sum_stream`](data_layer::stream_queries::sum_stream) and
+/// `*_release`. Values are summed as given, so they must be finite: clamping
+/// to the all-`f64` domain would turn NaN and ±inf into ±`f64::MAX`.
+fn unbounded_sum<S: ScalarStream>(src: &mut S) -> Result<SumState, MechError> {
+    let mut state = SumState::new(BoundedF64::new(f64::MIN, f64::MAX));
+    while let Some(v) = src.next_val() {
+        let v = v?;
+        if !v.is_finite() {
+            return Err(MechError::InvalidParam("values must be finite"));
+        }
+        state.push(v);
+    }
+    Ok(state)
 }
 
 /// DP sum (Laplace) with L1-sensitivity `Δ1`.
@@ -22,12 +34,28 @@ impl DpSum {
         if epsilon <= 0.0 {
             return Err(MechError::InvalidParam("epsilon must be > 0"));
         }
-        let b = l1_sensitivity / epsilon;
 
-        let mut sum = 0.0;
-        while let Some(res) = src.next_val() {
-            sum += res.map_err(MechError::Upstream)?;
+        let state = unbounded_sum(&mut src)?;
+        Self::laplace_release(state, l1_sensitivity, epsilon, seed)
+    }
+
+    /// Releases the sum of a (possibly resumed) [`SumState`] with Laplace noise.
+    ///
+    /// The state is consumed: this is the single point where noise is added.
+    pub fn laplace_release(
+        state: SumState,
+        l1_sensitivity: f64,
+        epsilon: f64,
+        seed: Option<u64>,
+    ) -> Result<f64, MechError> {
+        Self::release(state.result().0, l1_sensitivity, epsilon, seed)
+    }
+
+    fn release(sum: f64, l1_sensitivity: f64, epsilon: f64, seed: Option<u64>) -> Result<f64, MechError> {
+        if epsilon <= 0.0 {
+            return Err(MechError::InvalidParam("epsilon must be > 0"));
         }
+        let b = l1_sensitivity / epsilon;
 
         let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
         let noise = sample_laplace(&mut rng, b);
@@ -51,11 +79,36 @@ impl DpMean {
             return Err(MechError::InvalidParam("invalid epsilon/delta/n"));
         }
 
-        let mut sum = 0.0;
-        let mut n = 0usize;
-        while let Some(res) = src.next_val() {
-            sum += res.map_err(MechError::Upstream)?;
-            n += 1;
+        let state = unbounded_sum(&mut src)?;
+        Self::gaussian_release(state, l2_sensitivity_per_record, epsilon, delta, bounded_n, seed)
+    }
+
+    /// Releases the mean of a (possibly resumed) [`SumState`] with Gaussian noise.
+    ///
+    /// The state is consumed: this is the single point where noise is added.
+    pub fn gaussian_release(
+        state: SumState,
+        l2_sensitivity_per_record: f64,
+        epsilon: f64,
+        delta: f64,
+        bounded_n: usize,
+        seed: Option<u64>,
+    ) -> Result<f64, MechError> {
+        let (sum, n) = state.result();
+        Self::release(sum, n, l2_sensitivity_per_record, epsilon, delta, bounded_n, seed)
+    }
+
+    fn release(
+        sum: f64,
+        n: usize,
+        l2_sensitivity_per_record: f64,
+        epsilon: f64,
+        delta: f64,
+        bounded_n: usize,
+        seed: Option<u64>,
+    ) -> Result<f64, MechError> {
+        if epsilon <= 0.0 || !(0.0..1.0).contains(&delta) || bounded_n == 0 {
+            return Err(MechError::InvalidParam("invalid epsilon/delta/n"));
         }
         if n == 0 {
             return Err(MechError::NotEnoughData("empty stream"));
@@ -72,3 +125,139 @@ impl DpMean {
         Ok(mean + noise)
     }
 }
+
+/// Split of the privacy budget of [`DpVariance`] over its three noisy statistics.
+///
+/// The release is `ε_count + ε_sum + ε_sum_sq`-DP in total (sequential composition).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VarianceBudget {
+    pub count: f64,
+    pub sum: f64,
+    pub sum_sq: f64,
+}
+
+impl VarianceBudget {
+    pub fn new(count: f64, sum: f64, sum_sq: f64) -> Self { Self { count, sum, sum_sq } }
+
+    /// `epsilon` split into equal thirds.
+    pub fn even(epsilon: f64) -> Self { Self::new(epsilon / 3.0, epsilon / 3.0, epsilon / 3.0) }
+
+    /// Total privacy cost ε of the release.
+    pub fn total(&self) -> f64 { self.count + self.sum + self.sum_sq }
+
+    fn check(&self) -> Result<(), MechError> {
+        if [self.count, self.sum, self.sum_sq].iter().any(|&e| !(e > 0.0 && e.is_finite())) {
+            return Err(MechError::InvalidParam("every part of the variance budget must be finite and > 0"));
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`DpVariance`]; every field is post-processing of the same noisy statistics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DpSpread {
+    /// Noisy record count, at least 1.
+    pub count: f64,
+    /// Mean, within the domain.
+    pub mean: f64,
+    /// Population variance, in `[0, ((max - min) / 2)²]`.
+    pub variance: f64,
+    pub std_dev: f64,
+}
+
+/// DP variance and standard deviation (Laplace) over a clamped domain.
+///
+/// Values are clamped to `dom` and centered at its midpoint `c`, so every
+/// centered value lies in `[-r, r]` with `r = (max - min) / 2`. Three statistics
+/// get Laplace noise, with L1 sensitivities under adding or removing one record:
+///
+/// | statistic                  | sensitivity | budget          |
+/// |----------------------------|-------------|-----------------|
+/// | count `n`                  | `1`         | `budget.count`  |
+/// | sum `Σ(x - c)`             | `r`         | `budget.sum`    |
+/// | sum of squares `Σ(x - c)²` | `r²`        | `budget.sum_sq` |
+///
+/// Centering keeps the sum-of-squares sensitivity at `r²` instead of
+/// `max(|min|, |max|)²`. The noisy values are then post-processed: the count is
+/// floored at 1, the centered mean clamped to `[-r, r]` and the variance
+/// `Σ(x - c)²/n - mean²` clamped to `[0, r²]`.
+///
+/// Total privacy cost: `budget.total()`-DP.
+pub struct DpVariance;
+
+impl DpVariance {
+    pub fn laplace<S: ScalarStream>(
+        mut src: S,
+        dom: BoundedF64,
+        budget: VarianceBudget,
+        seed: Option<u64>,
+    ) -> Result<DpSpread, MechError> {
+        budget.check()?;
+
+        let mut state = VarianceState::new(dom);
+        state.feed(&mut src, usize::MAX)?;
+        Self::laplace_release(state, budget, seed)
+    }
+
+    /// Releases the spread of a (possibly resumed or merged) [`VarianceState`].
+    ///
+    /// The state is consumed: this is the single point where noise is added.
+    pub fn laplace_release(state: VarianceState, budget: VarianceBudget, seed: Option<u64>) -> Result<DpSpread, MechError> {
+        budget.check()?;
+
+        let (c, r) = (state.center(), state.radius());
+        let (sum, n) = state.sum.result();
+        let (sum_sq, _) = state.sum_sq.result();
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        let noisy_n = n as f64 + sample_laplace(&mut rng, 1.0 / budget.count);
+        let noisy_sum = sum + sample_laplace(&mut rng, r / budget.sum);
+        let noisy_sum_sq = sum_sq + sample_laplace(&mut rng, r * r / budget.sum_sq);
+
+        let count = noisy_n.max(1.0);
+        let mean = (noisy_sum / count).clamp(-r, r);
+        let variance = (noisy_sum_sq / count - mean * mean).clamp(0.0, r * r);
+        Ok(DpSpread { count, mean: mean + c, variance, std_dev: variance.sqrt() })
+    }
+}
+
+/// Resumable state of [`DpVariance`]: exact sums of the clamped values
+/// centered at the midpoint of `dom`, and of their squares.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VarianceState {
+    pub dom: BoundedF64,
+    /// `Σ(x - c)` and the count.
+    pub sum: SumState,
+    /// `Σ(x - c)²`.
+    pub sum_sq: SumState,
+}
+
+impl VarianceState {
+    pub fn new(dom: BoundedF64) -> Self {
+        let r = (dom.max - dom.min) / 2.0;
+        Self {
+            dom,
+            sum: SumState::new(BoundedF64::new(-r, r)),
+            sum_sq: SumState::new(BoundedF64::new(0.0, r * r)),
+        }
+    }
+
+    /// Midpoint `c` of the domain.
+    pub fn center(&self) -> f64 { (self.dom.min + self.dom.max) / 2.0 }
+
+    /// Half-width `r` of the domain.
+    pub fn radius(&self) -> f64 { (self.dom.max - self.dom.min) / 2.0 }
+}
+
+impl Aggregate for VarianceState {
+    fn push(&mut self, v: f64) {
+        let x = self.dom.clamp(v) - self.center();
+        self.sum.push(x);
+        self.sum_sq.push(x * x);
+    }
+
+    fn merge(&mut self, other: Self) {
+        assert_eq!(self.dom, other.dom, "cannot merge variances over different domains");
+        self.sum.merge(other.sum);
+        self.sum_sq.merge(other.sum_sq);
+    }
+}
@@ -1,3 +1,4 @@
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// Clamps each value into [lo, hi].
@@ -12,7 +13,7 @@ impl<S> Clipper<S> {
 }
 
 impl<S: ScalarStream> ScalarStream for Clipper<S> {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         self.src.next_val().map(|r| r.map(|v| v.clamp(self.lo, self.hi)))
     }
 }
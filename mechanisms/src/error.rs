@@ -1,9 +1,10 @@
+use data_layer::error::DataError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum MechError {
     #[error("upstream stream error: {0}")]
-    Upstream(#[from] Box<dyn std::error::Error + Send + Sync>),
+    Upstream(#[from] DataError),
 
     #[error("invalid parameter: {0}")]
     InvalidParam(&'static str),
@@ -0,0 +1,171 @@
+//! Noisy histogram release on top of [`histogram_stream`](data_layer::stream_queries::histogram_stream).
+//!
+//! ```
+//! use data_layer::prelude::*;
+//! use mechanisms::prelude::*;
+//!
+//! let src = VecStream::from(vec![1.0, 2.5, 2.7, 8.0]);
+//! let hist = DpHistogram::laplace(1.0).round(true)
+//!     .release(src, BoundedF64::new(0.0, 10.0), 5, Some(7))
+//!     .unwrap();
+//! assert_eq!(hist.edges.len(), 6);
+//! assert_eq!(hist.guarantee.epsilon, 1.0);
+//! ```
+//!
+//! Adding or removing one record changes one bucket count by 1, so the L1 and
+//! L2 sensitivities of the count vector are both
+//! [`l1_sens_hist_count`](data_layer::stream_queries::l1_sens_hist_count)` = 1`.
+
+use data_layer::stream::ScalarStream;
+use data_layer::stream_queries::{l1_sens_hist_count, Aggregate, BoundedF64, HistogramState};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::calibrate::gaussian_sigma;
+use crate::error::MechError;
+use crate::noise::sample_laplace;
+
+/// Noise added to every bucket count.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistogramNoise {
+    /// Laplace(Δ1/ε); ε-DP.
+    Laplace { epsilon: f64 },
+    /// Two-sided geometric noise with `P(k) ∝ exp(-ε|k|/Δ1)`: integer counts, ε-DP.
+    DiscreteLaplace { epsilon: f64 },
+    /// Gaussian with σ from [`gaussian_sigma`]; (ε, δ)-DP. The classic bound
+    /// behind that σ only holds for ε < 1, so larger ε are rejected.
+    Gaussian { epsilon: f64, delta: f64 },
+}
+
+/// The privacy guarantee a release spent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Guarantee {
+    /// Name of the mechanism, e.g. `"laplace"`.
+    pub mechanism: &'static str,
+    pub epsilon: f64,
+    /// `0` for pure ε-DP.
+    pub delta: f64,
+    /// Sensitivity the noise was calibrated to (L1, or L2 for Gaussian noise).
+    pub sensitivity: f64,
+    /// Noise scale: `b` for Laplace, `σ` for Gaussian, `Δ1/ε` for discrete Laplace.
+    pub scale: f64,
+}
+
+/// Bucket edges and noisy counts of a released histogram.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoisyHistogram {
+    /// `bins + 1` ascending edges; bucket `i` is `[edges[i], edges[i + 1])`, the last one closed.
+    pub edges: Vec<f64>,
+    /// One noisy count (or probability, if normalized) per bucket.
+    pub counts: Vec<f64>,
+    pub guarantee: Guarantee,
+}
+
+/// DP histogram release; see the [module docs](self).
+///
+/// Post-processing options (they do not change the guarantee):
+/// - [`round`](Self::round): round counts to the nearest non-negative integer.
+/// - [`normalize`](Self::normalize): floor counts at zero and scale them to sum
+///   to 1 (uniform if every count is zero).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DpHistogram {
+    noise: HistogramNoise,
+    round: bool,
+    normalize: bool,
+}
+
+impl DpHistogram {
+    pub fn new(noise: HistogramNoise) -> Self { Self { noise, round: false, normalize: false } }
+
+    pub fn laplace(epsilon: f64) -> Self { Self::new(HistogramNoise::Laplace { epsilon }) }
+
+    pub fn discrete_laplace(epsilon: f64) -> Self { Self::new(HistogramNoise::DiscreteLaplace { epsilon }) }
+
+    pub fn gaussian(epsilon: f64, delta: f64) -> Self { Self::new(HistogramNoise::Gaussian { epsilon, delta }) }
+
+    pub fn round(mut self, round: bool) -> Self { self.round = round; self }
+
+    pub fn normalize(mut self, normalize: bool) -> Self { self.normalize = normalize; self }
+
+    /// The guarantee a release with these settings spends.
+    pub fn guarantee(&self) -> Result<Guarantee, MechError> {
+        let sensitivity = l1_sens_hist_count();
+        match self.noise {
+            HistogramNoise::Laplace { epsilon } | HistogramNoise::DiscreteLaplace { epsilon } => {
+                if epsilon <= 0.0 {
+                    return Err(MechError::InvalidParam("epsilon must be > 0"));
+                }
+                let mechanism = match self.noise {
+                    HistogramNoise::Laplace { .. } => "laplace",
+                    _ => "discrete_laplace",
+                };
+                Ok(Guarantee { mechanism, epsilon, delta: 0.0, sensitivity, scale: sensitivity / epsilon })
+            }
+            HistogramNoise::Gaussian { epsilon, delta } => {
+                if epsilon <= 0.0 || !(delta > 0.0 && delta < 1.0) {
+                    return Err(MechError::InvalidParam("invalid epsilon/delta"));
+                }
+                if epsilon >= 1.0 {
+                    return Err(MechError::InvalidParam("gaussian noise requires epsilon < 1"));
+                }
+                let scale = gaussian_sigma(sensitivity, epsilon, delta);
+                Ok(Guarantee { mechanism: "gaussian", epsilon, delta, sensitivity, scale })
+            }
+        }
+    }
+
+    /// Builds the histogram of `src` with `bins` buckets over `dom` and releases it.
+    pub fn release<S: ScalarStream>(
+        &self,
+        mut src: S,
+        dom: BoundedF64,
+        bins: usize,
+        seed: Option<u64>,
+    ) -> Result<NoisyHistogram, MechError> {
+        let mut state = HistogramState::new(dom, bins);
+        state.feed(&mut src, usize::MAX)?;
+        self.release_state(state, seed)
+    }
+
+    /// Releases a (possibly resumed or merged) [`HistogramState`].
+    ///
+    /// The state is consumed: this is the single point where noise is added.
+    pub fn release_state(&self, state: HistogramState, seed: Option<u64>) -> Result<NoisyHistogram, MechError> {
+        let guarantee = self.guarantee()?;
+        let buckets = state.result();
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+
+        let mut counts: Vec<f64> = buckets.iter()
+            .map(|&(_, _, c)| c as f64 + match self.noise {
+                HistogramNoise::Laplace { .. } => sample_laplace(&mut rng, guarantee.scale),
+                HistogramNoise::DiscreteLaplace { epsilon } => sample_discrete_laplace(&mut rng, epsilon / guarantee.sensitivity),
+                HistogramNoise::Gaussian { .. } => Normal::new(0.0, guarantee.scale).unwrap().sample(&mut rng),
+            })
+            .collect();
+
+        if self.round {
+            counts.iter_mut().for_each(|c| *c = c.round().max(0.0));
+        }
+        if self.normalize {
+            counts.iter_mut().for_each(|c| *c = c.max(0.0));
+            let total: f64 = counts.iter().sum();
+            let n = counts.len() as f64;
+            counts.iter_mut().for_each(|c| *c = if total > 0.0 { *c / total } else { 1.0 / n });
+        }
+
+        let mut edges: Vec<f64> = buckets.iter().map(|b| b.0).collect();
+        edges.push(buckets[buckets.len() - 1].1);
+        Ok(NoisyHistogram { edges, counts, guarantee })
+    }
+}
+
+/// Two-sided geometric noise `P(k) ∝ exp(-ε|k|)`, as the difference of two
+/// geometric variables with success probability `1 - exp(-ε)`.
+fn sample_discrete_laplace<R: Rng>(rng: &mut R, epsilon: f64) -> f64 {
+    let ln_q = -epsilon;
+    let mut geometric = || {
+        let u: f64 = 1.0 - rng.gen::<f64>(); // (0, 1]
+        (u.ln() / ln_q).floor()
+    };
+    geometric() - geometric()
+}
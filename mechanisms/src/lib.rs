@@ -5,6 +5,10 @@ pub mod calibrate;
 pub mod clip;
 pub mod noise;
 pub mod aggregate;
+pub mod quantile;
+pub mod histogram;
+pub mod sparse;
+pub mod tree;
 
 /// Re-exports commonly used pieces.
 pub mod prelude {
@@ -12,5 +16,9 @@ pub mod prelude {
     pub use crate::calibrate::{laplace_b, gaussian_sigma};
     pub use crate::clip::Clipper;
     pub use crate::noise::{LaplaceNoise, GaussianNoise};
-    pub use crate::aggregate::{DpMean, DpSum};
+    pub use crate::aggregate::{DpMean, DpSpread, DpSum, DpVariance, VarianceBudget, VarianceState};
+    pub use crate::quantile::DpQuantile;
+    pub use crate::histogram::{DpHistogram, Guarantee, HistogramNoise, NoisyHistogram};
+    pub use crate::sparse::{DpSparseHistogram, SparseHistogram};
+    pub use crate::tree::{DpTreeHistogram, TreeHistogram};
 }
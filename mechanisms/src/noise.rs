@@ -1,10 +1,10 @@
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use rand_distr::{Distribution, Normal};
-use std::error::Error;
 
 /// Sample a Laplace(0, b) random value using the inverse CDF method.
-fn sample_laplace<R: Rng>(rng: &mut R, b: f64) -> f64 {
+pub(crate) fn sample_laplace<R: Rng>(rng: &mut R, b: f64) -> f64 {
     let u: f64 = rng.gen::<f64>() - 0.5;
     -b * u.signum() * (1.0 - 2.0 * u.abs()).ln()
 }
@@ -27,8 +27,8 @@ impl<S> LaplaceNoise<S> {
 }
 
 impl<S: ScalarStream> ScalarStream for LaplaceNoise<S> {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>> {
-        let Some(r) = self.src.next_val() else { return None; };
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        let r = self.src.next_val()?;
         match r {
             Ok(v) => {
                 let noise = sample_laplace(&mut self.rng, self.b);
@@ -57,8 +57,8 @@ impl<S> GaussianNoise<S> {
 }
 
 impl<S: ScalarStream> ScalarStream for GaussianNoise<S> {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn Error + Send + Sync>>> {
-        let Some(r) = self.src.next_val() else { return None; };
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        let r = self.src.next_val()?;
         match r {
             Ok(v) => {
                 let dist = Normal::new(0.0, self.sigma).unwrap();
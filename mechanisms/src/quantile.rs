@@ -0,0 +1,188 @@
+//! DP quantiles (median, percentiles) over a bounded domain.
+//!
+//! Two estimators:
+//! - [`DpQuantile::exponential`] / [`DpQuantile::exponential_many`]: the
+//!   exponential mechanism on the sorted data (Smith 2011). Accurate, but holds
+//!   all values in memory.
+//! - [`DpQuantile::histogram`] / [`DpQuantile::histogram_release`]: quantiles
+//!   read off a Laplace-noised histogram. Memory is bounded by the number of
+//!   bins; accuracy is limited by the bin width.
+//!
+//! Values are clamped to the domain. Neighbouring datasets differ by adding or
+//! removing one record.
+
+use data_layer::stream::ScalarStream;
+use data_layer::stream_queries::{Aggregate, BoundedF64, HistogramState};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::error::MechError;
+use crate::noise::sample_laplace;
+
+/// DP quantile estimation; see the [module docs](self).
+pub struct DpQuantile;
+
+impl DpQuantile {
+    /// One `q`-quantile (`0.5` = median) with the exponential mechanism, ε-DP.
+    ///
+    /// The output is drawn from the domain with density proportional to
+    /// `exp(-ε/2 · |rank error|)`, where the rank error of a point is how many
+    /// records it is away from rank `q·n`.
+    pub fn exponential<S: ScalarStream>(
+        src: S,
+        dom: BoundedF64,
+        q: f64,
+        epsilon: f64,
+        seed: Option<u64>,
+    ) -> Result<f64, MechError> {
+        Ok(Self::exponential_many(src, dom, &[q], epsilon, seed)?[0])
+    }
+
+    /// The median with the exponential mechanism, ε-DP.
+    pub fn median<S: ScalarStream>(src: S, dom: BoundedF64, epsilon: f64, seed: Option<u64>) -> Result<f64, MechError> {
+        Self::exponential(src, dom, 0.5, epsilon, seed)
+    }
+
+    /// Several quantiles jointly, ε-DP in total; the results are non-decreasing.
+    ///
+    /// The middle quantile is estimated first and splits the data and domain in
+    /// two; the remaining quantiles are estimated recursively on their side
+    /// (Kaplan, Schnapp & Stemmer 2022). Each record is used once per level of
+    /// the recursion, so the budget is split over `⌊log2 m⌋ + 1` levels for `m`
+    /// quantiles instead of over `m` separate releases.
+    ///
+    /// # Arguments
+    /// * `qs` – quantiles in `[0, 1]`, in ascending order.
+    pub fn exponential_many<S: ScalarStream>(
+        mut src: S,
+        dom: BoundedF64,
+        qs: &[f64],
+        epsilon: f64,
+        seed: Option<u64>,
+    ) -> Result<Vec<f64>, MechError> {
+        check_params(qs, epsilon)?;
+        let mut xs = Vec::new();
+        while let Some(res) = src.next_val() {
+            xs.push(res.map_err(MechError::Upstream)?.min(dom.max).max(dom.min));
+        }
+        xs.sort_by(f64::total_cmp);
+
+        let levels = (usize::BITS - qs.len().leading_zeros()) as f64;
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        let mut out = Vec::with_capacity(qs.len());
+        joint(&xs, dom.min, dom.max, qs, epsilon / levels, &mut rng, &mut out);
+        Ok(out)
+    }
+
+    /// Quantiles from a noisy `bins`-bucket histogram, ε-DP in total.
+    pub fn histogram<S: ScalarStream>(
+        mut src: S,
+        dom: BoundedF64,
+        bins: usize,
+        qs: &[f64],
+        epsilon: f64,
+        seed: Option<u64>,
+    ) -> Result<Vec<f64>, MechError> {
+        let mut state = HistogramState::new(dom, bins);
+        state.feed(&mut src, usize::MAX)?;
+        Self::histogram_release(state, qs, epsilon, seed)
+    }
+
+    /// Releases quantiles of a (possibly resumed or merged) [`HistogramState`].
+    ///
+    /// Every count gets Laplace(1/ε) noise and is floored at zero; each quantile
+    /// is then interpolated linearly inside the bucket where the noisy cumulative
+    /// count reaches `q` of the noisy total. All quantiles are post-processing of
+    /// one noisy histogram, so they cost ε together. The state is consumed: this
+    /// is the single point where noise is added.
+    ///
+    /// # Arguments
+    /// * `qs` – quantiles in `[0, 1]`, in ascending order.
+    pub fn histogram_release(
+        state: HistogramState,
+        qs: &[f64],
+        epsilon: f64,
+        seed: Option<u64>,
+    ) -> Result<Vec<f64>, MechError> {
+        check_params(qs, epsilon)?;
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        let buckets: Vec<(f64, f64, f64)> = state.result().into_iter()
+            .map(|(lo, hi, c)| (lo, hi, (c as f64 + sample_laplace(&mut rng, 1.0 / epsilon)).max(0.0)))
+            .collect();
+        let total: f64 = buckets.iter().map(|b| b.2).sum();
+        let (min, max) = (buckets[0].0, buckets[buckets.len() - 1].1);
+
+        Ok(qs.iter().map(|&q| {
+            if total <= 0.0 {
+                return min + q * (max - min);
+            }
+            let target = q * total;
+            let mut cum = 0.0;
+            for &(lo, hi, c) in &buckets {
+                if c > 0.0 && cum + c >= target {
+                    return lo + (hi - lo) * ((target - cum) / c).clamp(0.0, 1.0);
+                }
+                cum += c;
+            }
+            max
+        }).collect())
+    }
+}
+
+fn check_params(qs: &[f64], epsilon: f64) -> Result<(), MechError> {
+    if epsilon <= 0.0 {
+        return Err(MechError::InvalidParam("epsilon must be > 0"));
+    }
+    if qs.is_empty() || qs.iter().any(|q| !(0.0..=1.0).contains(q)) {
+        return Err(MechError::InvalidParam("quantiles must be in [0, 1]"));
+    }
+    if qs.windows(2).any(|w| w[0] > w[1]) {
+        return Err(MechError::InvalidParam("quantiles must be in ascending order"));
+    }
+    Ok(())
+}
+
+/// Recursive joint estimation; appends the estimates for `qs` to `out` in order.
+fn joint(xs: &[f64], lo: f64, hi: f64, qs: &[f64], epsilon: f64, rng: &mut StdRng, out: &mut Vec<f64>) {
+    if qs.is_empty() {
+        return;
+    }
+    let mid = qs.len() / 2;
+    let qm = qs[mid];
+    let v = exponential_quantile(xs, lo, hi, qm, epsilon, rng);
+    let k = xs.partition_point(|&x| x <= v);
+
+    // Quantiles relative to each side (q ≤ qm on the left, q ≥ qm on the right).
+    let left: Vec<f64> = qs[..mid].iter().map(|&q| if qm > 0.0 { q / qm } else { 0.0 }).collect();
+    let right: Vec<f64> = qs[mid + 1..].iter().map(|&q| if qm < 1.0 { (q - qm) / (1.0 - qm) } else { 1.0 }).collect();
+    joint(&xs[..k], lo, v, &left, epsilon, rng, out);
+    out.push(v);
+    joint(&xs[k..], v, hi, &right, epsilon, rng, out);
+}
+
+/// Exponential mechanism for the `q`-quantile of sorted `xs` in `[lo, hi]`.
+///
+/// The gaps between consecutive points (and the domain ends) are the
+/// candidates; gap `i` has `i` points below it, utility `-|i - q·n|` (sensitivity 1)
+/// and weight `width · exp(ε/2 · utility)`. The output is uniform inside the chosen gap.
+fn exponential_quantile(xs: &[f64], lo: f64, hi: f64, q: f64, epsilon: f64, rng: &mut StdRng) -> f64 {
+    let n = xs.len();
+    let target = q * n as f64;
+    let gap = |i: usize| (if i == 0 { lo } else { xs[i - 1] }, if i == n { hi } else { xs[i] });
+    let log_w: Vec<f64> = (0..=n)
+        .map(|i| {
+            let (l, r) = gap(i);
+            (r - l).ln() - epsilon / 2.0 * (i as f64 - target).abs()
+        })
+        .collect();
+    let max = log_w.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return lo; // empty domain
+    }
+    let weights: Vec<f64> = log_w.iter().map(|w| (w - max).exp()).collect();
+    let mut u = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    let i = weights.iter().position(|&w| { u -= w; u < 0.0 }).unwrap_or_else(|| {
+        weights.iter().rposition(|&w| w > 0.0).unwrap_or(0)
+    });
+    let (l, r) = gap(i);
+    l + rng.gen::<f64>() * (r - l)
+}
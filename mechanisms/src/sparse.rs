@@ -0,0 +1,181 @@
+//! DP histograms over categorical keys that are not known in advance.
+//!
+//! Releasing a noisy count for every observed key (URL, product id, ...) would
+//! reveal that the key occurs at all. [`DpSparseHistogram`] instead releases only
+//! keys whose noisy count clears a threshold calibrated from (ε, δ), the
+//! Laplace partition-selection mechanism (Korolova et al. 2009; Wilson et al. 2020):
+//!
+//! 1. Each privacy unit contributes at most once to each key and to at most
+//!    `k` distinct keys ([`max_keys`](DpSparseHistogram::max_keys)).
+//! 2. Every observed key gets Laplace(k/ε) noise.
+//! 3. Keys with a noisy count below `τ = 1 + (k/ε)·ln(k / (2δ))` are suppressed.
+//!
+//! A key only one unit could have produced survives with probability at most
+//! δ/k, so the release is (ε, δ)-DP at the unit level.
+//!
+//! ```no_run
+//! use data_layer::prelude::*;
+//! use mechanisms::prelude::*;
+//!
+//! let schema = RecordSchema::new().str("user").str("url");
+//! let src = NdjsonRecordStream::from_path("visits.ndjson", schema).unwrap();
+//! let hist = DpSparseHistogram::new(1.0, 1e-6).max_keys(5)
+//!     .release(src, "user", "url", Selection::FirstK, None)
+//!     .unwrap();
+//! for (url, count) in &hist.counts {
+//!     println!("{url}: {count:.0}");
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use data_layer::contribution::Selection;
+use data_layer::error::{DataError, Location};
+use data_layer::record::{Field, RecordStream};
+use rand::{rngs::StdRng, seq::index, SeedableRng};
+
+use crate::error::MechError;
+use crate::histogram::Guarantee;
+use crate::noise::sample_laplace;
+
+/// Released keys with their noisy counts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseHistogram {
+    /// Keys that cleared the threshold, sorted by key.
+    pub counts: Vec<(String, f64)>,
+    /// The suppression threshold `τ`.
+    pub threshold: f64,
+    pub guarantee: Guarantee,
+}
+
+/// Thresholded Laplace histogram over categorical keys; see the [module docs](self).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DpSparseHistogram {
+    epsilon: f64,
+    delta: f64,
+    max_keys: usize,
+}
+
+impl DpSparseHistogram {
+    /// (ε, δ)-DP with one key per unit; raise the bound with [`max_keys`](Self::max_keys).
+    pub fn new(epsilon: f64, delta: f64) -> Self { Self { epsilon, delta, max_keys: 1 } }
+
+    /// Number of distinct keys one privacy unit may contribute to (`k`).
+    pub fn max_keys(mut self, max_keys: usize) -> Self { self.max_keys = max_keys; self }
+
+    /// The suppression threshold `τ = 1 + (k/ε)·ln(k / (2δ))`.
+    pub fn threshold(&self) -> Result<f64, MechError> {
+        self.check()?;
+        let k = self.max_keys as f64;
+        Ok(1.0 + k / self.epsilon * (k / (2.0 * self.delta)).ln())
+    }
+
+    /// The guarantee a release with these settings spends.
+    pub fn guarantee(&self) -> Result<Guarantee, MechError> {
+        self.check()?;
+        let sensitivity = self.max_keys as f64;
+        Ok(Guarantee {
+            mechanism: "laplace_threshold",
+            epsilon: self.epsilon,
+            delta: self.delta,
+            sensitivity,
+            scale: sensitivity / self.epsilon,
+        })
+    }
+
+    /// Counts, per key, the privacy units that contributed to it and releases
+    /// the keys whose noisy count reaches the [`threshold`](Self::threshold).
+    ///
+    /// Keys are the text form of the `key` field; records with a null key are
+    /// skipped. A null unit is a [`DataError::MissingKey`], since such a record
+    /// cannot be bounded.
+    ///
+    /// # Arguments
+    /// * `unit` – column holding the privacy unit (user id, device id, ...).
+    /// * `key` – column holding the categorical key.
+    /// * `selection` – which keys are kept for a unit with more than `k`:
+    ///   its first `k` distinct keys, or `k` of them uniformly at random.
+    ///   Memory is proportional to the distinct (unit, key) pairs kept, or to
+    ///   all distinct pairs for [`Selection::Reservoir`].
+    pub fn release<R: RecordStream>(
+        &self,
+        mut src: R,
+        unit: &str,
+        key: &str,
+        selection: Selection,
+        seed: Option<u64>,
+    ) -> Result<SparseHistogram, MechError> {
+        let guarantee = self.guarantee()?;
+        let threshold = self.threshold()?;
+        let schema = src.schema();
+        let unit_idx = schema.index_of(unit)
+            .ok_or_else(|| DataError::missing_key(Location::default().with_column(unit), unit))?;
+        let key_idx = schema.index_of(key)
+            .ok_or_else(|| DataError::missing_key(Location::default().with_column(key), key))?;
+
+        // Distinct keys per unit, in first-seen order.
+        let mut units: HashMap<String, (HashSet<String>, Vec<String>)> = HashMap::new();
+        let cap = match selection {
+            Selection::FirstK => self.max_keys,
+            Selection::Reservoir { .. } => usize::MAX,
+        };
+        while let Some(rec) = src.next_record() {
+            let rec = rec?;
+            let u = match &rec.values()[unit_idx] {
+                Field::Null => {
+                    let loc = rec.location().clone().with_column(unit);
+                    return Err(DataError::missing_key(loc, format!("{} (null)", unit)).into());
+                }
+                f => f.to_string(),
+            };
+            let k = match &rec.values()[key_idx] {
+                Field::Null => continue,
+                f => f.to_string(),
+            };
+            let (seen, keys) = units.entry(u).or_default();
+            if keys.len() < cap && seen.insert(k.clone()) {
+                keys.push(k);
+            }
+        }
+
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        match selection {
+            Selection::FirstK => {
+                for (_, keys) in units.into_values() {
+                    keys.into_iter().for_each(|k| *counts.entry(k).or_default() += 1);
+                }
+            }
+            Selection::Reservoir { seed: sample_seed } => {
+                // Units in a fixed order, so that a seeded sample is reproducible.
+                let mut units: Vec<(String, Vec<String>)> = units.into_iter().map(|(u, (_, keys))| (u, keys)).collect();
+                units.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                let mut rng = sample_seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+                for (_, mut keys) in units {
+                    if keys.len() > self.max_keys {
+                        let picked = index::sample(&mut rng, keys.len(), self.max_keys);
+                        let sampled: Vec<String> = picked.into_iter().map(|i| std::mem::take(&mut keys[i])).collect();
+                        keys = sampled;
+                    }
+                    keys.into_iter().for_each(|k| *counts.entry(k).or_default() += 1);
+                }
+            }
+        }
+
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        let counts = counts.into_iter()
+            .map(|(k, c)| (k, c as f64 + sample_laplace(&mut rng, guarantee.scale)))
+            .filter(|&(_, c)| c >= threshold)
+            .collect();
+        Ok(SparseHistogram { counts, threshold, guarantee })
+    }
+
+    fn check(&self) -> Result<(), MechError> {
+        if self.epsilon <= 0.0 || !(self.delta > 0.0 && self.delta < 1.0) {
+            return Err(MechError::InvalidParam("invalid epsilon/delta"));
+        }
+        if self.max_keys == 0 {
+            return Err(MechError::InvalidParam("max_keys must be at least 1"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! Hierarchical (dyadic) histograms for answering many range queries from one release.
+//!
+//! A fresh noisy count per range query spends budget on every query. A tree
+//! histogram is released once and answers any number of range and CDF queries
+//! as post-processing:
+//!
+//! 1. The domain is split into `2^depth` equal-width leaves; every inner node
+//!    counts the records of its two children, up to the root.
+//! 2. A record is counted once per level, so the `depth + 1` levels together
+//!    have L1 sensitivity `depth + 1`, and every node gets Laplace((depth + 1)/ε).
+//! 3. The noisy counts are made consistent (every node equals the sum of its
+//!    children) by least squares, in one bottom-up and one top-down pass
+//!    (Hay, Rastogi, Miklau & Suciu 2010). This only lowers the variance.
+//!
+//! A range is the union of at most `2·depth` nodes, so its error grows like
+//! `depth^1.5 / ε`, polylogarithmic in the number of leaves, instead of linearly
+//! in the number of leaves it covers.
+//!
+//! ```
+//! use data_layer::prelude::*;
+//! use mechanisms::prelude::*;
+//!
+//! let src = VecStream::from((0..1000).map(f64::from).collect::<Vec<_>>());
+//! let tree = DpTreeHistogram::new(1.0).release(src, BoundedF64::new(0.0, 1000.0), 8, Some(3)).unwrap();
+//! let between = tree.range_count(300.0, 470.0);
+//! let below_half = tree.cdf(500.0);
+//! assert!((between - 170.0).abs() < 100.0 && (0.0..=1.0).contains(&below_half));
+//! ```
+
+use data_layer::stream::ScalarStream;
+use data_layer::stream_queries::{Aggregate, BoundedF64, HistogramState};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::error::MechError;
+use crate::histogram::Guarantee;
+use crate::noise::sample_laplace;
+
+/// Deepest supported tree (`2^24` leaves).
+const MAX_DEPTH: usize = 24;
+
+/// Consistent noisy counts of every node of a dyadic tree over a domain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeHistogram {
+    dom: BoundedF64,
+    /// `levels[l]` holds the `2^l` nodes of level `l`; `levels[0]` is the root,
+    /// the last level the leaves.
+    levels: Vec<Vec<f64>>,
+    guarantee: Guarantee,
+}
+
+impl TreeHistogram {
+    pub fn dom(&self) -> BoundedF64 { self.dom }
+
+    /// Number of levels below the root (`2^depth` leaves).
+    pub fn depth(&self) -> usize { self.levels.len() - 1 }
+
+    /// Node counts per level, root first.
+    pub fn levels(&self) -> &[Vec<f64>] { &self.levels }
+
+    /// Counts of the `2^depth` leaves, in domain order.
+    pub fn leaves(&self) -> &[f64] { &self.levels[self.depth()] }
+
+    /// `2^depth + 1` ascending leaf edges.
+    pub fn edges(&self) -> Vec<f64> {
+        let n = self.leaves().len();
+        let width = (self.dom.max - self.dom.min) / n as f64;
+        (0..=n).map(|i| if i == n { self.dom.max } else { self.dom.min + i as f64 * width }).collect()
+    }
+
+    /// Noisy total (the root).
+    pub fn total(&self) -> f64 { self.levels[0][0] }
+
+    pub fn guarantee(&self) -> &Guarantee { &self.guarantee }
+
+    /// Estimated number of records in `[lo, hi]`, clamped to the domain.
+    ///
+    /// Leaves partially covered by the range contribute in proportion to the
+    /// covered width. `0` for an empty range.
+    pub fn range_count(&self, lo: f64, hi: f64) -> f64 {
+        if lo >= hi {
+            return 0.0;
+        }
+        self.count_below(hi) - self.count_below(lo)
+    }
+
+    /// Estimated fraction of records `≤ x`, in `[0, 1]`.
+    ///
+    /// Noisy counts can be negative, so the estimate is not guaranteed to be
+    /// monotone in `x`. `0` below the domain and `1` above it; uniform over the
+    /// domain if the noisy total is not positive.
+    pub fn cdf(&self, x: f64) -> f64 {
+        let total = self.total();
+        if total <= 0.0 {
+            let x = x.min(self.dom.max).max(self.dom.min);
+            return (x - self.dom.min) / (self.dom.max - self.dom.min);
+        }
+        (self.count_below(x) / total).clamp(0.0, 1.0)
+    }
+
+    /// Estimated count of `[dom.min, x]`, descending from the root: `depth + 1`
+    /// nodes at most.
+    fn count_below(&self, x: f64) -> f64 {
+        if x <= self.dom.min {
+            return 0.0;
+        }
+        if x >= self.dom.max {
+            return self.total();
+        }
+        let (mut lo, mut hi) = (self.dom.min, self.dom.max);
+        let mut j = 0;
+        let mut acc = 0.0;
+        for l in 1..self.levels.len() {
+            let mid = lo + (hi - lo) / 2.0;
+            if x < mid {
+                hi = mid;
+                j *= 2;
+            } else {
+                acc += self.levels[l][2 * j];
+                lo = mid;
+                j = 2 * j + 1;
+            }
+        }
+        acc + self.levels[self.depth()][j] * (x - lo) / (hi - lo)
+    }
+}
+
+/// Laplace tree histogram with least-squares consistency; see the [module docs](self).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DpTreeHistogram {
+    epsilon: f64,
+}
+
+impl DpTreeHistogram {
+    /// ε-DP in total over all levels.
+    pub fn new(epsilon: f64) -> Self { Self { epsilon } }
+
+    /// The guarantee a release of a tree with `depth` levels below the root spends.
+    pub fn guarantee(&self, depth: usize) -> Result<Guarantee, MechError> {
+        if self.epsilon <= 0.0 {
+            return Err(MechError::InvalidParam("epsilon must be > 0"));
+        }
+        if depth > MAX_DEPTH {
+            return Err(MechError::InvalidParam("depth must be at most 24"));
+        }
+        let sensitivity = (depth + 1) as f64;
+        Ok(Guarantee { mechanism: "laplace_tree", epsilon: self.epsilon, delta: 0.0, sensitivity, scale: sensitivity / self.epsilon })
+    }
+
+    /// Builds the tree of `src` with `2^depth` leaves over `dom` and releases it.
+    pub fn release<S: ScalarStream>(
+        &self,
+        mut src: S,
+        dom: BoundedF64,
+        depth: usize,
+        seed: Option<u64>,
+    ) -> Result<TreeHistogram, MechError> {
+        self.guarantee(depth)?;
+        let mut state = HistogramState::new(dom, 1 << depth);
+        state.feed(&mut src, usize::MAX)?;
+        self.release_state(state, seed)
+    }
+
+    /// Releases a (possibly resumed or merged) [`HistogramState`] as the leaves of
+    /// a tree; its number of bins must be a power of two.
+    ///
+    /// The state is consumed: this is the single point where noise is added.
+    pub fn release_state(&self, state: HistogramState, seed: Option<u64>) -> Result<TreeHistogram, MechError> {
+        let leaves: Vec<f64> = state.result().into_iter().map(|(_, _, c)| c as f64).collect();
+        if !leaves.len().is_power_of_two() {
+            return Err(MechError::InvalidParam("number of bins must be a power of two"));
+        }
+        let depth = leaves.len().trailing_zeros() as usize;
+        let guarantee = self.guarantee(depth)?;
+
+        // True counts per level, root first.
+        let mut levels = vec![leaves];
+        while levels[0].len() > 1 {
+            let parent = levels[0].chunks(2).map(|c| c[0] + c[1]).collect();
+            levels.insert(0, parent);
+        }
+        let mut rng = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        for level in &mut levels {
+            level.iter_mut().for_each(|c| *c += sample_laplace(&mut rng, guarantee.scale));
+        }
+
+        Ok(TreeHistogram { dom: state.dom, levels: consistent(levels), guarantee })
+    }
+}
+
+/// Least-squares consistent counts for a binary tree of equally noisy counts
+/// (Hay et al. 2010, Theorem 3).
+fn consistent(noisy: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let depth = noisy.len() - 1;
+
+    // Bottom-up: weighted average of a node and the sum of its children, where
+    // a node at height i (leaves: 1) weighs (2^i - 2^(i-1)) / (2^i - 1).
+    let mut z = noisy.clone();
+    for l in (0..depth).rev() {
+        let p = 2f64.powi((depth - l + 1) as i32);
+        let (a, b) = ((p - p / 2.0) / (p - 1.0), (p / 2.0 - 1.0) / (p - 1.0));
+        for j in 0..z[l].len() {
+            z[l][j] = a * noisy[l][j] + b * (z[l + 1][2 * j] + z[l + 1][2 * j + 1]);
+        }
+    }
+
+    // Top-down: split each parent's difference to its children's sum evenly.
+    let mut h = z.clone();
+    for l in 1..=depth {
+        for j in 0..h[l].len() {
+            let siblings = z[l][j & !1] + z[l][j | 1];
+            h[l][j] = z[l][j] + (h[l - 1][j / 2] - siblings) / 2.0;
+        }
+    }
+    h
+}
@@ -0,0 +1,12 @@
+// mechanisms/tests/common/mod.rs
+//! Seeded sample generators shared by the integration tests.
+
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+/// `n` samples of N(50, 10²); its quantiles are `50 + 10·z_q`.
+pub fn normal(n: usize, seed: u64) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let dist = Normal::new(50.0, 10.0).unwrap();
+    (0..n).map(|_| dist.sample(&mut rng)).collect()
+}
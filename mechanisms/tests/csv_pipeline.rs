@@ -0,0 +1,100 @@
+// mechanisms/tests/csv_pipeline.rs
+//! End-to-end pipelines built from the public APIs of data-layer, preprocessing and mechanisms.
+
+use std::io::Write;
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+use preprocessing::prelude::Clip;
+use tempfile::NamedTempFile;
+
+fn sales_csv() -> NamedTempFile {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "store,amount").unwrap();
+    for (store, amount) in [("a", 12.0), ("b", -3.0), ("c", 250.0), ("d", 40.5)] {
+        writeln!(tmp, "{},{}", store, amount).unwrap();
+    }
+    tmp
+}
+
+#[test]
+fn csv_clip_dp_sum() {
+    let tmp = sales_csv();
+    let dom = BoundedF64::new(0.0, 100.0);
+    let src = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let clipped = Clip::new(src, dom.min, dom.max);
+
+    // Clipped values: 12, 0, 100, 40.5.
+    let exact = 152.5;
+    let noisy = DpSum::laplace(clipped, l1_sens_sum(dom), 1.0, Some(42)).unwrap();
+    assert_ne!(noisy, exact);
+    // Laplace(b = 100) noise: a deviation beyond 20·b has probability e^-20.
+    assert!((noisy - exact).abs() < 20.0 * laplace_b(l1_sens_sum(dom), 1.0));
+
+    // The same seed gives the same release.
+    let src = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let again = DpSum::laplace(Clip::new(src, dom.min, dom.max), l1_sens_sum(dom), 1.0, Some(42)).unwrap();
+    assert_eq!(noisy, again);
+}
+
+#[test]
+fn csv_parse_error_surfaces_as_upstream() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    writeln!(tmp, "amount\n1\nnot-a-number\n3").unwrap();
+    let src = CsvScalarStream::from_path_with(tmp.path(), CsvOptions::by_name("amount")).unwrap();
+    let err = DpSum::laplace(Clip::new(src, 0.0, 10.0), 10.0, 1.0, Some(1)).unwrap_err();
+    match err {
+        MechError::Upstream(DataError::Parse { loc, .. }) => assert_eq!(loc.record, Some(2)),
+        other => panic!("expected upstream parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn resumed_sum_is_released_once() {
+    let tmp = sales_csv();
+    let dom = BoundedF64::new(0.0, 100.0);
+    let opts = || CsvOptions::by_name("amount");
+
+    // First run stops after two rows and checkpoints; the second resumes.
+    let mut src = CsvScalarStream::from_path_with(tmp.path(), opts()).unwrap();
+    let mut state = SumState::new(dom);
+    assert!(!state.feed(&mut src, 2).unwrap());
+    let ck = Checkpoint::new(state, src.position());
+
+    let mut src = CsvScalarStream::resume_with(tmp.path(), opts(), &ck.source).unwrap();
+    let mut state = ck.state;
+    assert!(state.feed(&mut src, usize::MAX).unwrap());
+    assert_eq!(state.result(), (152.5, 4));
+
+    let resumed = DpSum::laplace_release(state, l1_sens_sum(dom), 1.0, Some(42)).unwrap();
+    let src = CsvScalarStream::from_path_with(tmp.path(), opts()).unwrap();
+    let single = DpSum::laplace(Clip::new(src, dom.min, dom.max), l1_sens_sum(dom), 1.0, Some(42)).unwrap();
+    assert_eq!(resumed, single);
+}
+
+#[test]
+fn direct_and_state_releases_sum_exactly() {
+    // Naive left-to-right summation gives 1 here; the exact sum is 2.
+    let values = vec![1e16, 1.0, -1e16, 1.0];
+    let dom = BoundedF64::new(-1e16, 1e16);
+    let mut state = SumState::new(dom);
+    state.feed(&mut VecStream::from(values.clone()), usize::MAX).unwrap();
+    assert_eq!(state.result(), (2.0, 4));
+
+    let direct = DpSum::laplace(VecStream::from(values.clone()), 1.0, 1.0, Some(3)).unwrap();
+    assert_eq!(direct, DpSum::laplace_release(state.clone(), 1.0, 1.0, Some(3)).unwrap());
+
+    let direct = DpMean::gaussian(VecStream::from(values), 1.0, 0.5, 1e-6, 4, Some(3)).unwrap();
+    assert_eq!(direct, DpMean::gaussian_release(state, 1.0, 0.5, 1e-6, 4, Some(3)).unwrap());
+}
+
+#[test]
+fn non_finite_values_are_rejected() {
+    for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let values = vec![1.0, bad, 2.0];
+        let sum = DpSum::laplace(VecStream::from(values.clone()), 1.0, 1.0, Some(3));
+        assert!(matches!(sum, Err(MechError::InvalidParam(_))), "{bad}: {sum:?}");
+        let mean = DpMean::gaussian(VecStream::from(values), 1.0, 0.5, 1e-6, 3, Some(3));
+        assert!(matches!(mean, Err(MechError::InvalidParam(_))), "{bad}: {mean:?}");
+    }
+}
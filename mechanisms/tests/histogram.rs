@@ -0,0 +1,104 @@
+// mechanisms/tests/histogram.rs
+//! Noise distributions, post-processing and metadata of DP histograms.
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+
+/// 1000 records: 100 in each of ten unit buckets over [0, 10].
+fn data() -> Vec<f64> {
+    (0..1000).map(|i| (i % 10) as f64 + 0.5).collect()
+}
+
+fn dom() -> BoundedF64 { BoundedF64::new(0.0, 10.0) }
+
+#[test]
+fn edges_counts_and_guarantee() {
+    let hist = DpHistogram::laplace(0.5).release(VecStream::from(data()), dom(), 10, Some(1)).unwrap();
+    assert_eq!(hist.edges, (0..=10).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(hist.counts.len(), 10);
+    assert!(hist.counts.iter().all(|c| (c - 100.0).abs() < 30.0), "{:?}", hist.counts);
+    assert_eq!(hist.guarantee, Guarantee { mechanism: "laplace", epsilon: 0.5, delta: 0.0, sensitivity: 1.0, scale: 2.0 });
+
+    let g = DpHistogram::gaussian(0.5, 1e-6).guarantee().unwrap();
+    assert_eq!((g.mechanism, g.delta, g.sensitivity), ("gaussian", 1e-6, 1.0));
+    assert!(g.scale > 2.0);
+}
+
+#[test]
+fn noise_has_the_calibrated_spread() {
+    // Empirical variance of noisy counts around the true count 100.
+    let variance = |h: DpHistogram| {
+        let counts: Vec<f64> = (0..200)
+            .flat_map(|seed| h.release(VecStream::from(data()), dom(), 10, Some(seed)).unwrap().counts)
+            .collect();
+        counts.iter().map(|c| (c - 100.0).powi(2)).sum::<f64>() / counts.len() as f64
+    };
+    // Laplace(b): 2b²; discrete Laplace: 2q/(1-q)², q = e^-ε; Gaussian: σ².
+    let v = variance(DpHistogram::laplace(1.0));
+    assert!((v - 2.0).abs() < 0.3, "laplace {v}");
+    let q = (-1.0f64).exp();
+    let v = variance(DpHistogram::discrete_laplace(1.0));
+    assert!((v - 2.0 * q / (1.0 - q).powi(2)).abs() < 0.3, "discrete laplace {v}");
+    let sigma = DpHistogram::gaussian(0.5, 1e-5).guarantee().unwrap().scale;
+    let v = variance(DpHistogram::gaussian(0.5, 1e-5));
+    assert!((v / sigma.powi(2) - 1.0).abs() < 0.1, "gaussian {v} vs {}", sigma.powi(2));
+}
+
+#[test]
+fn discrete_noise_and_rounding_give_non_negative_integers() {
+    let sparse = vec![0.5; 3];
+    for h in [DpHistogram::discrete_laplace(0.5), DpHistogram::laplace(0.5).round(true)] {
+        let hist = h.release(VecStream::from(sparse.clone()), dom(), 10, Some(4)).unwrap();
+        assert!(hist.counts.iter().all(|c| c.fract() == 0.0), "{:?}", hist.counts);
+    }
+    let hist = DpHistogram::laplace(0.5).round(true).release(VecStream::from(sparse), dom(), 10, Some(4)).unwrap();
+    assert!(hist.counts.iter().all(|&c| c >= 0.0));
+}
+
+#[test]
+fn normalized_counts_form_a_distribution() {
+    let hist = DpHistogram::gaussian(0.8, 1e-6).normalize(true)
+        .release(VecStream::from(data()), dom(), 10, Some(2))
+        .unwrap();
+    assert!((hist.counts.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(hist.counts.iter().all(|&p| (0.0..=1.0).contains(&p)));
+
+    // Whatever the noise, normalized counts sum to 1, also on empty input.
+    for seed in 0..50 {
+        let hist = DpHistogram::laplace(1.0).normalize(true).release(VecStream::from(vec![]), dom(), 4, Some(seed)).unwrap();
+        assert!((hist.counts.iter().sum::<f64>() - 1.0).abs() < 1e-9, "{:?}", hist.counts);
+    }
+
+    // All counts zero: with Laplace(1/200) noise, rounding gives exactly 0 in
+    // every bucket (|noise| ≥ 0.5 has probability e^-100), so the output is uniform.
+    for seed in 0..10 {
+        let hist = DpHistogram::laplace(200.0).round(true).normalize(true)
+            .release(VecStream::from(vec![]), dom(), 4, Some(seed))
+            .unwrap();
+        assert_eq!(hist.counts, vec![0.25; 4]);
+    }
+}
+
+#[test]
+fn releasing_a_state_matches_a_direct_release() {
+    let mut state = HistogramState::new(dom(), 10);
+    state.feed(&mut VecStream::from(data()), usize::MAX).unwrap();
+    let h = DpHistogram::discrete_laplace(1.0);
+    assert_eq!(h.release_state(state, Some(9)).unwrap(), h.release(VecStream::from(data()), dom(), 10, Some(9)).unwrap());
+}
+
+#[test]
+fn parameters_are_checked() {
+    for h in [
+        DpHistogram::laplace(0.0),
+        DpHistogram::discrete_laplace(-1.0),
+        DpHistogram::gaussian(0.5, 0.0),
+        DpHistogram::gaussian(0.5, 1.0),
+        DpHistogram::gaussian(1.0, 1e-6),
+        DpHistogram::gaussian(3.0, 1e-6),
+        DpHistogram::gaussian(0.0, 1e-6),
+    ] {
+        let res = h.release(VecStream::from(data()), dom(), 10, None);
+        assert!(matches!(res, Err(MechError::InvalidParam(_))), "{h:?}");
+    }
+}
@@ -0,0 +1,88 @@
+// mechanisms/tests/quantiles.rs
+//! Accuracy of the DP quantile estimators on samples from known distributions.
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod common;
+use common::normal;
+
+const N: usize = 10_000;
+
+fn uniform(seed: u64) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..N).map(|_| rng.gen::<f64>() * 100.0).collect()
+}
+
+const QS: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+const Z: [f64; 5] = [-1.2816, -0.6745, 0.0, 0.6745, 1.2816];
+
+#[test]
+fn median_of_uniform_data() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    for seed in 0..20 {
+        let m = DpQuantile::median(VecStream::from(uniform(seed)), dom, 1.0, Some(seed)).unwrap();
+        assert!((m - 50.0).abs() < 2.0, "seed {seed}: median {m}");
+    }
+}
+
+#[test]
+fn joint_quantiles_of_normal_data() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    for seed in 0..10 {
+        let est = DpQuantile::exponential_many(VecStream::from(normal(N, seed)), dom, &QS, 1.0, Some(seed)).unwrap();
+        assert!(est.windows(2).all(|w| w[0] <= w[1]), "not sorted: {est:?}");
+        for (e, z) in est.iter().zip(Z) {
+            assert!((e - (50.0 + 10.0 * z)).abs() < 1.5, "seed {seed}: {est:?}");
+        }
+    }
+}
+
+#[test]
+fn histogram_quantiles_use_bounded_memory() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    for seed in 0..10 {
+        let est = DpQuantile::histogram(VecStream::from(normal(N, seed)), dom, 200, &QS, 1.0, Some(seed)).unwrap();
+        assert!(est.windows(2).all(|w| w[0] <= w[1]), "not sorted: {est:?}");
+        for (e, z) in est.iter().zip(Z) {
+            assert!((e - (50.0 + 10.0 * z)).abs() < 1.5, "seed {seed}: {est:?}");
+        }
+    }
+
+    // Releasing a state built elsewhere (e.g. merged from a parallel scan) gives the same result.
+    let mut state = HistogramState::new(dom, 200);
+    state.feed(&mut VecStream::from(uniform(1)), usize::MAX).unwrap();
+    let released = DpQuantile::histogram_release(state, &[0.5], 1.0, Some(7)).unwrap();
+    let direct = DpQuantile::histogram(VecStream::from(uniform(1)), dom, 200, &[0.5], 1.0, Some(7)).unwrap();
+    assert_eq!(released, direct);
+    assert!((released[0] - 50.0).abs() < 2.0);
+}
+
+#[test]
+fn values_are_clamped_and_empty_input_is_defined() {
+    let dom = BoundedF64::new(0.0, 10.0);
+    let data: Vec<f64> = (0..1000).map(|i| if i % 2 == 0 { -1e9 } else { 1e9 }).collect();
+    // Clamped to 0 and 10: the only gap of positive width lies between them.
+    let q = DpQuantile::exponential_many(VecStream::from(data), dom, &[0.25, 0.75], 1.0, Some(3)).unwrap();
+    assert!(q.iter().all(|v| (0.0..=10.0).contains(v)) && q[0] <= q[1], "{q:?}");
+
+    // No data: the output is uniform over the domain, still ε-DP.
+    let m = DpQuantile::median(VecStream::from(vec![]), dom, 1.0, Some(3)).unwrap();
+    assert!((0.0..=10.0).contains(&m));
+    let m = DpQuantile::histogram(VecStream::from(vec![]), dom, 10, &[0.5], 1.0, Some(3)).unwrap();
+    assert!((0.0..=10.0).contains(&m[0]));
+}
+
+#[test]
+fn seeded_releases_repeat_and_parameters_are_checked() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    let a = DpQuantile::exponential(VecStream::from(uniform(5)), dom, 0.9, 0.5, Some(11)).unwrap();
+    let b = DpQuantile::exponential(VecStream::from(uniform(5)), dom, 0.9, 0.5, Some(11)).unwrap();
+    assert_eq!(a, b);
+
+    for (qs, eps) in [(&[0.5][..], 0.0), (&[1.5][..], 1.0), (&[0.9, 0.1][..], 1.0), (&[][..], 1.0)] {
+        let res = DpQuantile::exponential_many(VecStream::from(vec![1.0]), dom, qs, eps, None);
+        assert!(matches!(res, Err(MechError::InvalidParam(_))), "{qs:?}, {eps}");
+    }
+}
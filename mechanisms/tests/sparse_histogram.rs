@@ -0,0 +1,93 @@
+// mechanisms/tests/sparse_histogram.rs
+//! Key selection, per-unit bounding and thresholds of sparse categorical histograms.
+
+use std::fmt::Write as _;
+use std::io::Write;
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+use tempfile::NamedTempFile;
+
+/// A `user,url` CSV file from `(user, url)` rows.
+fn visits(rows: impl IntoIterator<Item = (String, &'static str)>) -> NamedTempFile {
+    let mut text = String::from("user,url\n");
+    for (u, k) in rows {
+        writeln!(text, "{u},{k}").unwrap();
+    }
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(text.as_bytes()).unwrap();
+    tmp
+}
+
+fn open(tmp: &NamedTempFile) -> CsvRecordStream {
+    CsvRecordStream::from_path(tmp.path(), RecordSchema::new().str("user").str("url"), b',', true).unwrap()
+}
+
+fn get(hist: &SparseHistogram, key: &str) -> Option<f64> {
+    hist.counts.iter().find(|(k, _)| k == key).map(|&(_, c)| c)
+}
+
+#[test]
+fn frequent_keys_are_released_and_rare_ones_suppressed() {
+    let tmp = visits(
+        (0..500).map(|i| (format!("u{i}"), "/home"))
+            .chain((0..300).map(|i| (format!("v{i}"), "/cart")))
+            .chain([("w".to_string(), "/secret")]),
+    );
+    let dp = DpSparseHistogram::new(1.0, 1e-6);
+    assert!((dp.threshold().unwrap() - (1.0 + (0.5e6f64).ln())).abs() < 1e-9);
+    for seed in 0..20 {
+        let hist = dp.release(open(&tmp), "user", "url", Selection::FirstK, Some(seed)).unwrap();
+        assert_eq!(hist.counts.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(), ["/cart", "/home"]);
+        assert!((get(&hist, "/home").unwrap() - 500.0).abs() < 15.0);
+        assert!((get(&hist, "/cart").unwrap() - 300.0).abs() < 15.0);
+    }
+}
+
+#[test]
+fn units_contribute_once_per_key_and_to_at_most_k_keys() {
+    // One heavy user repeats a key 1000 times: it still counts once.
+    let tmp = visits((0..1000).map(|_| ("bot".to_string(), "/spam")));
+    let hist = DpSparseHistogram::new(1.0, 1e-6).release(open(&tmp), "user", "url", Selection::FirstK, Some(1)).unwrap();
+    assert!(hist.counts.is_empty(), "{:?}", hist.counts);
+
+    // 300 users visit /a, /b, /c in that order; k = 2.
+    let tmp = visits((0..300).flat_map(|i| ["/a", "/b", "/c", "/a"].map(|k| (format!("u{i}"), k))));
+    let dp = DpSparseHistogram::new(2.0, 1e-6).max_keys(2);
+    assert_eq!(dp.guarantee().unwrap().scale, 1.0);
+
+    let first = dp.release(open(&tmp), "user", "url", Selection::FirstK, Some(2)).unwrap();
+    assert!((get(&first, "/a").unwrap() - 300.0).abs() < 10.0);
+    assert!((get(&first, "/b").unwrap() - 300.0).abs() < 10.0);
+    assert_eq!(get(&first, "/c"), None);
+
+    // Uniform choice of 2 out of 3 keys: about 200 units each.
+    let sampled = dp.release(open(&tmp), "user", "url", Selection::Reservoir { seed: Some(3) }, Some(2)).unwrap();
+    for key in ["/a", "/b", "/c"] {
+        assert!((get(&sampled, key).unwrap() - 200.0).abs() < 40.0, "{:?}", sampled.counts);
+    }
+    let total: f64 = sampled.counts.iter().map(|&(_, c)| c).sum();
+    assert!((total - 600.0).abs() < 10.0);
+    let again = dp.release(open(&tmp), "user", "url", Selection::Reservoir { seed: Some(3) }, Some(2)).unwrap();
+    assert_eq!(sampled, again);
+}
+
+#[test]
+fn null_units_missing_columns_and_parameters_are_errors() {
+    let tmp = visits([("a".to_string(), "/x"), ("".to_string(), "/y")]);
+    let dp = DpSparseHistogram::new(1.0, 1e-6);
+    let res = dp.release(open(&tmp), "user", "url", Selection::FirstK, None);
+    assert!(matches!(res, Err(MechError::Upstream(DataError::MissingKey { .. }))), "{res:?}");
+    let res = dp.release(open(&tmp), "user", "path", Selection::FirstK, None);
+    assert!(matches!(res, Err(MechError::Upstream(DataError::MissingKey { .. }))), "{res:?}");
+
+    for dp in [
+        DpSparseHistogram::new(0.0, 1e-6),
+        DpSparseHistogram::new(1.0, 0.0),
+        DpSparseHistogram::new(1.0, 1.0),
+        DpSparseHistogram::new(1.0, 1e-6).max_keys(0),
+    ] {
+        let res = dp.release(open(&tmp), "user", "url", Selection::FirstK, None);
+        assert!(matches!(res, Err(MechError::InvalidParam(_))), "{dp:?}");
+    }
+}
@@ -0,0 +1,87 @@
+// mechanisms/tests/tree_histogram.rs
+//! Consistency and range/CDF accuracy of hierarchical histograms.
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const N: usize = 20_000;
+
+/// Skewed data on [0, 100): most of the mass near 0.
+fn skewed(seed: u64) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..N).map(|_| rng.gen::<f64>().powi(2) * 100.0).collect()
+}
+
+fn dom() -> BoundedF64 { BoundedF64::new(0.0, 100.0) }
+
+fn true_range(xs: &[f64], lo: f64, hi: f64) -> f64 {
+    xs.iter().filter(|&&x| lo <= x && x < hi).count() as f64
+}
+
+#[test]
+fn released_counts_are_consistent() {
+    let tree = DpTreeHistogram::new(0.5).release(VecStream::from(skewed(1)), dom(), 6, Some(1)).unwrap();
+    assert_eq!(tree.depth(), 6);
+    assert_eq!(tree.leaves().len(), 64);
+    assert_eq!(tree.edges().len(), 65);
+    assert_eq!(*tree.guarantee(), Guarantee { mechanism: "laplace_tree", epsilon: 0.5, delta: 0.0, sensitivity: 7.0, scale: 14.0 });
+    for l in 0..tree.depth() {
+        for (j, parent) in tree.levels()[l].iter().enumerate() {
+            let children = tree.levels()[l + 1][2 * j] + tree.levels()[l + 1][2 * j + 1];
+            assert!((parent - children).abs() < 1e-6, "level {l}, node {j}");
+        }
+    }
+}
+
+#[test]
+fn range_counts_have_polylog_error() {
+    // Leaves of width 100/1024; ranges on leaf edges so that only noise matters.
+    let width = 100.0 / 1024.0;
+    let mut rng = StdRng::seed_from_u64(99);
+    let mut worst: f64 = 0.0;
+    for seed in 0..10 {
+        let xs = skewed(seed);
+        let tree = DpTreeHistogram::new(1.0).release(VecStream::from(xs.clone()), dom(), 10, Some(seed)).unwrap();
+        for _ in 0..50 {
+            let (a, b) = (rng.gen_range(0..=1024), rng.gen_range(0..=1024));
+            let (lo, hi) = (a.min(b) as f64 * width, a.max(b) as f64 * width);
+            worst = worst.max((tree.range_count(lo, hi) - true_range(&xs, lo, hi)).abs());
+        }
+        assert!((tree.total() - N as f64).abs() < 100.0);
+        assert_eq!(tree.range_count(60.0, 30.0), 0.0);
+    }
+    // Per node Laplace(11); a range uses at most 20 nodes: standard deviation < 70.
+    assert!(worst < 250.0, "worst range error {worst}");
+}
+
+#[test]
+fn cdf_tracks_the_empirical_distribution() {
+    let xs = skewed(4);
+    let tree = DpTreeHistogram::new(1.0).release(VecStream::from(xs.clone()), dom(), 8, Some(4)).unwrap();
+    assert_eq!(tree.cdf(-5.0), 0.0);
+    assert_eq!(tree.cdf(100.0), 1.0);
+    for x in [1.0, 10.0, 25.0, 50.0, 81.0, 99.0] {
+        // P(100·U² ≤ x) = sqrt(x / 100).
+        assert!((tree.cdf(x) - (x / 100.0).sqrt()).abs() < 0.02, "cdf({x}) = {}", tree.cdf(x));
+    }
+
+    // Empty input: the noisy total may be ≤ 0, the CDF stays within [0, 1].
+    let empty = DpTreeHistogram::new(1.0).release(VecStream::from(vec![]), dom(), 4, Some(4)).unwrap();
+    assert!((0..=100).all(|x| (0.0..=1.0).contains(&empty.cdf(x as f64))));
+}
+
+#[test]
+fn states_release_like_streams_and_parameters_are_checked() {
+    let mut state = HistogramState::new(dom(), 256);
+    state.feed(&mut VecStream::from(skewed(2)), usize::MAX).unwrap();
+    let dp = DpTreeHistogram::new(1.0);
+    assert_eq!(dp.release_state(state, Some(5)).unwrap(), dp.release(VecStream::from(skewed(2)), dom(), 8, Some(5)).unwrap());
+
+    let res = dp.release_state(HistogramState::new(dom(), 100), None);
+    assert!(matches!(res, Err(MechError::InvalidParam(_))));
+    let res = DpTreeHistogram::new(0.0).release(VecStream::from(vec![1.0]), dom(), 4, None);
+    assert!(matches!(res, Err(MechError::InvalidParam(_))));
+    let res = dp.release(VecStream::from(vec![1.0]), dom(), 40, None);
+    assert!(matches!(res, Err(MechError::InvalidParam(_))));
+}
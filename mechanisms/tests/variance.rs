@@ -0,0 +1,71 @@
+// mechanisms/tests/variance.rs
+//! DP variance and standard deviation: accuracy, post-processing and budget checks.
+
+use data_layer::prelude::*;
+use mechanisms::prelude::*;
+
+mod common;
+use common::normal;
+
+#[test]
+fn std_dev_of_normal_data() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    for seed in 0..10 {
+        let s = DpVariance::laplace(VecStream::from(normal(100_000, seed)), dom, VarianceBudget::even(1.0), Some(seed)).unwrap();
+        assert!((s.std_dev - 10.0).abs() < 0.5, "seed {seed}: {s:?}");
+        assert!((s.mean - 50.0).abs() < 0.5, "seed {seed}: {s:?}");
+        assert!((s.count - 100_000.0).abs() < 50.0, "seed {seed}: {s:?}");
+        assert_eq!(s.variance.sqrt(), s.std_dev);
+    }
+}
+
+#[test]
+fn post_processing_keeps_the_result_valid() {
+    // Constant data and a tiny budget: the raw noisy variance is often negative.
+    let dom = BoundedF64::new(-5.0, 15.0);
+    for seed in 0..50 {
+        let s = DpVariance::laplace(VecStream::from(vec![5.0; 10]), dom, VarianceBudget::even(0.1), Some(seed)).unwrap();
+        assert!((0.0..=100.0).contains(&s.variance), "{s:?}");
+        assert!((-5.0..=15.0).contains(&s.mean), "{s:?}");
+        assert!(s.count >= 1.0);
+    }
+
+    // Values outside the domain are clamped before centering.
+    let s = DpVariance::laplace(VecStream::from(vec![-1e6, 1e6]), dom, VarianceBudget::even(1e6), Some(1)).unwrap();
+    assert!((s.variance - 100.0).abs() < 1e-3, "{s:?}");
+}
+
+#[test]
+fn budget_is_explicit() {
+    let dom = BoundedF64::new(0.0, 1.0);
+    let budget = VarianceBudget::new(0.1, 0.45, 0.45);
+    assert!((budget.total() - 1.0).abs() < 1e-12);
+    assert!((VarianceBudget::even(0.9).total() - 0.9).abs() < 1e-12);
+
+    let a = DpVariance::laplace(VecStream::from(vec![0.2, 0.4]), dom, budget, Some(9)).unwrap();
+    let b = DpVariance::laplace(VecStream::from(vec![0.2, 0.4]), dom, budget, Some(9)).unwrap();
+    assert_eq!(a, b);
+
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        for budget in [VarianceBudget::new(bad, 1.0, 1.0), VarianceBudget::new(1.0, bad, 1.0), VarianceBudget::new(1.0, 1.0, bad)] {
+            let res = DpVariance::laplace(VecStream::from(vec![0.5]), dom, budget, None);
+            assert!(matches!(res, Err(MechError::InvalidParam(_))), "{budget:?}: {res:?}");
+        }
+    }
+}
+
+#[test]
+fn merged_states_release_like_a_single_pass() {
+    let dom = BoundedF64::new(0.0, 100.0);
+    let values = normal(1_000, 4);
+    let direct = DpVariance::laplace(VecStream::from(values.clone()), dom, VarianceBudget::even(1.0), Some(4)).unwrap();
+
+    let (a, b) = values.split_at(300);
+    let mut state = VarianceState::new(dom);
+    state.feed(&mut VecStream::from(a.to_vec()), usize::MAX).unwrap();
+    let mut rest = VarianceState::new(dom);
+    rest.feed(&mut VecStream::from(b.to_vec()), usize::MAX).unwrap();
+    state.merge(rest);
+    assert_eq!(state.sum.n, 1_000);
+    assert_eq!(DpVariance::laplace_release(state, VarianceBudget::even(1.0), Some(4)).unwrap(), direct);
+}
@@ -1,5 +1,6 @@
 //! Clamps numeric values to a fixed interval.
 
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// Restricts each upstream value `v` to the range `[lo, hi]`.
@@ -17,7 +18,7 @@ impl<S> ScalarStream for Clip<S>
 where
     S: ScalarStream,
 {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         self.src.next_val().map(|r| r.map(|v| v.clamp(self.lo, self.hi)))
     }
 }
@@ -1,5 +1,6 @@
 //! Filters a stream by a boolean predicate.
 
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// Passes through only values satisfying a predicate function.
@@ -17,9 +18,9 @@ where
     S: ScalarStream,
     P: Fn(f64) -> bool + Send + Sync + 'static,
 {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         loop {
-            let Some(res) = self.src.next_val() else { return None; };
+            let res = self.src.next_val()?;
             match res {
                 Ok(v) if (self.pred)(v) => return Some(Ok(v)),
                 Ok(_) => continue,
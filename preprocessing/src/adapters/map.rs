@@ -1,5 +1,6 @@
 //! Applies a user-defined function to each value in a stream.
 
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// Transforms each upstream value using a closure `f(v)`.
@@ -17,7 +18,7 @@ where
     S: ScalarStream,
     F: Fn(f64) -> f64 + Send + Sync + 'static,
 {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         self.src.next_val().map(|r| r.map(|v| (self.f)(v)))
     }
 }
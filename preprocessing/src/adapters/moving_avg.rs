@@ -1,5 +1,6 @@
 //! Computes a simple moving average over a fixed-size window.
 
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 use std::collections::VecDeque;
 
@@ -22,8 +23,8 @@ impl<S> ScalarStream for MovingAverage<S>
 where
     S: ScalarStream,
 {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
-        let Some(res) = self.src.next_val() else { return None; };
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
+        let res = self.src.next_val()?;
         match res {
             Ok(v) => {
                 self.buf.push_back(v);
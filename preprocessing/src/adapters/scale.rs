@@ -1,5 +1,6 @@
 //! Applies an affine transformation to each stream element.
 
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// Performs a linear scaling and offset using `v ↦ a*v + b`.
@@ -17,7 +18,7 @@ impl<S> ScalarStream for Scale<S>
 where
     S: ScalarStream,
 {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         self.src.next_val().map(|r| r.map(|v| self.a * v + self.b))
     }
 }
@@ -1,5 +1,6 @@
 //! Error types used throughout the preprocessing layer.
 
+use data_layer::error::DataError;
 use thiserror::Error;
 
 /// High-level errors for preprocessing operations.
@@ -7,7 +8,7 @@ use thiserror::Error;
 pub enum PrepError {
     /// Error bubbled up from an upstream [`ScalarStream`].
     #[error("upstream stream error: {0}")]
-    Upstream(#[from] Box<dyn std::error::Error + Send + Sync>),
+    Upstream(#[from] DataError),
 
     /// Operation required more data than available.
     #[error("not enough data: {0}")]
@@ -7,6 +7,7 @@ pub mod prelude {
     pub use crate::error::PrepError;
 }
 
+#[cfg(test)]
 mod tests {
     mod test_adapters;
 }
\ No newline at end of file
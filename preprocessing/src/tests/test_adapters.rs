@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::error::*;
+use data_layer::error::DataError;
 use data_layer::stream::ScalarStream;
 
 /// A simple stream used for testing: emits a fixed sequence of f64 values.
@@ -10,7 +10,7 @@ impl FromVec {
     fn new(v: Vec<f64>) -> Self { Self { it: v.into_iter() } }
 }
 impl ScalarStream for FromVec {
-    fn next_val(&mut self) -> Option<Result<f64, Box<dyn std::error::Error + Send + Sync>>> {
+    fn next_val(&mut self) -> Option<Result<f64, DataError>> {
         self.it.next().map(Ok)
     }
 }
@@ -46,7 +46,7 @@ fn moving_average_smooths_values() {
     let mut ma = MovingAverage::new(src, 2);
     let out: Vec<_> = std::iter::from_fn(|| ma.next_val()).map(|r| r.unwrap()).collect();
     // expected running average with window=2 → [1, 1.5, 2.5, 3.5]
-    let expect = vec![1.0, 1.5, 2.5, 3.5];
+    let expect = [1.0, 1.5, 2.5, 3.5];
     for (a, b) in out.iter().zip(expect.iter()) {
         assert!((a - b).abs() < 1e-9, "expected {b}, got {a}");
     }